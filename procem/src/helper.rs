@@ -3,6 +3,8 @@ use core::{
     ops::Deref,
 };
 
+use crate::word::Word;
+
 /// A helper struct for formatting arrays.
 ///
 /// # Example:
@@ -40,6 +42,49 @@ impl<T: Display> Display for FmtArray<'_, T> {
     }
 }
 
+/// A helper struct for formatting a word in uppercase hexadecimal, zero-padded to the width of the word.
+///
+/// # Example:
+/// ```ignore
+/// assert_eq!(FmtHex(I8::from(-1)).to_string(), "FF");
+/// assert_eq!(FmtHex(I32::from(255)).to_string(), "000000FF");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FmtHex<W>(pub W);
+
+impl<W: Word> Display for FmtHex<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{:0width$X}", self.0, width = W::BITS.div_ceil(4) as usize)
+    }
+}
+
+/// A helper struct for formatting arrays of words in hexadecimal. See [`FmtHex`].
+///
+/// # Example:
+/// ```ignore
+/// let array = [I8::from(-1), I8::from(10)];
+/// assert_eq!(FmtHexArray(&array).to_string(), "[FF, 0A]");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FmtHexArray<'a, W>(pub &'a [W]);
+
+impl<W: Word> Display for FmtHexArray<'_, W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "[")?;
+
+        let mut iter = self.0.iter();
+        if let Some(val) = iter.next() {
+            write!(f, "{}", FmtHex(*val))?;
+
+            for val in iter {
+                write!(f, ", {}", FmtHex(*val))?;
+            }
+        }
+
+        write!(f, "]")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +104,21 @@ mod tests {
         let formatted = FmtArray(&array);
         assert_eq!(format!("{}", formatted), "[1, 2, 3]");
     }
+
+    #[test]
+    fn test_fmt_hex() {
+        use crate::word::{I4, I8, I32};
+
+        assert_eq!(FmtHex(I8::from(-1)).to_string(), "FF");
+        assert_eq!(FmtHex(I32::from(255)).to_string(), "000000FF");
+        assert_eq!(FmtHex(I4::from(-1)).to_string(), "F");
+    }
+
+    #[test]
+    fn test_fmt_hex_array() {
+        use crate::word::I8;
+
+        let array = [I8::from(-1), I8::from(10)];
+        assert_eq!(FmtHexArray(&array).to_string(), "[FF, 0A]");
+    }
 }
@@ -1,18 +1,85 @@
-//! The [`Instruction`] trait.
+//! The [`Instruction`] trait and the [`Handler`] type.
 
 use core::fmt::Debug;
 use core::ops::Deref;
 
-use crate::{processor::Processor, word::Word};
+use crate::{
+    processor::Processor,
+    register::FlagRegister,
+    stack::{Stack, StackMemory},
+    word::Word,
+};
+
+/// A function pointer that executes a single instruction on a processor.
+///
+/// A dispatch table is a slice of handlers with one handler per instruction of a [`Program`](crate::program::Program).
+/// It can be built with [`Program::dispatch_table()`](crate::program::Program::dispatch_table()) and
+/// executed with [`Processor::run_program_dispatched()`](Processor::run_program_dispatched()).
+pub type Handler<const STACK_SIZE: usize, I, P, W, S = Stack<STACK_SIZE, W>> =
+    fn(I, &mut Processor<'_, STACK_SIZE, I, P, W, S>);
 
 /// The [`Instruction`] trait is implemented by all instructions or instruction sets that can be executed by the processor.
 ///
 /// The [`procem_default`](../../procem_default/index.html) crate provides a default implementation of this trait using a custom instruction set.
 /// Its [`execute`](Instruction::execute) method is used by the processor to execute the instruction.
 pub trait Instruction<W: Word>: Debug + Copy + Eq + Ord {
+    /// The flags of the processor the instructions are executed on.
+    ///
+    /// Instruction sets that use the carry, signed, overflow and zero flags can use the default [`FlagSet`](crate::register::FlagSet).
+    /// Other instruction sets can define their own [`FlagRegister`] or use [`NoFlags`](crate::register::NoFlags).
+    type Flags: FlagRegister;
+
     /// This function is called when an instruction is executed by the processor.
-    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    ///
+    /// The instruction has to work with any stack implementing [`StackMemory`].
+    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         instruction: Self,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     );
+
+    /// Returns the [`Handler`] that executes this instruction.
+    ///
+    /// The default implementation returns [`execute`](Instruction::execute).
+    /// Instruction sets can return a specialized handler per opcode instead, so that a dispatch table
+    /// does not have to match on the instruction again every time it is executed.
+    #[must_use]
+    #[inline]
+    fn handler<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        &self,
+    ) -> Handler<STACK_SIZE, Self, P, W, S> {
+        Self::execute
+    }
+
+    /// Returns the assembly mnemonic of this instruction, e.g. `MOV` or `ADD`.
+    ///
+    /// The mnemonic is used to tell instructions apart without formatting them, e.g. in the profile of a processor
+    /// (see [`Processor::enable_profiling()`]).
+    /// The default implementation returns the placeholder `?` for every instruction,
+    /// so instruction sets should override this method to tell their instructions apart in the profile.
+    #[must_use]
+    #[inline]
+    fn mnemonic(&self) -> &'static str {
+        "?"
+    }
+
+    /// Returns the number of clock cycles it takes to execute this instruction.
+    ///
+    /// The processor adds up the cycles of all executed instructions, see [`Processor::cycle_count()`].
+    /// The default implementation returns 1 for every instruction.
+    /// Instruction sets can override this method to model instructions that take longer, e.g. multiplication or division.
+    #[must_use]
+    #[inline]
+    fn cycles(&self) -> u32 {
+        1
+    }
+
+    /// Returns the program address this instruction jumps to, if the address is known before execution.
+    ///
+    /// This is used by [`Program::validate()`](crate::program::Program::validate()) to find jump targets that are out of bounds.
+    /// The default implementation returns `None`, so instructions are not checked unless the instruction set overrides this method.
+    #[must_use]
+    #[inline]
+    fn jump_target(&self) -> Option<usize> {
+        None
+    }
 }
@@ -9,13 +9,14 @@
 //!
 //! The [`Registers`](register::Registers) and [`Stack`](stack::Stack) use [`Word`](word::Word) as their data type.
 //!
-//! The processor’s [`Registers`](register::Registers), [`Flags`](register::Flag) and [`Stack`](stack::Stack)
+//! The processor’s [`Registers`](register::Registers), [`Flags`](register::FlagRegister) and [`Stack`](stack::Stack)
 //! are directly accessible and modifiable through the [`Processor`](processor::Processor) structure.
 //!
 //! ```
-//! # use procem::register::{Flag, Register};
+//! # use procem::register::{Flag, FlagSet, Register};
 //! # use procem::processor::Processor;
 //! # use procem::instruction::Instruction;
+//! # use procem::stack::StackMemory;
 //! # use procem::word::{I32, Word};
 //! # use core::marker::PhantomData;
 //! # use core::ops::Deref;
@@ -24,9 +25,11 @@
 //! # struct Inst<W: Word> (PhantomData<W>);
 //! #
 //! # impl<W: Word> Instruction<W> for Inst<W> {
-//! #     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+//! #     type Flags = FlagSet;
+//! #
+//! #     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
 //! #         instruction: Self,
-//! #         processor: &mut Processor<STACK_SIZE, Self, P, W>
+//! #         processor: &mut Processor<STACK_SIZE, Self, P, W, S>
 //! #     ) {}
 //! # }
 //! #
@@ -43,7 +46,8 @@
 
 #![no_std]
 
-#[cfg(feature = "alloc")]
+// the tests use `alloc` to format values, even if the feature is disabled
+#[cfg(any(feature = "alloc", test))]
 extern crate alloc;
 
 pub mod instruction;
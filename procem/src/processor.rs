@@ -1,11 +1,19 @@
-//! The [`Processor`] and [`ProcessorBuilder`] structs.
+//! The [`Processor`] and [`ProcessorBuilder`] structs and the [`RunOutcome`] of running a program.
 use core::fmt::{Display, Formatter};
 use core::ops::Deref;
 
-use crate::instruction::Instruction;
+#[cfg(feature = "alloc")]
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    string::{String, ToString},
+};
+use thiserror::Error;
+
+use crate::instruction::{Handler, Instruction};
 use crate::program::{Program, ProgramError};
-use crate::register::{Register, Registers};
-use crate::stack::Stack;
+use crate::register::{FlagRegister, FlagSet, Register, Registers};
+use crate::stack::{Stack, StackError, StackGrowth, StackMemory};
 use crate::word::Word;
 
 /// The [`Processor`] is the main component of the emulator. It represents a simplified real world processor with a stack, registers and flags.
@@ -13,45 +21,269 @@ use crate::word::Word;
 /// It can store a singular [`Program`].
 /// It has [`GENERAL_REGISTER_COUNT`](crate::register::GENERAL_REGISTER_COUNT) general purpose [`register`](crate::register)s,
 /// a program counter ([`pc`](crate::register::Registers::pc)), a stack pointer ([`sp`](crate::register::Registers::sp))
-/// and 4 flags ([`C`](crate::register::Flag::C), [`S`](crate::register::Flag::S), [`V`](crate::register::Flag::V), [`Z`](crate::register::Flag::Z)).
+/// and the flags chosen by the instruction set, see [`Instruction::Flags`].
+/// By default these are 5 flags ([`C`](crate::register::Flag::C), [`S`](crate::register::Flag::S), [`V`](crate::register::Flag::V), [`Z`](crate::register::Flag::Z), [`P`](crate::register::Flag::P)).
 /// It also has a stack of size `STACK_SIZE`.
+/// Any other stack implementing [`StackMemory`] can be used instead by setting the stack type `S`,
+/// e.g. the growable [`DynStack`](crate::stack::DynStack). `STACK_SIZE` is ignored by such stacks.
+/// The stack grows upwards unless configured otherwise, see [`StackGrowth`].
 ///
 /// The processor can be created by using the [`builder()`](Processor::builder()) method or the [`ProcessorBuilder`] directly or by using the [`new()`](Processor::new()) method.
 /// Using the builder pattern allows specifying the initial registers, stack and program.
 /// Any unspecifed values will be initialized to their default values.
 /// Using the [`new()`](Processor::new()) method just creates a default processor.
 /// The program is then loaded using the [`load_program()`](Processor::load_program()) method.
+/// The processor can also take ownership of the program with [`load_program_owned()`](Processor::load_program_owned()),
+/// so that it does not borrow anything and can e.g. be returned from a function.
 ///
 /// To run a loaded program two methods are provided:
 /// - To run the entire program use [`run_program()`](Processor::run_program()).
+///   To guard against programs that never terminate use [`run_program_with_limit()`](Processor::run_program_with_limit()).
+///   To find out why and after how many instructions the program stopped use [`run_program_detailed()`](Processor::run_program_detailed()).
 /// - To run only the next instruction use [`execute_next_instruction()`](Processor::execute_next_instruction()).
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
-pub struct Processor<'a, const STACK_SIZE: usize, I, P, W: Word> {
-    pub registers: Registers<W>,
-    pub stack: Stack<STACK_SIZE, W>,
-    program: Option<&'a Program<I, P, W>>,
+///
+/// To run a fixed number of instructions use [`run_n()`](Processor::run_n()).
+/// The instruction that is executed next can be inspected with [`current_instruction()`](Processor::current_instruction()).
+/// The number of clock cycles the executed instructions took is returned by [`cycle_count()`](Processor::cycle_count()).
+/// With the `alloc` feature the executions of each kind of instruction can be counted, see [`enable_profiling()`](Processor::enable_profiling()).
+/// With the `alloc` feature executed instructions can also be undone with [`step_back()`](Processor::step_back())
+/// after enabling the history with [`with_history()`](Processor::with_history()).
+/// Which flags an instruction updates can be changed with a [`FlagPolicy`], see [`update_flag()`](Processor::update_flag()).
+/// Handlers of the host can be connected to I/O ports with [`connect_input()`](Processor::connect_input())
+/// and [`connect_output()`](Processor::connect_output()), e.g. to emulate a console.
+///
+/// Both methods also have a variant that executes through a precomputed dispatch table instead of matching on every instruction:
+/// [`run_program_dispatched()`](Processor::run_program_dispatched()) and
+/// [`execute_next_instruction_dispatched()`](Processor::execute_next_instruction_dispatched()).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Processor<'a, const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S = Stack<STACK_SIZE, W>> {
+    pub registers: Registers<W, I::Flags>,
+    pub stack: S,
+    program: Option<LoadedProgram<'a, I, P, W>>,
+    stack_growth: StackGrowth,
+    // Stack pointer of the empty stack.
+    stack_base: W,
+    halted: bool,
+    // Error reported by the instruction that is currently executed.
+    fault: Option<ProgramError>,
+    call_depth: usize,
+    cycles: u64,
+    // Instruction that is currently executed, which the flag policy is consulted for.
+    executing: Option<I>,
+    // Decides which flags the executed instructions update. `None` if every instruction updates all of its flags.
+    flag_policy: PolicySlot<I, I::Flags>,
+    // Number of executions per mnemonic. `None` if profiling is disabled.
+    #[cfg(feature = "alloc")]
+    profile: Option<BTreeMap<&'static str, u64>>,
+    // States before the last executed instructions. `None` if the history is disabled.
+    #[cfg(feature = "alloc")]
+    history: Option<History<W, S, I::Flags>>,
+    #[cfg(feature = "alloc")]
+    io: IoBus<'a, STACK_SIZE, I, P, W, S>,
+}
+
+/// A function that decides whether an instruction updates a flag, see [`Processor::update_flag()`].
+///
+/// Instruction sets that write their flags with [`update_flag()`](Processor::update_flag()) can be adapted
+/// to an ISA that sets its flags differently without changing the instructions,
+/// e.g. one that never sets the carry on an increment.
+pub type FlagPolicy<I, F> = fn(&I, <F as FlagRegister>::Flag) -> bool;
+
+/// The [`FlagPolicy`] of a processor, if one is set.
+#[derive(Debug, Clone, Copy)]
+struct PolicySlot<I, F: FlagRegister>(Option<FlagPolicy<I, F>>);
+
+impl<I, F: FlagRegister> Default for PolicySlot<I, F> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+// Function pointers cannot be compared reliably, so processors are compared without their policies.
+impl<I, F: FlagRegister> PartialEq for PolicySlot<I, F> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<I, F: FlagRegister> Eq for PolicySlot<I, F> {}
+
+impl<I, F: FlagRegister> core::hash::Hash for PolicySlot<I, F> {
+    fn hash<H: core::hash::Hasher>(&self, _: &mut H) {}
+}
+
+/// The program loaded into a [`Processor`], either borrowed from the caller or owned by the processor.
+///
+/// It dereferences to the [`Program`] in both cases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LoadedProgram<'a, I, P, W> {
+    Borrowed(&'a Program<I, P, W>),
+    Owned(Program<I, P, W>),
+}
+
+impl<I, P, W> Deref for LoadedProgram<'_, I, P, W> {
+    type Target = Program<I, P, W>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(program) => program,
+            Self::Owned(program) => program,
+        }
+    }
+}
+
+/// A snapshot of the registers, the stack and the internal state of a [`Processor`].
+///
+/// It is taken with [`Processor::state()`] and can be restored with [`Processor::restore()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProcessorState<W, S, F = FlagSet> {
+    pub registers: Registers<W, F>,
+    pub stack: S,
+    call_depth: usize,
+    cycles: u64,
+}
+
+/// The states before the last executed instructions, see [`Processor::with_history()`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+struct History<W, S, F> {
+    states: VecDeque<ProcessorState<W, S, F>>,
+    depth: usize,
+    // The stack is only required to be `Clone` when the history is enabled.
+    clone_stack: fn(&S) -> S,
+}
+
+// The clone function is determined by `S`, so it is left out of the comparisons.
+#[cfg(feature = "alloc")]
+impl<W: PartialEq, S: PartialEq, F: PartialEq> PartialEq for History<W, S, F> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.states, self.depth) == (&other.states, other.depth)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Eq, S: Eq, F: Eq> Eq for History<W, S, F> {}
+
+#[cfg(feature = "alloc")]
+impl<W: core::hash::Hash, S: core::hash::Hash, F: core::hash::Hash> core::hash::Hash for History<W, S, F> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.states.hash(state);
+        self.depth.hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+type InputHandler<'a, const STACK_SIZE: usize, I, P, W, S> =
+    Box<dyn FnMut(&mut Processor<'a, STACK_SIZE, I, P, W, S>) -> W + 'a>;
+#[cfg(feature = "alloc")]
+type OutputHandler<'a, const STACK_SIZE: usize, I, P, W, S> =
+    Box<dyn FnMut(&mut Processor<'a, STACK_SIZE, I, P, W, S>, W) + 'a>;
+
+/// The handlers of the host connected to the I/O ports, see [`Processor::connect_input()`] and [`Processor::connect_output()`].
+#[cfg(feature = "alloc")]
+struct IoBus<'a, const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> {
+    inputs: BTreeMap<W, InputHandler<'a, STACK_SIZE, I, P, W, S>>,
+    outputs: BTreeMap<W, OutputHandler<'a, STACK_SIZE, I, P, W, S>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> IoBus<'_, STACK_SIZE, I, P, W, S> {
+    // The handlers cannot be compared, so buses are compared by their connected ports.
+    fn ports(&self) -> (impl Iterator<Item = &W>, impl Iterator<Item = &W>) {
+        (self.inputs.keys(), self.outputs.keys())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> Default for IoBus<'_, STACK_SIZE, I, P, W, S> {
+    fn default() -> Self {
+        Self {
+            inputs: BTreeMap::new(),
+            outputs: BTreeMap::new(),
+        }
+    }
+}
+
+/// Closures cannot be cloned, so a cloned processor has no I/O ports connected.
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> Clone for IoBus<'_, STACK_SIZE, I, P, W, S> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> core::fmt::Debug for IoBus<'_, STACK_SIZE, I, P, W, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let (inputs, outputs) = self.ports();
+        f.debug_struct("IoBus")
+            .field("inputs", &inputs.collect::<alloc::vec::Vec<_>>())
+            .field("outputs", &outputs.collect::<alloc::vec::Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> PartialEq for IoBus<'_, STACK_SIZE, I, P, W, S> {
+    fn eq(&self, other: &Self) -> bool {
+        let (inputs, outputs) = self.ports();
+        let (other_inputs, other_outputs) = other.ports();
+        inputs.eq(other_inputs) && outputs.eq(other_outputs)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S> Eq for IoBus<'_, STACK_SIZE, I, P, W, S> {}
+
+#[cfg(feature = "alloc")]
+impl<const STACK_SIZE: usize, I: Instruction<W>, P, W: Word + core::hash::Hash, S> core::hash::Hash
+    for IoBus<'_, STACK_SIZE, I, P, W, S>
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        let (inputs, outputs) = self.ports();
+        inputs.for_each(|port| port.hash(state));
+        outputs.for_each(|port| port.hash(state));
+    }
 }
 
-impl<'a, const STACK_SIZE: usize, I, P, W> Processor<'a, STACK_SIZE, I, P, W>
+impl<'a, const STACK_SIZE: usize, I, P, W, S> Processor<'a, STACK_SIZE, I, P, W, S>
 where
     I: Instruction<W>,
     P: Deref<Target = [I]>,
     W: Word,
+    S: StackMemory<W>,
 {
     #[must_use]
     #[inline]
-    pub const fn builder() -> ProcessorBuilder<'a, STACK_SIZE, I, P, W> {
+    pub const fn builder() -> ProcessorBuilder<'a, STACK_SIZE, I, P, W, S> {
         ProcessorBuilder::new()
     }
 
     /// Creates a new processor.
     #[must_use]
     #[inline]
-    pub fn new() -> Self {
+    pub fn new() -> Self
+    where
+        S: Default,
+    {
         Self {
             registers: Registers::new(),
-            stack: Stack::new(),
+            stack: S::default(),
             program: None,
+            stack_growth: StackGrowth::Up,
+            stack_base: W::default(),
+            halted: false,
+            fault: None,
+            call_depth: 0,
+            cycles: 0,
+            executing: None,
+            flag_policy: PolicySlot::default(),
+            #[cfg(feature = "alloc")]
+            profile: None,
+            #[cfg(feature = "alloc")]
+            history: None,
+            #[cfg(feature = "alloc")]
+            io: IoBus::default(),
         }
     }
 
@@ -59,67 +291,689 @@ where
     ///
     /// The program cannot be changed after being loaded. To make changes, an updated or entirely new program has to be loaded.
     #[inline]
-    pub const fn load_program(&mut self, program: &'a Program<I, P, W>) {
-        self.program = Some(program);
+    pub fn load_program(&mut self, program: &'a Program<I, P, W>) {
+        self.program = Some(LoadedProgram::Borrowed(program));
+    }
+
+    /// Loads a program into the processor, which takes ownership of it.
+    ///
+    /// Unlike [`load_program()`](Processor::load_program()) the program does not have to outlive the processor.
+    #[inline]
+    pub fn load_program_owned(&mut self, program: Program<I, P, W>) {
+        self.program = Some(LoadedProgram::Owned(program));
+    }
+
+    /// Returns the loaded program, or `None` if no program is loaded.
+    #[must_use]
+    #[inline]
+    pub fn program(&self) -> Option<&Program<I, P, W>> {
+        self.program.as_deref()
+    }
+
+    /// Returns the direction in which the stack of this processor grows.
+    ///
+    /// Instructions that push or pop values should move the stack pointer with
+    /// [`StackGrowth::push()`] and [`StackGrowth::pop()`].
+    #[must_use]
+    #[inline]
+    pub const fn stack_growth(&self) -> StackGrowth {
+        self.stack_growth
+    }
+
+    /// Returns true if no values are on the stack, i.e. the stack pointer is at its initial value.
+    ///
+    /// The initial value is the stack pointer when the processor was built, see [`ProcessorBuilder::with_stack_pointer()`].
+    /// Instructions that pop values should check this and report [`ProgramError::StackUnderflow`] instead of popping from an empty stack.
+    #[must_use]
+    #[inline]
+    pub fn stack_is_empty(&self) -> bool {
+        self.registers.sp() == self.stack_base
+    }
+
+    /// Returns the value on top of the stack, i.e. at the stack pointer, without popping it.
+    ///
+    /// # Panics
+    /// Panics if the stack pointer is out of bounds.
+    #[must_use]
+    #[inline]
+    pub fn peek_stack(&self) -> W {
+        self.stack.read(self.registers.sp())
+    }
+
+    /// Returns the value `offset` values away from the top of the stack without popping it,
+    /// e.g. `-1` for the value that was pushed before the top, independent of the [`StackGrowth`].
+    ///
+    /// # Panics
+    /// Panics if the address is out of bounds.
+    #[must_use]
+    #[inline]
+    pub fn peek_stack_offset(&self, offset: i32) -> W {
+        let sp = self.registers.sp();
+        let addr = match self.stack_growth {
+            StackGrowth::Up => sp + offset.into(),
+            StackGrowth::Down => sp - offset.into(),
+        };
+        self.stack.read(addr)
+    }
+
+    /// Writes a block of values to the stack, starting at the address `addr`.
+    ///
+    /// This is useful to load data before running a program.
+    ///
+    /// # Errors
+    /// Returns `StackError::OutOfBounds` if the block does not fit into the stack. Nothing is written in that case.
+    #[inline]
+    pub fn write_memory(&mut self, addr: usize, data: &[W]) -> Result<(), StackError> {
+        self.stack.write_region(addr, data)
+    }
+
+    /// Reads a block of `len` values from the stack, starting at the address `addr`.
+    ///
+    /// This is useful to inspect the results of a program.
+    ///
+    /// # Errors
+    /// Returns `StackError::OutOfBounds` if the block is not within the stack.
+    #[inline]
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<&[W], StackError> {
+        self.stack.read_region(addr, len)
+    }
+
+    /// Returns the instruction the program counter points to, without executing it.
+    ///
+    /// Returns `None` if no program is loaded or the program counter is past the end of the program.
+    #[must_use]
+    #[inline]
+    pub fn current_instruction(&self) -> Option<&I> {
+        self.program()?.get(self.registers.pc().into())
+    }
+
+    /// Returns the assembly representation of the instruction the program counter points to, without executing it.
+    ///
+    /// Returns `None` if no program is loaded or the program counter is past the end of the program.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn current_instruction_asm(&self) -> Option<String>
+    where
+        I: Display,
+    {
+        self.current_instruction().map(ToString::to_string)
+    }
+
+    /// Returns a snapshot of the registers, flags and stack as text, e.g. for logging a run.
+    ///
+    /// Every register, the flags and every stack cell have their own line in a fixed order, so dumps of different runs can be diffed.
+    /// Only the stack cells that differ from the default value are listed, which keeps the dump short for large stacks.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn dump(&self) -> String
+    where
+        S: Deref<Target: AsRef<[W]>>,
+    {
+        use core::fmt::Write;
+
+        let registers = &self.registers;
+        let mut dump = String::new();
+
+        // writing to a string cannot fail
+        for (idx, val) in registers.general().iter().enumerate() {
+            let _ = writeln!(dump, "R{idx}: {val}");
+        }
+        let _ = writeln!(dump, "PC: {}\nSP: {}", registers.pc(), registers.sp());
+        let _ = writeln!(dump, "flags: {}", self.registers.flags());
+        let stack = self.stack.deref().as_ref();
+        for (addr, val) in stack.iter().enumerate().filter(|(_, val)| **val != W::default()) {
+            let _ = writeln!(dump, "stack[{addr}]: {val}");
+        }
+
+        dump
+    }
+
+    /// Returns the total number of clock cycles of all instructions executed by this processor.
+    ///
+    /// The cycles of an instruction are given by [`Instruction::cycles()`].
+    /// This can be used to compare different implementations of the same algorithm.
+    #[must_use]
+    #[inline]
+    pub const fn cycle_count(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Enables counting how many times each kind of instruction is executed.
+    ///
+    /// The instructions are told apart by their [`mnemonic`](Instruction::mnemonic).
+    /// The counts can be read with [`profile()`](Processor::profile()). Profiling is disabled by default.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn enable_profiling(&mut self) {
+        self.profile.get_or_insert_default();
+    }
+
+    /// Returns the number of executions of each kind of instruction since profiling was enabled.
+    ///
+    /// The map is empty if profiling is not enabled, see [`enable_profiling()`](Processor::enable_profiling()).
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    #[inline]
+    pub fn profile(&self) -> &BTreeMap<&'static str, u64> {
+        const EMPTY: &BTreeMap<&str, u64> = &BTreeMap::new();
+        self.profile.as_ref().unwrap_or(EMPTY)
+    }
+
+    /// Counts the cycles of the instruction and the instruction itself if profiling is enabled.
+    #[inline]
+    fn record(&mut self, instruction: &I) {
+        self.cycles += u64::from(instruction.cycles());
+
+        #[cfg(feature = "alloc")]
+        if let Some(profile) = &mut self.profile {
+            *profile.entry(instruction.mnemonic()).or_default() += 1;
+        }
+    }
+
+    /// Takes a snapshot of the registers, the stack and the internal state of the processor.
+    #[must_use]
+    pub fn state(&self) -> ProcessorState<W, S, I::Flags>
+    where
+        S: Clone,
+    {
+        ProcessorState {
+            registers: self.registers.clone(),
+            stack: self.stack.clone(),
+            call_depth: self.call_depth,
+            cycles: self.cycles,
+        }
+    }
+
+    /// Restores a snapshot taken with [`state()`](Processor::state()).
+    pub fn restore(&mut self, state: ProcessorState<W, S, I::Flags>) {
+        self.registers = state.registers;
+        self.stack = state.stack;
+        self.call_depth = state.call_depth;
+        self.cycles = state.cycles;
+    }
+
+    /// Enables recording the state before each executed instruction, so that up to `depth` instructions
+    /// can be undone with [`step_back()`](Processor::step_back()).
+    ///
+    /// Every recorded state contains a copy of the stack, so the history is disabled by default to save memory.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn with_history(mut self, depth: usize) -> Self
+    where
+        S: Clone,
+    {
+        self.history = Some(History {
+            states: VecDeque::with_capacity(depth),
+            depth,
+            clone_stack: S::clone,
+        });
+        self
+    }
+
+    /// Undoes the last executed instruction by restoring the state before it was executed.
+    ///
+    /// # Errors
+    /// Returns [`HistoryError::Disabled`] if the history was not enabled with [`with_history()`](Processor::with_history())
+    /// and [`HistoryError::Exhausted`] if all recorded instructions have been undone.
+    #[cfg(feature = "alloc")]
+    pub fn step_back(&mut self) -> Result<(), HistoryError> {
+        let history = self.history.as_mut().ok_or(HistoryError::Disabled)?;
+        let state = history.states.pop_back().ok_or(HistoryError::Exhausted)?;
+
+        self.restore(state);
+        Ok(())
+    }
+
+    /// Records the state before an instruction is executed if the history is enabled.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn save_state(&mut self) {
+        let Some(history) = &mut self.history else {
+            return;
+        };
+        if history.depth == 0 {
+            return;
+        }
+        if history.states.len() == history.depth {
+            history.states.pop_front();
+        }
+
+        history.states.push_back(ProcessorState {
+            registers: self.registers.clone(),
+            stack: (history.clone_stack)(&self.stack),
+            call_depth: self.call_depth,
+            cycles: self.cycles,
+        });
+    }
+
+    /// Sets a flag unless the [`FlagPolicy`] prevents the instruction that is currently executed from updating it.
+    ///
+    /// Instructions should write their flags with this method instead of [`Registers::set_flag()`],
+    /// so that the flags they update can be changed with [`ProcessorBuilder::with_flag_policy()`].
+    /// Outside of an instruction the flag is always set.
+    #[inline]
+    pub fn update_flag(&mut self, flag: <I::Flags as FlagRegister>::Flag, val: bool) {
+        if let (PolicySlot(Some(policy)), Some(instruction)) = (self.flag_policy, &self.executing)
+            && !policy(instruction, flag)
+        {
+            return;
+        }
+
+        self.registers.set_flag(flag, val);
+    }
+
+    /// Stops a running program after the instruction that is currently executed.
+    ///
+    /// This is meant to be called by instructions. The run is then reported as [`RunOutcome::Halted`].
+    #[inline]
+    pub const fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    /// Stops a running program after the instruction that is currently executed and reports the error.
+    ///
+    /// This is meant to be called by instructions that cannot be executed, e.g. a return without a call.
+    /// The error is returned by the method that executed the instruction, e.g. [`execute_next_instruction()`](Processor::execute_next_instruction()).
+    #[inline]
+    pub fn fault(&mut self, error: ProgramError) {
+        self.fault = Some(error);
+    }
+
+    /// Sets the program counter to `target`, so the instruction at `target` is executed next.
+    ///
+    /// This is meant to be called by jump, call and return instructions, as it validates the target.
+    /// Jumping directly behind the last instruction is allowed, as it ends the program.
+    /// A target past the end of the program faults with `InvalidJumpTarget` (see [`fault()`](Processor::fault())) and leaves the program counter unchanged.
+    /// Without a loaded program there is nothing to validate against, so the program counter is always set.
+    ///
+    /// Setting the program counter directly with [`Registers::set_reg()`] bypasses the validation.
+    /// A target past the end is then only reported after the instruction was executed.
+    pub fn jump(&mut self, target: W) {
+        let pc = target.into();
+        if self.program.as_ref().is_some_and(|program| pc > program.len()) {
+            return self.fault(ProgramError::InvalidJumpTarget { pc });
+        }
+
+        self.registers.set_reg(Register::PC, target);
+    }
+
+    /// Sets the program counter to the instruction at index `pc` of the loaded program, e.g. to continue a debugged program at another instruction.
+    ///
+    /// Unlike [`jump()`](Processor::jump()) this is meant to be called from outside of instructions.
+    ///
+    /// # Errors
+    /// Returns `NoProgramLoaded` if there is no program and `PCOutOfBounds` if `pc` is not the index of an instruction of the program.
+    /// The program counter is not changed in that case.
+    pub fn set_pc(&mut self, pc: usize) -> Result<(), ProgramError> {
+        let program_len = self.program.as_ref().ok_or(ProgramError::NoProgramLoaded)?.len();
+        let out_of_bounds = ProgramError::PCOutOfBounds { pc, program_len };
+        if pc >= program_len {
+            return Err(out_of_bounds);
+        }
+
+        let pc = W::try_from(pc).map_err(|_| out_of_bounds)?;
+        self.registers.set_reg(Register::PC, pc);
+        Ok(())
+    }
+
+    /// Connects a handler to an input port. It is called to get the value whenever the program reads from the port.
+    /// A handler that was connected to the port before is replaced.
+    ///
+    /// A handler can e.g. emulate a device mapped to the port. It must not connect other handlers to the processor itself.
+    #[cfg(feature = "alloc")]
+    pub fn connect_input(&mut self, port: W, handler: impl FnMut(&mut Self) -> W + 'a) {
+        self.io.inputs.insert(port, Box::new(handler));
+    }
+
+    /// Connects a handler to an output port. It is called with the value whenever the program writes to the port.
+    /// A handler that was connected to the port before is replaced.
+    ///
+    /// A handler can e.g. emulate a device mapped to the port. It must not connect other handlers to the processor itself.
+    #[cfg(feature = "alloc")]
+    pub fn connect_output(&mut self, port: W, handler: impl FnMut(&mut Self, W) + 'a) {
+        self.io.outputs.insert(port, Box::new(handler));
+    }
+
+    /// Reads a value from an input port by calling its handler. This is meant to be called by input instructions.
+    ///
+    /// Returns `None` if no handler is connected to the port.
+    #[cfg(feature = "alloc")]
+    pub fn read_port(&mut self, port: W) -> Option<W> {
+        // the handler is removed while it is called, as it gets mutable access to the processor
+        let mut handler = self.io.inputs.remove(&port)?;
+        let value = handler(self);
+        self.io.inputs.insert(port, handler);
+        Some(value)
+    }
+
+    /// Writes a value to an output port by calling its handler. This is meant to be called by output instructions.
+    ///
+    /// Returns `None` if no handler is connected to the port.
+    #[cfg(feature = "alloc")]
+    pub fn write_port(&mut self, port: W, value: W) -> Option<()> {
+        let mut handler = self.io.outputs.remove(&port)?;
+        handler(self, value);
+        self.io.outputs.insert(port, handler);
+        Some(())
+    }
+
+    /// Returns the number of subroutine calls that have not returned yet.
+    #[must_use]
+    #[inline]
+    pub const fn call_depth(&self) -> usize {
+        self.call_depth
+    }
+
+    /// Records a call of a subroutine. This is meant to be called by call instructions.
+    #[inline]
+    pub const fn enter_call(&mut self) {
+        self.call_depth += 1;
+    }
+
+    /// Records a return from a subroutine. This is meant to be called by return instructions.
+    ///
+    /// Returns `false` if there is no subroutine to return from. The call depth is not changed in that case.
+    #[must_use]
+    #[inline]
+    pub const fn leave_call(&mut self) -> bool {
+        if self.call_depth == 0 {
+            return false;
+        }
+
+        self.call_depth -= 1;
+        true
     }
 
     /// Runs the entire program.
     ///
+    /// Running past the last instruction or halting completes the program and returns `Ok(())`.
+    ///
     /// # Errors
-    /// The execution of the program stops and a `ProgramError` is returned if an error occured during the fetching of an instruction.
+    /// The execution of the program stops and a `ProgramError` is returned if an error occured during the fetching
+    /// or execution of an instruction.
     ///
-    /// Note: The execution of an instruction will never return an error. If the instruction is valid it will not error.
-    /// Invalid instructions are a major bug in the implementation of the instruction set that is used for the program.
+    /// Note: Instructions can only report errors through [`fault()`](Processor::fault()), e.g. a return without a call.
+    /// Apart from that, invalid instructions are a major bug in the implementation of the instruction set that is used for the program.
     pub fn run_program(&mut self) -> Result<(), ProgramError> {
-        loop {
-            self.execute_next_instruction()?;
+        self.run_program_with_limit(u64::MAX)
+    }
+
+    /// Runs the entire program and reports why it stopped and how many instructions were executed.
+    ///
+    /// See [`RunOutcome`] for the possible outcomes.
+    #[inline]
+    pub fn run_program_detailed(&mut self) -> RunOutcome {
+        self.run_program_detailed_with_limit(u64::MAX)
+    }
+
+    /// Runs the entire program like [`run_program_detailed()`](Processor::run_program_detailed()),
+    /// but executes at most `max_instructions` instructions.
+    pub fn run_program_detailed_with_limit(&mut self, max_instructions: u64) -> RunOutcome {
+        self.run_steps(max_instructions, Self::execute_next_instruction)
+    }
+
+    /// Executes instructions with `step` until the program stops or `max_instructions` instructions were executed.
+    fn run_steps(
+        &mut self,
+        max_instructions: u64,
+        mut step: impl FnMut(&mut Self) -> Result<(), ProgramError>,
+    ) -> RunOutcome {
+        for steps in 0..max_instructions {
+            let result = step(self);
+            if let Some(outcome) = self.stop_reason(result, steps) {
+                return outcome;
+            }
+        }
+
+        // the last allowed instruction may have been the last instruction of the program
+        if self.is_at_end() {
+            return RunOutcome::Completed {
+                steps: max_instructions,
+            };
+        }
+
+        RunOutcome::StepLimit {
+            steps: max_instructions,
+        }
+    }
+
+    /// Returns true if the pc points directly behind the last instruction of the loaded program.
+    fn is_at_end(&self) -> bool {
+        self.program
+            .as_ref()
+            .is_some_and(|program| self.registers.pc().into() == program.len())
+    }
+
+    /// Returns why the program stopped after executing an instruction with `result`, or `None` if it continues.
+    /// `steps` is the number of instructions that were executed before.
+    fn stop_reason(&mut self, result: Result<(), ProgramError>, steps: u64) -> Option<RunOutcome> {
+        match result {
+            Ok(()) if self.halted => {
+                self.halted = false;
+                Some(RunOutcome::Halted { steps: steps + 1 })
+            }
+            Ok(()) => None,
+            // the pc points directly behind the last instruction, so the program ran to completion
+            Err(ProgramError::PCOutOfBounds { pc, program_len }) if pc == program_len => {
+                Some(RunOutcome::Completed { steps })
+            }
+            Err(error) => Some(RunOutcome::Error { steps, error }),
+        }
+    }
+
+    /// Converts the outcome of a run with at most `max_instructions` instructions into the result of [`run_program()`](Processor::run_program()).
+    fn run_result(outcome: RunOutcome, max_instructions: u64) -> Result<(), ProgramError> {
+        match outcome {
+            RunOutcome::Completed { .. } | RunOutcome::Halted { .. } => Ok(()),
+            RunOutcome::Error { error, .. } => Err(error),
+            RunOutcome::StepLimit { .. } => Err(ProgramError::InstructionLimitExceeded {
+                limit: max_instructions,
+            }),
         }
     }
 
+    /// Runs the entire program, but executes at most `max_instructions` instructions.
+    ///
+    /// This guards against programs that never terminate, e.g. because of an unconditional backward jump.
+    ///
+    /// # Errors
+    /// The execution of the program stops and a `ProgramError` is returned if an error occured during the fetching of an instruction.
+    /// If `max_instructions` instructions were executed without the program terminating, `InstructionLimitExceeded` is returned.
+    pub fn run_program_with_limit(&mut self, max_instructions: u64) -> Result<(), ProgramError> {
+        let outcome = self.run_program_detailed_with_limit(max_instructions);
+        Self::run_result(outcome, max_instructions)
+    }
+
+    /// Executes up to `n` instructions and returns the number of instructions that were actually executed.
+    ///
+    /// Fewer than `n` instructions are executed if the program completes or halts, like in [`run_program()`](Processor::run_program()).
+    /// This is not treated as an error.
+    ///
+    /// # Errors
+    /// Returns a `ProgramError` if an error occured during the fetching or execution of an instruction.
+    #[inline]
+    pub fn run_n(&mut self, n: usize) -> Result<usize, ProgramError> {
+        match self.run_steps(n as u64, Self::execute_next_instruction) {
+            RunOutcome::Completed { steps } | RunOutcome::Halted { steps } | RunOutcome::StepLimit { steps } => {
+                Ok(steps as usize)
+            }
+            RunOutcome::Error { error, .. } => Err(error),
+        }
+    }
+
+    /// Runs the program until the pc reaches `target` and stops before executing the instruction at `target`,
+    /// e.g. for "run to cursor" in a debugger. At most `limit` instructions are executed.
+    ///
+    /// Returns `true` if the target was reached, which is immediately the case if the pc already points to it.
+    /// Returns `false` if the limit was exceeded or the program completed or halted before reaching the target.
+    ///
+    /// # Errors
+    /// Returns a `ProgramError` if an error other than reaching the end of the program occured.
+    pub fn run_to_pc(&mut self, target: usize, limit: u64) -> Result<bool, ProgramError> {
+        for steps in 0..limit {
+            if self.registers.pc().into() == target {
+                return Ok(true);
+            }
+
+            let result = self.execute_next_instruction();
+            match self.stop_reason(result, steps) {
+                None => (),
+                Some(RunOutcome::Error { error, .. }) => return Err(error),
+                Some(_) => return Ok(false),
+            }
+        }
+
+        // a program that completes with the last allowed instruction only reaches a target behind its end
+        Ok(self.registers.pc().into() == target)
+    }
+
     /// Fetches the current instruction (where pc points to), increments the pc and then executes the instruction.
     ///
     /// # Errors
-    /// Returns a `ProgramError` if an error occured during fetching.
+    /// Returns a `ProgramError` if an error occured during fetching, the instruction reported an error (see [`fault()`](Processor::fault()))
+    /// or jumped past the end of the program.
     ///
-    /// Note: The execution of an instruction will never return an error. If the instruction is valid it will not error.
-    /// Invalid instructions are a major bug in the implementation of the instruction set that is used for the program.
+    /// Note: Instructions can only report errors through [`fault()`](Processor::fault()), e.g. a return without a call.
+    /// Apart from that, invalid instructions are a major bug in the implementation of the instruction set that is used for the program.
     pub fn execute_next_instruction(&mut self) -> Result<(), ProgramError> {
         let program = self.program.as_ref().ok_or(ProgramError::NoProgramLoaded)?;
 
-        let instruction = program.fetch_instruction(self.registers.pc().into())?;
+        let pc = self.registers.pc().into();
+        let instruction = program.fetch_instruction(pc)?;
+        let program_len = program.len();
 
+        #[cfg(feature = "alloc")]
+        self.save_state();
         self.registers.inc(Register::PC);
+        self.record(&instruction);
 
+        self.executing = Some(instruction);
         I::execute(instruction, self);
+        self.executing = None;
+
+        self.check_executed(pc, program_len)
+    }
+
+    /// Runs the entire program using a dispatch table.
+    ///
+    /// The dispatch table is a slice of [`Handler`]s, one per instruction of the loaded program.
+    /// It can be built with [`Program::dispatch_table()`](crate::program::Program::dispatch_table()).
+    ///
+    /// Like [`run_program()`](Processor::run_program()), running past the last instruction or halting completes the program and returns `Ok(())`.
+    ///
+    /// # Errors
+    /// The execution of the program stops and a `ProgramError` is returned if an error occured during the fetching
+    /// or execution of an instruction.
+    pub fn run_program_dispatched(&mut self, table: &[Handler<STACK_SIZE, I, P, W, S>]) -> Result<(), ProgramError> {
+        let outcome = self.run_steps(u64::MAX, |processor| {
+            processor.execute_next_instruction_dispatched(table)
+        });
+        Self::run_result(outcome, u64::MAX)
+    }
+
+    /// Fetches the current instruction (where pc points to), increments the pc and then executes the instruction
+    /// by calling the handler at the same position in the dispatch table.
+    ///
+    /// If the dispatch table has no handler at this position, the instruction is executed with [`Instruction::execute`].
+    ///
+    /// # Errors
+    /// Returns a `ProgramError` if an error occured during fetching, the instruction reported an error (see [`fault()`](Processor::fault()))
+    /// or jumped past the end of the program.
+    #[inline]
+    pub fn execute_next_instruction_dispatched(
+        &mut self,
+        table: &[Handler<STACK_SIZE, I, P, W, S>],
+    ) -> Result<(), ProgramError> {
+        let program = self.program.as_ref().ok_or(ProgramError::NoProgramLoaded)?;
+
+        let pc = self.registers.pc().into();
+        let instruction = program.fetch_instruction(pc)?;
+        let program_len = program.len();
+        let handler = table.get(pc).copied().unwrap_or(I::execute);
+
+        #[cfg(feature = "alloc")]
+        self.save_state();
+        self.registers.inc(Register::PC);
+        self.record(&instruction);
+
+        self.executing = Some(instruction);
+        handler(instruction, self);
+        self.executing = None;
+
+        self.check_executed(pc, program_len)
+    }
+
+    /// Returns the error reported by the instruction at `pc` that was just executed, see [`fault()`](Processor::fault()).
+    ///
+    /// If the instruction jumped past the end of the program, `InvalidJumpTarget` is returned.
+    /// Jumping directly behind the last instruction is allowed, as it ends the program.
+    #[inline]
+    fn check_executed(&mut self, pc: usize, program_len: usize) -> Result<(), ProgramError> {
+        if let Some(error) = self.fault.take() {
+            return Err(error);
+        }
+
+        let next_pc = self.registers.pc().into();
+        if next_pc != pc + 1 && next_pc > program_len {
+            return Err(ProgramError::InvalidJumpTarget { pc: next_pc });
+        }
 
         Ok(())
     }
 }
 
-impl<const STACK_SIZE: usize, I, P, W> Display for Processor<'_, STACK_SIZE, I, P, W>
+impl<const STACK_SIZE: usize, I, P, W, S> Display for Processor<'_, STACK_SIZE, I, P, W, S>
 where
     I: Instruction<W>,
     P: Deref<Target = [I]>,
     W: Word,
+    S: StackMemory<W> + Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(f, "Registers: \n{}\nStack: \t\t{}", self.registers, self.stack)
     }
 }
 
+/// The error returned by [`Processor::step_back()`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HistoryError {
+    #[error("The history is not enabled")]
+    Disabled,
+    #[error("No more instructions to step back")]
+    Exhausted,
+}
+
+/// The reason a [`Processor`] stopped running a program, together with the number of executed instructions (`steps`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The program counter moved past the last instruction.
+    Completed { steps: u64 },
+    /// An instruction halted the processor, see [`Processor::halt()`].
+    Halted { steps: u64 },
+    /// An error occured during the fetching of an instruction.
+    Error { steps: u64, error: ProgramError },
+    /// The maximum number of instructions was executed without the program stopping.
+    StepLimit { steps: u64 },
+}
+
 /// The [`ProcessorBuilder`] is used to create a [`Processor`].
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Default)]
-pub struct ProcessorBuilder<'a, const STACK_SIZE: usize, I, P, W> {
-    registers: Option<Registers<W>>,
-    stack: Option<Stack<STACK_SIZE, W>>,
-    program: Option<&'a Program<I, P, W>>,
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ProcessorBuilder<'a, const STACK_SIZE: usize, I: Instruction<W>, P, W: Word, S = Stack<STACK_SIZE, W>> {
+    registers: Option<Registers<W, I::Flags>>,
+    stack: Option<S>,
+    program: Option<LoadedProgram<'a, I, P, W>>,
+    stack_growth: StackGrowth,
+    stack_pointer: Option<W>,
+    program_counter: Option<W>,
+    flag_policy: PolicySlot<I, I::Flags>,
 }
 
-impl<'a, const STACK_SIZE: usize, I, P, W> ProcessorBuilder<'a, STACK_SIZE, I, P, W>
+impl<'a, const STACK_SIZE: usize, I, P, W, S> ProcessorBuilder<'a, STACK_SIZE, I, P, W, S>
 where
     I: Instruction<W>,
     P: Deref<Target = [I]>,
     W: Word,
+    S: StackMemory<W>,
 {
     /// Creates a new `ProcessorBuilder` with registers, stack and program set to `None`.
     #[inline]
@@ -128,13 +982,17 @@ where
             registers: None,
             stack: None,
             program: None,
+            stack_growth: StackGrowth::Up,
+            stack_pointer: None,
+            program_counter: None,
+            flag_policy: PolicySlot(None),
         }
     }
 
     /// Sets the registers for the `ProcessorBuilder`.
     #[must_use]
     #[inline]
-    pub const fn with_registers(mut self, registers: Registers<W>) -> Self {
+    pub const fn with_registers(mut self, registers: Registers<W, I::Flags>) -> Self {
         self.registers = Some(registers);
         self
     }
@@ -142,27 +1000,112 @@ where
     /// Sets the stack for the `ProcessorBuilder`.
     #[must_use]
     #[inline]
-    pub const fn with_stack(mut self, stack: Stack<STACK_SIZE, W>) -> Self {
+    pub fn with_stack(mut self, stack: S) -> Self {
         self.stack = Some(stack);
         self
     }
 
+    /// Sets the direction in which the stack grows. The stack grows upwards by default.
+    ///
+    /// If no registers are set, the stack pointer starts at 0 for [`StackGrowth::Up`]
+    /// and at `STACK_SIZE - 1` for [`StackGrowth::Down`].
+    #[must_use]
+    #[inline]
+    pub const fn with_stack_growth(mut self, stack_growth: StackGrowth) -> Self {
+        self.stack_growth = stack_growth;
+        self
+    }
+
+    /// Sets the initial stack pointer. This overrides the stack pointer of the registers set with [`with_registers()`](Self::with_registers()).
+    ///
+    /// The stack pointer points to the value on top of the stack: `PUSH` first moves the stack pointer and then writes the value,
+    /// `POP` first reads the value and then moves the stack pointer back. The slot at the initial stack pointer is therefore never written
+    /// by a push, and the stack is empty when the stack pointer is back at its initial value.
+    #[must_use]
+    #[inline]
+    pub const fn with_stack_pointer(mut self, sp: W) -> Self {
+        self.stack_pointer = Some(sp);
+        self
+    }
+
+    /// Sets the initial program counter, e.g. to start at the entry point of a program instead of its first instruction.
+    /// This overrides the program counter of the registers set with [`with_registers()`](Self::with_registers()).
+    #[must_use]
+    #[inline]
+    pub const fn with_program_counter(mut self, pc: W) -> Self {
+        self.program_counter = Some(pc);
+        self
+    }
+
+    /// Sets the [`FlagPolicy`] that decides which flags the executed instructions update, see [`Processor::update_flag()`].
+    /// By default every instruction updates all of its flags.
+    #[must_use]
+    #[inline]
+    pub const fn with_flag_policy(mut self, policy: FlagPolicy<I, I::Flags>) -> Self {
+        self.flag_policy = PolicySlot(Some(policy));
+        self
+    }
+
     /// Sets the program for the `ProcessorBuilder`.
     #[must_use]
     #[inline]
-    pub const fn with_program(mut self, program: &'a Program<I, P, W>) -> Self {
-        self.program = Some(program);
+    pub fn with_program(mut self, program: &'a Program<I, P, W>) -> Self {
+        self.program = Some(LoadedProgram::Borrowed(program));
+        self
+    }
+
+    /// Sets a program for the `ProcessorBuilder` that is owned by the built processor, see [`Processor::load_program_owned()`].
+    #[must_use]
+    #[inline]
+    pub fn with_owned_program(mut self, program: Program<I, P, W>) -> Self {
+        self.program = Some(LoadedProgram::Owned(program));
         self
     }
 
     /// Builds the `Processor` with the given registers, stack and program.
+    ///
+    /// # Panics
+    /// Panics if the stack grows downwards, no registers are set and `STACK_SIZE - 1` cannot be represented by the word type.
     #[must_use]
     #[inline]
-    pub fn build(self) -> Processor<'a, STACK_SIZE, I, P, W> {
+    pub fn build(self) -> Processor<'a, STACK_SIZE, I, P, W, S>
+    where
+        S: Default,
+    {
+        let mut registers = self.registers.unwrap_or_else(|| {
+            let mut registers = Registers::new();
+            if self.stack_growth == StackGrowth::Down {
+                let sp = W::try_from(STACK_SIZE.saturating_sub(1))
+                    .unwrap_or_else(|_| panic!("Stack size {STACK_SIZE} is too large for the word type"));
+                registers.set_reg(Register::SP, sp);
+            }
+            registers
+        });
+        if let Some(sp) = self.stack_pointer {
+            registers.set_reg(Register::SP, sp);
+        }
+        if let Some(pc) = self.program_counter {
+            registers.set_reg(Register::PC, pc);
+        }
+
         Processor {
-            registers: self.registers.unwrap_or_default(),
+            stack_base: registers.sp(),
+            registers,
             stack: self.stack.unwrap_or_default(),
             program: self.program,
+            stack_growth: self.stack_growth,
+            halted: false,
+            fault: None,
+            call_depth: 0,
+            cycles: 0,
+            executing: None,
+            flag_policy: self.flag_policy,
+            #[cfg(feature = "alloc")]
+            profile: None,
+            #[cfg(feature = "alloc")]
+            history: None,
+            #[cfg(feature = "alloc")]
+            io: IoBus::default(),
         }
     }
 }
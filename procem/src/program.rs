@@ -6,9 +6,28 @@ use thiserror::Error;
 use crate::instruction::Instruction;
 use crate::word::Word;
 
+#[cfg(feature = "alloc")]
+use crate::instruction::Handler;
+#[cfg(feature = "alloc")]
+use crate::stack::Stack;
+#[cfg(feature = "alloc")]
+use alloc::{sync::Arc, vec::Vec};
+
 /// [`Program`] is a container for a sequence of instructions that is executed by the [`Processor`](crate::processor::Processor).
 ///
 /// An instruction can be fetched from the program using the [`fetch_instruction`](Program::fetch_instruction) method.
+///
+/// A program does not have to be assembled. It can be created from any container of instructions
+/// (e.g. an array or a `Vec`) using [`Program::new`] or [`From`].
+/// A borrowed slice works as well, so a program can point at a static instruction array without allocating.
+/// As [`Program::new`] is `const`, such a program can also be stored in a `static`.
+/// With the `alloc` feature a `Vec` based program can also be built incrementally
+/// using [`push`](Program::push) and [`Extend`].
+///
+/// Cloning a program clones its container. With an [`Arc<[I]>`](alloc::sync::Arc) container, created with
+/// [`Program::from_arc`], cloning is cheap and all clones share the same instructions. This way one program can be run
+/// by several processors at once, e.g. on different threads, each owning a clone (see
+/// [`Processor::load_program_owned()`](crate::processor::Processor::load_program_owned())).
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Program<I, T, W>(T, PhantomData<(I, W)>);
 
@@ -24,6 +43,7 @@ where
     }
 }
 
+/// Creates a program from a container of instructions. This is the same as [`Program::new`].
 impl<I, T, W> From<T> for Program<I, T, W>
 where
     I: Instruction<W>,
@@ -43,8 +63,8 @@ where
 {
     /// Creates a new program from the provided instructions.
     #[must_use]
-    pub fn new(instructions: T) -> Self {
-        instructions.into()
+    pub const fn new(instructions: T) -> Self {
+        Self(instructions, PhantomData)
     }
 
     /// Returns the instruction at the provided index.
@@ -63,12 +83,158 @@ where
             |instruction| Ok(*instruction),
         )
     }
+
+    /// Precomputes a dispatch table for this program, containing the [`Handler`] of every instruction.
+    ///
+    /// The table can be executed with [`Processor::run_program_dispatched()`](crate::processor::Processor::run_program_dispatched()).
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn dispatch_table<const STACK_SIZE: usize>(&self) -> Vec<Handler<STACK_SIZE, I, T, W>> {
+        self.iter()
+            .map(I::handler::<STACK_SIZE, T, Stack<STACK_SIZE, W>>)
+            .collect()
+    }
+
+    /// Checks the program for errors that would otherwise only be detected while running it.
+    ///
+    /// Every instruction with a known [`jump_target`](Instruction::jump_target) is checked to jump to an address
+    /// within the program. Jumping to the address directly after the last instruction is allowed, as it ends the program.
+    ///
+    /// # Errors
+    /// Returns all found [`ProgramValidationError`]s if the program is invalid.
+    #[cfg(feature = "alloc")]
+    pub fn validate(&self) -> Result<(), Vec<ProgramValidationError>> {
+        let program_len = self.len();
+        let errors: Vec<_> = self
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, instruction)| {
+                instruction
+                    .jump_target()
+                    .filter(|&target| target > program_len)
+                    .map(|target| ProgramValidationError::JumpTargetOutOfBounds {
+                        idx,
+                        target,
+                        program_len,
+                    })
+            })
+            .collect();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
 }
 
-#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "alloc")]
+impl<I, W> Program<I, Vec<I>, W>
+where
+    I: Instruction<W>,
+    W: Word,
+{
+    /// Appends an instruction to the end of the program.
+    ///
+    /// # Example
+    /// ```
+    /// # use procem::instruction::Instruction;
+    /// # use procem::processor::Processor;
+    /// # use procem::program::Program;
+    /// # use procem::register::{FlagSet, Register};
+    /// # use procem::stack::StackMemory;
+    /// # use procem::word::{I32, Word};
+    /// # use core::ops::Deref;
+    /// #
+    /// #[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd, Hash)]
+    /// enum Inst {
+    ///     Inc(Register),
+    ///     Double(Register),
+    /// }
+    ///
+    /// impl Instruction<I32> for Inst {
+    ///     type Flags = FlagSet;
+    ///
+    ///     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<I32>>(
+    ///         instruction: Self,
+    ///         processor: &mut Processor<STACK_SIZE, Self, P, I32, S>,
+    ///     ) {
+    ///         match instruction {
+    ///             Inst::Inc(reg) => processor.registers.inc(reg),
+    ///             Inst::Double(reg) => {
+    ///                 let val = processor.registers.get_reg(reg);
+    ///                 processor.registers.set_reg(reg, val + val);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut program = Program::new(Vec::new());
+    /// program.push(Inst::Inc(Register::R0));
+    /// program.extend([Inst::Double(Register::R0), Inst::Double(Register::R0)]);
+    ///
+    /// let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    /// let _ = processor.run_program();
+    ///
+    /// assert_eq!(processor.registers.get_reg(Register::R0), 4.into());
+    /// ```
+    #[inline]
+    pub fn push(&mut self, instruction: I) {
+        self.0.push(instruction);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, W> Program<I, Arc<[I]>, W>
+where
+    I: Instruction<W>,
+    W: Word,
+{
+    /// Creates a program from shared instructions without copying them. This is the same as [`Program::new`].
+    ///
+    /// Clones of the program share the instructions, e.g. a program assembled into a `Vec` can be shared with `Arc::from(vec)`.
+    #[must_use]
+    #[inline]
+    pub const fn from_arc(instructions: Arc<[I]>) -> Self {
+        Self::new(instructions)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, W> Extend<I> for Program<I, Vec<I>, W>
+where
+    I: Instruction<W>,
+    W: Word,
+{
+    /// Appends all instructions of the iterator to the end of the program.
+    #[inline]
+    fn extend<It: IntoIterator<Item = I>>(&mut self, iter: It) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ProgramError {
     #[error("Program counter out of bounds. Program length: {program_len}, Program counter: {pc}")]
     PCOutOfBounds { pc: usize, program_len: usize },
     #[error("No program loaded")]
     NoProgramLoaded,
+    #[error("Instruction limit of {limit} instructions exceeded")]
+    InstructionLimitExceeded { limit: u64 },
+    #[error("Return without call at program counter {pc}")]
+    ReturnWithoutCall { pc: usize },
+    #[error("Jump to invalid program counter {pc}")]
+    InvalidJumpTarget { pc: usize },
+    #[error("Pop from an empty stack at program counter {pc}")]
+    StackUnderflow { pc: usize },
+    #[error("Push onto a full stack at program counter {pc}")]
+    StackOverflow { pc: usize },
+    #[error("No handler connected to I/O port {port} at program counter {pc}")]
+    UnconnectedPort { port: usize, pc: usize },
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProgramValidationError {
+    #[error("Instruction {idx} jumps to address {target}, which is out of bounds. Program length: {program_len}")]
+    JumpTargetOutOfBounds {
+        idx: usize,
+        target: usize,
+        program_len: usize,
+    },
 }
@@ -1,56 +1,84 @@
-//! The [`Registers`] struct, [`Register`] enum and [`Flag`] enum.
-use core::fmt::Debug;
+//! The [`Registers`] struct, [`RegisterDump`] struct, [`Register`] enum, [`FlagRegister`] trait and [`Flag`] enum.
+use core::fmt::{Debug, Display};
+use core::hash::Hash;
+use core::ops::{Index, IndexMut};
 use core::str::FromStr;
 use thiserror::Error;
 
-use crate::helper::FmtArray;
+use crate::helper::{FmtArray, FmtHex, FmtHexArray};
 use crate::word::Word;
 
 #[cfg(feature = "alloc")]
-use alloc::string::{String, ToString};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
 
 pub const GENERAL_REGISTER_COUNT: usize = 16;
 
 /// The `Registers` struct provides general purpose registers,
 /// a program counter, a stack pointer and flags.
 ///
-/// There are [`GENERAL_REGISTER_COUNT`] general purpose registers (R1 - Rn).
+/// There are `REG_COUNT` general purpose registers (R0 - Rn), by default [`GENERAL_REGISTER_COUNT`].
 /// They can be accessed with the [`get_reg`](Registers::get_reg) and [`set_reg`](Registers::set_reg) methods by providing the corresponding [`Register`] value.
+/// A smaller register file does not have the higher registers, use [`try_get_reg`](Registers::try_get_reg) to access them without panicking.
 ///
 /// The program counter (pc) can be read with the [`pc`](Registers::pc) method and the stack pointer (sp) can be read with the [`sp`](Registers::sp) method.
 /// Both of these registers can also be accessed with the [`get_reg`](Registers::get_reg) and [`set_reg`](Registers::set_reg) methods.
+/// Setting the program counter this way bypasses the validation of [`Processor::jump`](crate::processor::Processor::jump), which instructions should use instead,
+/// and of [`Processor::set_pc`](crate::processor::Processor::set_pc).
 ///
 /// The register sizes correspond to the stack word size.
 ///
-/// The flags are carry flag ([`C`](Flag::C)), signed flag ([`S`](Flag::S)), overflow flag ([`V`](Flag::V)) and zero condition flag ([`Z`](Flag::Z)).
-/// They can be accessed with the [`get_flag`](Registers::get_flag) and [`set_flag`](Registers::set_flag) methods by providing the corresponding [`Flag`] value.
+/// The flags are given by the [`FlagRegister`] `F`, which is chosen by the instruction set with [`Instruction::Flags`](crate::instruction::Instruction::Flags).
+/// By default these are the [`FlagSet`] carry flag ([`C`](Flag::C)), signed flag ([`S`](Flag::S)), overflow flag ([`V`](Flag::V)), zero condition flag ([`Z`](Flag::Z))
+/// and parity flag ([`P`](Flag::P)).
+/// They can be accessed with the [`get_flag`](Registers::get_flag) and [`set_flag`](Registers::set_flag) methods by providing the corresponding flag, e.g. a [`Flag`] value.
+/// All flags can be accessed at once with the [`flags`](Registers::flags) and [`set_flags`](Registers::set_flags) methods.
+///
+/// A snapshot of all registers and the default flags can be taken with the [`dump`](Registers::dump) method.
+///
+/// Registers can also be indexed directly, e.g. `registers[Register::R0]`.
 ///
 /// There are two convenience methods for incrementing and decrementing registers: [`inc`](Registers::inc) and [`dec`](Registers::dec).
-#[derive(Debug, PartialEq, Eq, Clone, Hash, PartialOrd, Ord, Default)]
-pub struct Registers<W> {
+///
+/// Registers can be compared for equality but have no ordering, as there is no meaningful order of processor states.
+/// The same holds for a [`RegisterDump`], the [`FlagSet`], the stacks and the [`Processor`](crate::processor::Processor) itself.
+/// ```compile_fail
+/// # use procem::{register::Registers, word::I32};
+/// let sorted = Registers::<I32>::default() < Registers::<I32>::default();
+/// ```
+/// ```compile_fail
+/// # use procem::{register::RegisterDump, word::I32};
+/// let sorted = RegisterDump::<I32>::default() < RegisterDump::<I32>::default();
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Registers<W, F = FlagSet, const REG_COUNT: usize = GENERAL_REGISTER_COUNT> {
     // General purpose registers.
-    general: [W; GENERAL_REGISTER_COUNT],
+    general: [W; REG_COUNT],
     // Program counter register.
     pc: W,
     // Stack pointer register.
     sp: W,
-    // Flags: carry flag (C), signed flag (S), overflow flag (V), zero condition flag (Z).
-    flags: [bool; 4],
+    flags: F,
 }
 
-impl<W: Word> Registers<W> {
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> Registers<W, F, REG_COUNT> {
     /// Create a new set of registers with all values initialized to the default value.
     #[must_use]
     pub fn new() -> Self {
         Self {
-            general: [W::default(); GENERAL_REGISTER_COUNT],
+            general: [W::default(); REG_COUNT],
             pc: W::default(),
             sp: W::default(),
-            flags: [false; 4],
+            flags: F::default(),
         }
     }
 
     /// Get the value of a register.
+    ///
+    /// # Panics
+    /// Panics if the register is not part of the register file, e.g. `R8` with only 8 general purpose registers.
     #[inline]
     pub const fn get_reg(&self, reg: Register) -> W {
         match reg {
@@ -60,6 +88,20 @@ impl<W: Word> Registers<W> {
         }
     }
 
+    /// Get the value of a register or an error if the register is not part of the register file.
+    ///
+    /// # Errors
+    /// Returns `OutOfRange` if the index of the general purpose register is not below `REG_COUNT`.
+    #[inline]
+    pub const fn try_get_reg(&self, reg: Register) -> Result<W, RegisterError> {
+        match reg {
+            Register::PC => Ok(self.pc),
+            Register::SP => Ok(self.sp),
+            _ if (reg as usize) < REG_COUNT => Ok(self.general[reg as usize]),
+            _ => Err(RegisterError::OutOfRange { reg, count: REG_COUNT }),
+        }
+    }
+
     /// Get the value of the program counter register.
     #[inline]
     pub const fn pc(&self) -> W {
@@ -82,21 +124,41 @@ impl<W: Word> Registers<W> {
         }
     }
 
+    /// Get the values of the general purpose registers.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) const fn general(&self) -> &[W; REG_COUNT] {
+        &self.general
+    }
+
     /// Get the value of a flag.
     #[inline]
-    pub const fn get_flag(&self, f: Flag) -> bool {
-        match f {
-            Flag::C => self.flags[0],
-            Flag::S => self.flags[1],
-            Flag::V => self.flags[2],
-            Flag::Z => self.flags[3],
-        }
+    pub fn get_flag(&self, f: F::Flag) -> bool {
+        self.flags.get(f)
     }
 
     /// Set the value of a flag.
     #[inline]
-    pub const fn set_flag(&mut self, f: Flag, val: bool) {
-        self.flags[f as usize] = val;
+    pub fn set_flag(&mut self, f: F::Flag, val: bool) {
+        self.flags.set(f, val);
+    }
+
+    /// Get the values of all flags.
+    #[inline]
+    pub const fn flags(&self) -> F {
+        self.flags
+    }
+
+    /// Set the values of all flags.
+    #[inline]
+    pub const fn set_flags(&mut self, flags: F) {
+        self.flags = flags;
+    }
+
+    /// Iterate over all flags of the [`FlagRegister`] with their current values, in the order of [`FlagRegister::ALL`].
+    #[inline]
+    pub fn flag_values(&self) -> impl Iterator<Item = (F::Flag, bool)> {
+        F::ALL.iter().map(move |&flag| (flag, self.get_flag(flag)))
     }
 
     /// Increment the value in a register by one.
@@ -118,21 +180,129 @@ impl<W: Word> Registers<W> {
             _ => self.general[reg as usize] -= 1.into(),
         }
     }
+
+    /// Formats the registers like [`Display`](core::fmt::Display), but renders every register in hexadecimal,
+    /// zero-padded to the width of the word.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn format_hex(&self) -> String {
+        format!("{self:X}")
+    }
+}
+
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> Default for Registers<W, F, REG_COUNT> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Word> Registers<W> {
+    /// Take a snapshot of all registers and flags.
+    ///
+    /// Unlike the [`Display`](core::fmt::Display) output, the returned [`RegisterDump`]
+    /// is meant for programmatic inspection and comparison.
+    #[inline]
+    pub const fn dump(&self) -> RegisterDump<W> {
+        RegisterDump {
+            general: self.general,
+            pc: self.pc,
+            sp: self.sp,
+            c: self.flags.c,
+            s: self.flags.s,
+            v: self.flags.v,
+            z: self.flags.z,
+            p: self.flags.p,
+        }
+    }
+
+    /// Formats the flags compactly in the order `CSVZP`, with set flags in uppercase and clear flags in lowercase,
+    /// e.g. `"csVzp"` if only the overflow flag is set.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn flags_string(&self) -> String {
+        [
+            ('C', Flag::C),
+            ('S', Flag::S),
+            ('V', Flag::V),
+            ('Z', Flag::Z),
+            ('P', Flag::P),
+        ]
+        .into_iter()
+        .map(|(name, flag)| {
+            if self.get_flag(flag) {
+                name
+            } else {
+                name.to_ascii_lowercase()
+            }
+        })
+        .collect()
+    }
+}
+
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> Index<Register> for Registers<W, F, REG_COUNT> {
+    type Output = W;
+
+    #[inline]
+    fn index(&self, reg: Register) -> &Self::Output {
+        match reg {
+            Register::PC => &self.pc,
+            Register::SP => &self.sp,
+            _ => &self.general[reg as usize],
+        }
+    }
 }
 
-impl<W: Word> core::fmt::Display for Registers<W> {
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> IndexMut<Register> for Registers<W, F, REG_COUNT> {
+    #[inline]
+    fn index_mut(&mut self, reg: Register) -> &mut Self::Output {
+        match reg {
+            Register::PC => &mut self.pc,
+            Register::SP => &mut self.sp,
+            _ => &mut self.general[reg as usize],
+        }
+    }
+}
+
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> core::fmt::Display for Registers<W, F, REG_COUNT> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(f, "general:\t")?;
         writeln!(f, "{}", FmtArray(self.general.as_slice()))?;
         writeln!(f, "pc:\t\t{}\nsp:\t\t{}", self.pc, self.sp)?;
-        writeln!(
-            f,
-            "flags:\t\t[C: {}, S: {}, V: {}, Z: {}]",
-            self.flags[0], self.flags[1], self.flags[2], self.flags[3]
-        )
+        writeln!(f, "flags:\t\t{}", self.flags)
+    }
+}
+
+impl<W: Word, F: FlagRegister, const REG_COUNT: usize> core::fmt::UpperHex for Registers<W, F, REG_COUNT> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "general:\t")?;
+        writeln!(f, "{}", FmtHexArray(self.general.as_slice()))?;
+        writeln!(f, "pc:\t\t{}\nsp:\t\t{}", FmtHex(self.pc), FmtHex(self.sp))?;
+        writeln!(f, "flags:\t\t{}", self.flags)
     }
 }
 
+/// A snapshot of the values of all registers and the default [`FlagSet`]. See [`Registers::dump`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct RegisterDump<W> {
+    /// General purpose registers (R0 - R15).
+    pub general: [W; GENERAL_REGISTER_COUNT],
+    /// Program counter register.
+    pub pc: W,
+    /// Stack pointer register.
+    pub sp: W,
+    /// Carry flag.
+    pub c: bool,
+    /// Signed flag.
+    pub s: bool,
+    /// Overflow flag.
+    pub v: bool,
+    /// Zero condition flag.
+    pub z: bool,
+    /// Parity flag.
+    pub p: bool,
+}
+
 /// Register enum.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
 pub enum Register {
@@ -156,6 +326,76 @@ pub enum Register {
     SP,
 }
 
+impl core::fmt::Display for Register {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Register {
+    /// All registers in the order of their declaration.
+    #[must_use]
+    #[inline]
+    pub const fn all() -> [Self; 18] {
+        [
+            Self::R0,
+            Self::R1,
+            Self::R2,
+            Self::R3,
+            Self::R4,
+            Self::R5,
+            Self::R6,
+            Self::R7,
+            Self::R8,
+            Self::R9,
+            Self::R10,
+            Self::R11,
+            Self::R12,
+            Self::R13,
+            Self::R14,
+            Self::R15,
+            Self::PC,
+            Self::SP,
+        ]
+    }
+
+    /// Returns the name of the register that is closest to the input, if it is at most one edit away.
+    ///
+    /// The case of the input is ignored. On a tie the register with the length of the input is preferred,
+    /// then the one that is declared first, e.g. `RO` suggests `R0` and `R1O` suggests `R10`.
+    #[cfg(feature = "alloc")]
+    fn suggest(input: &str) -> Option<String> {
+        let input = input.to_ascii_uppercase();
+        Self::all()
+            .into_iter()
+            .map(|reg| reg.to_string())
+            .map(|name| (edit_distance(&input, &name), name.len().abs_diff(input.len()), name))
+            .filter(|&(distance, ..)| distance <= 1)
+            .min_by_key(|&(distance, len_diff, _)| (distance, len_diff))
+            .map(|(.., name)| name)
+    }
+}
+
+/// The Levenshtein distance between two strings, i.e. the number of inserted, removed or replaced characters.
+#[cfg(feature = "alloc")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: alloc::vec::Vec<char> = b.chars().collect();
+    // distances from the processed prefix of `a` to every prefix of `b`
+    let mut row: alloc::vec::Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let replaced = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = replaced.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
 impl FromStr for Register {
     type Err = RegisterError;
     fn from_str(value: &str) -> Result<Self, Self::Err> {
@@ -182,6 +422,7 @@ impl FromStr for Register {
                 #[cfg(feature = "alloc")]
                 RegisterError::ConversionFailed {
                     input: value.to_string(),
+                    suggestion: Self::suggest(value),
                 },
                 #[cfg(not(feature = "alloc"))]
                 RegisterError::ConversionFailed,
@@ -190,7 +431,26 @@ impl FromStr for Register {
     }
 }
 
-/// Flag enum.
+/// The flag register of the [`Registers`].
+///
+/// The register is the storage of the values of its flags, which are named by the [`Flag`](FlagRegister::Flag) type.
+/// Instruction sets choose their flag register with [`Instruction::Flags`](crate::instruction::Instruction::Flags),
+/// e.g. the default [`FlagSet`], [`NoFlags`] or one of their own with a parity flag.
+pub trait FlagRegister: Debug + Display + Clone + Copy + PartialEq + Eq + Hash + Default {
+    /// The flags of the set, usually an enum.
+    type Flag: Debug + Clone + Copy + PartialEq + Eq + Hash + 'static;
+
+    /// All flags of the set, e.g. to render the flags without knowing the set.
+    const ALL: &'static [Self::Flag];
+
+    /// Get the value of a flag.
+    fn get(&self, flag: Self::Flag) -> bool;
+
+    /// Set the value of a flag.
+    fn set(&mut self, flag: Self::Flag, val: bool);
+}
+
+/// Flag enum of the default [`FlagSet`].
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub enum Flag {
     /// Carry flag. Normally set when an addition results in a carry or a subtraction results in a borrow.
@@ -201,14 +461,283 @@ pub enum Flag {
     V,
     /// Zero condition flag. Normally set when the last arithmetic, logical or bitwise computation resulted in zero.
     Z,
+    /// Parity flag. Normally set when the low byte of the result of the last arithmetic, logical or bitwise computation
+    /// has an even number of set bits.
+    P,
+}
+
+impl Flag {
+    /// All flags in the order they are displayed.
+    #[must_use]
+    #[inline]
+    pub const fn all() -> [Self; 5] {
+        [Self::C, Self::S, Self::V, Self::Z, Self::P]
+    }
+}
+
+/// The default [`FlagRegister`] with the values of the five flags of [`Flag`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct FlagSet {
+    /// Carry flag.
+    pub c: bool,
+    /// Signed flag.
+    pub s: bool,
+    /// Overflow flag.
+    pub v: bool,
+    /// Zero condition flag.
+    pub z: bool,
+    /// Parity flag.
+    pub p: bool,
+}
+
+impl FlagRegister for FlagSet {
+    type Flag = Flag;
+
+    const ALL: &'static [Flag] = &Flag::all();
+
+    #[inline]
+    fn get(&self, flag: Flag) -> bool {
+        match flag {
+            Flag::C => self.c,
+            Flag::S => self.s,
+            Flag::V => self.v,
+            Flag::Z => self.z,
+            Flag::P => self.p,
+        }
+    }
+
+    #[inline]
+    fn set(&mut self, flag: Flag, val: bool) {
+        match flag {
+            Flag::C => self.c = val,
+            Flag::S => self.s = val,
+            Flag::V => self.v = val,
+            Flag::Z => self.z = val,
+            Flag::P => self.p = val,
+        }
+    }
+}
+
+impl core::fmt::Display for FlagSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "[C: {}, S: {}, V: {}, Z: {}, P: {}]",
+            self.c, self.s, self.v, self.z, self.p
+        )
+    }
+}
+
+/// A [`FlagRegister`] without any flags, for instruction sets that do not use flags.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+pub struct NoFlags;
+
+impl FlagRegister for NoFlags {
+    type Flag = core::convert::Infallible;
+
+    const ALL: &'static [Self::Flag] = &[];
+
+    #[inline]
+    fn get(&self, flag: Self::Flag) -> bool {
+        match flag {}
+    }
+
+    #[inline]
+    fn set(&mut self, flag: Self::Flag, _val: bool) {
+        match flag {}
+    }
+}
+
+impl core::fmt::Display for NoFlags {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "[]")
+    }
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq, Hash)]
 pub enum RegisterError {
     #[cfg(feature = "alloc")]
-    #[error("Failed to convert {input} into a register.")]
-    ConversionFailed { input: String },
+    #[error(
+        "Failed to convert {input} into a register.{}",
+        suggestion.as_ref().map_or_else(String::new, |reg| format!(" Did you mean {reg}?"))
+    )]
+    ConversionFailed {
+        input: String,
+        /// The name of the closest register, if the input is only one edit away from it.
+        suggestion: Option<String>,
+    },
     #[cfg(not(feature = "alloc"))]
     #[error("Invalid register name. Conversion into register failed.")]
     ConversionFailed,
+    #[error("Register {reg} is out of range of the {count} general purpose registers.")]
+    OutOfRange { reg: Register, count: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word::I8;
+    use alloc::{collections::BTreeMap, string::ToString};
+
+    #[test]
+    fn test_flags_round_trip() {
+        let mut registers = Registers::<I8>::new();
+        assert_eq!(registers.flags(), FlagSet::default());
+
+        let flags = FlagSet {
+            c: true,
+            s: false,
+            v: true,
+            z: false,
+            p: true,
+        };
+        registers.set_flags(flags);
+
+        assert_eq!(registers.flags(), flags);
+        assert!(registers.get_flag(Flag::C));
+        assert!(!registers.get_flag(Flag::S));
+        assert!(registers.get_flag(Flag::V));
+        assert!(!registers.get_flag(Flag::Z));
+        assert!(registers.get_flag(Flag::P));
+        assert_eq!(flags.to_string(), "[C: true, S: false, V: true, Z: false, P: true]");
+    }
+
+    #[test]
+    fn test_flag_values() {
+        let mut registers = Registers::<I8>::new();
+        registers.set_flag(Flag::S, true);
+        registers.set_flag(Flag::P, true);
+
+        let values: BTreeMap<_, _> = registers.flag_values().collect();
+        assert_eq!(values.len(), Flag::all().len());
+        for flag in Flag::all() {
+            assert_eq!(values[&flag], registers.get_flag(flag), "{flag:?}");
+        }
+        assert!(values[&Flag::S] && values[&Flag::P]);
+
+        assert_eq!(Registers::<I8, NoFlags>::new().flag_values().count(), 0);
+    }
+
+    #[test]
+    fn test_dump() {
+        let run = || {
+            let mut registers = Registers::<I8>::new();
+            registers.set_reg(Register::R3, 42.into());
+            registers.inc(Register::PC);
+            registers.dec(Register::SP);
+            registers.set_flag(Flag::Z, true);
+            registers
+        };
+
+        let dump = run().dump();
+        assert_eq!(dump, run().dump());
+        assert_ne!(dump, Registers::<I8>::new().dump());
+
+        let mut general = [I8::default(); GENERAL_REGISTER_COUNT];
+        general[3] = 42.into();
+        assert_eq!(
+            dump,
+            RegisterDump {
+                general,
+                pc: 1.into(),
+                sp: (-1).into(),
+                c: false,
+                s: false,
+                v: false,
+                z: true,
+                p: false,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_flags_string() {
+        let mut registers = Registers::<I8>::new();
+        assert_eq!(registers.flags_string(), "csvzp");
+
+        registers.set_flag(Flag::V, true);
+        assert_eq!(registers.flags_string(), "csVzp");
+
+        registers.set_flag(Flag::C, true);
+        registers.set_flag(Flag::Z, true);
+        assert_eq!(registers.flags_string(), "CsVZp");
+
+        registers.set_flag(Flag::S, true);
+        registers.set_flag(Flag::P, true);
+        assert_eq!(registers.flags_string(), "CSVZP");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_format_hex() {
+        let mut registers = Registers::<I8>::new();
+        registers.set_reg(Register::R0, (-1).into());
+        registers.set_reg(Register::PC, 10.into());
+
+        assert_eq!(
+            registers.format_hex(),
+            "general:\t[FF, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]\n\
+             pc:\t\t0A\nsp:\t\t00\n\
+             flags:\t\t[C: false, S: false, V: false, Z: false, P: false]\n"
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let mut registers = Registers::<I8>::new();
+        registers[Register::R5] = 5.into();
+        registers[Register::PC] = 10.into();
+        registers[Register::SP] = 20.into();
+
+        assert_eq!(registers[Register::R5], 5.into());
+        assert_eq!(registers[Register::PC], 10.into());
+        assert_eq!(registers[Register::SP], 20.into());
+        assert_eq!(registers.get_reg(Register::R5), 5.into());
+        assert_eq!(registers.pc(), 10.into());
+        assert_eq!(registers.sp(), 20.into());
+        assert_eq!(registers[Register::R0], 0.into());
+    }
+
+    #[test]
+    fn test_try_get_reg() {
+        let mut registers = Registers::<I8, FlagSet, 8>::new();
+        registers.set_reg(Register::R7, 7.into());
+        registers.set_reg(Register::SP, 1.into());
+
+        assert_eq!(registers.try_get_reg(Register::R7), Ok(7.into()));
+        assert_eq!(registers.try_get_reg(Register::SP), Ok(1.into()));
+        assert_eq!(
+            registers.try_get_reg(Register::R8),
+            Err(RegisterError::OutOfRange {
+                reg: Register::R8,
+                count: 8,
+            })
+        );
+        assert_eq!(Registers::<I8>::new().try_get_reg(Register::R15), Ok(0.into()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_suggestion() {
+        let suggestion = |input: &str| match input.parse::<Register>() {
+            Err(RegisterError::ConversionFailed { suggestion, .. }) => suggestion,
+            Err(err) => panic!("{input} failed with {err}"),
+            Ok(reg) => panic!("{input} was parsed as {reg}"),
+        };
+
+        assert_eq!(suggestion("RO").as_deref(), Some("R0"));
+        assert_eq!(suggestion("R1O").as_deref(), Some("R10"));
+        assert_eq!(suggestion("PCC").as_deref(), Some("PC"));
+        assert_eq!(suggestion("sp ").as_deref(), Some("SP"));
+        assert_eq!(suggestion("ACC"), None);
+        assert_eq!(
+            "RO".parse::<Register>().unwrap_err().to_string(),
+            "Failed to convert RO into a register. Did you mean R0?"
+        );
+        assert_eq!(
+            "ACC".parse::<Register>().unwrap_err().to_string(),
+            "Failed to convert ACC into a register."
+        );
+    }
 }
@@ -1,18 +1,101 @@
-//! The processor's [`Stack`].
+//! The processor's [`Stack`], the [`StackMemory`] trait and the [`StackGrowth`] direction.
+//!
+//! With the `alloc` feature a growable [`DynStack`] is available as well.
 
 use crate::helper;
+use crate::register::{FlagRegister, Register, Registers};
 use crate::word::Word;
-use core::fmt::{Debug, Display, Formatter};
+use core::fmt::{Debug, Display, Formatter, UpperHex};
+use core::ops::Range;
+use thiserror::Error;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
 use core::ops::{Deref, DerefMut};
 
+/// The direction in which the stack grows when values are pushed.
+///
+/// The stack pointer always points to the value on top of the stack.
+/// As a push moves the stack pointer before writing, the slot at the initial stack pointer is never written,
+/// e.g. index 0 for [`Up`](StackGrowth::Up) and index `STACK_SIZE - 1` for [`Down`](StackGrowth::Down).
+/// A stack of `STACK_SIZE` values therefore holds at most `STACK_SIZE - 1` pushed values.
+/// The direction is configured with [`ProcessorBuilder::with_stack_growth()`](crate::processor::ProcessorBuilder::with_stack_growth()),
+/// the initial stack pointer can be changed with [`ProcessorBuilder::with_stack_pointer()`](crate::processor::ProcessorBuilder::with_stack_pointer()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum StackGrowth {
+    /// Pushing a value increments the stack pointer. The stack pointer starts at 0.
+    #[default]
+    Up,
+    /// Pushing a value decrements the stack pointer. The stack pointer starts at `STACK_SIZE - 1`.
+    Down,
+}
+
+impl StackGrowth {
+    /// Moves the stack pointer to the slot of a value that is pushed.
+    #[inline]
+    pub fn push<W: Word, F: FlagRegister>(self, registers: &mut Registers<W, F>) {
+        match self {
+            Self::Up => registers.inc(Register::SP),
+            Self::Down => registers.dec(Register::SP),
+        }
+    }
+
+    /// Moves the stack pointer back to the value below the popped value.
+    #[inline]
+    pub fn pop<W: Word, F: FlagRegister>(self, registers: &mut Registers<W, F>) {
+        match self {
+            Self::Up => registers.dec(Register::SP),
+            Self::Down => registers.inc(Register::SP),
+        }
+    }
+}
+
+/// The [`StackMemory`] trait is implemented by all stacks that can be used by the [`Processor`](crate::processor::Processor).
+///
+/// The processor uses a [`Stack`] by default.
+pub trait StackMemory<W: Word> {
+    /// Read a value from the stack at the given stack pointer.
+    ///
+    /// # Panics
+    /// Panics if the stack pointer is out of bounds.
+    fn read(&self, sp: W) -> W;
+
+    /// Write a value to the stack at the given stack pointer.
+    ///
+    /// # Panics
+    /// Panics if the stack pointer is out of bounds.
+    fn write(&mut self, sp: W, value: W);
+
+    /// Read `len` values starting at the address `addr` with a single bounds check.
+    ///
+    /// # Errors
+    /// Returns `StackError::OutOfBounds` if the region is not within the stack.
+    fn read_region(&self, addr: usize, len: usize) -> Result<&[W], StackError>;
+
+    /// Write all values of `data` starting at the address `addr` with a single bounds check.
+    ///
+    /// # Errors
+    /// Returns `StackError::OutOfBounds` if the region is not within the stack. Nothing is written in that case.
+    fn write_region(&mut self, addr: usize, data: &[W]) -> Result<(), StackError>;
+}
+
+/// Returns the range of the region `addr..addr + len` if it ends within `stack_size`.
+fn region(addr: usize, len: usize, stack_size: usize) -> Result<Range<usize>, StackError> {
+    addr.checked_add(len)
+        .filter(|&end| end <= stack_size)
+        .map(|end| addr..end)
+        .ok_or(StackError::OutOfBounds { addr, len, stack_size })
+}
+
 /// The [`Stack`] is a wrapper around a fixed-size array of values implementing the [`Word`] trait.
 ///
 /// It can be read with the [`read`](Stack::read) method. It can also be written to with the [`write`](Stack::write) method.
 /// For both reading and writing, the stack pointer needs to be provided.
 /// ```
-/// # use procem::register::{Flag, Register};
+/// # use procem::register::{Flag, FlagSet, Register};
 /// # use procem::processor::Processor;
 /// # use procem::instruction::Instruction;
+/// # use procem::stack::StackMemory;
 /// # use procem::word::{I64, Word};
 /// # use core::marker::PhantomData;
 /// # use core::ops::Deref;
@@ -21,9 +104,11 @@ use core::ops::{Deref, DerefMut};
 /// # struct Inst<W: Word> (PhantomData<W>);
 /// #
 /// # impl<W: Word> Instruction<W> for Inst<W> {
-/// #     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+/// #     type Flags = FlagSet;
+/// #
+/// #     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
 /// #         instruction: Self,
-/// #         processor: &mut Processor<STACK_SIZE, Self, P, W>
+/// #         processor: &mut Processor<STACK_SIZE, Self, P, W, S>
 /// #     ) {}
 /// # }
 /// # let mut processor = Processor::<4, _,  Vec<Inst<I64>>,_>::new();
@@ -37,7 +122,7 @@ use core::ops::{Deref, DerefMut};
 /// processor.stack.write(processor.registers.get_reg(Register::SP), 10.into());
 /// assert_eq!(processor.stack.read(processor.registers.get_reg(Register::SP)), 10.into());
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct Stack<const STACK_SIZE: usize, W>([W; STACK_SIZE]);
 
@@ -67,6 +152,12 @@ impl<const STACK_SIZE: usize, W: Word> Display for Stack<STACK_SIZE, W> {
     }
 }
 
+impl<const STACK_SIZE: usize, W: Word> UpperHex for Stack<STACK_SIZE, W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", helper::FmtHexArray(self.deref().as_slice()))
+    }
+}
+
 impl<const STACK_SIZE: usize, W: Word> Stack<STACK_SIZE, W> {
     /// Create a new stack with all elements initialized to the default value.
     #[must_use]
@@ -94,4 +185,288 @@ impl<const STACK_SIZE: usize, W: Word> Stack<STACK_SIZE, W> {
             .unwrap_or_else(|| panic!("Out of bounds stack access. Stack size: {STACK_SIZE}, Stack pointer: {sp}")) =
             value;
     }
+
+    /// Formats the stack like [`Display`], but renders every value in hexadecimal, zero-padded to the width of the word.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn format_hex(&self) -> String {
+        format!("{self:X}")
+    }
+}
+
+impl<const STACK_SIZE: usize, W: Word> StackMemory<W> for Stack<STACK_SIZE, W> {
+    #[inline]
+    fn read(&self, sp: W) -> W {
+        Self::read(self, sp)
+    }
+
+    #[inline]
+    fn write(&mut self, sp: W, value: W) {
+        Self::write(self, sp, value);
+    }
+
+    fn read_region(&self, addr: usize, len: usize) -> Result<&[W], StackError> {
+        Ok(&self.0[region(addr, len, STACK_SIZE)?])
+    }
+
+    fn write_region(&mut self, addr: usize, data: &[W]) -> Result<(), StackError> {
+        self.0[region(addr, data.len(), STACK_SIZE)?].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// The [`DynStack`] is a growable stack backed by a [`Vec`].
+///
+/// Writing past the end of the stack grows it, so the needed stack size does not have to be known in advance.
+/// The stack never grows beyond its `limit`. Values that were never written are zero, just like in a [`Stack`].
+///
+/// It can be used instead of a [`Stack`] by setting the stack type of the [`Processor`](crate::processor::Processor):
+/// ```
+/// # use procem::register::{FlagSet, Register};
+/// # use procem::processor::Processor;
+/// # use procem::instruction::Instruction;
+/// # use procem::stack::{DynStack, StackMemory};
+/// # use procem::word::{I64, Word};
+/// # use core::marker::PhantomData;
+/// # use core::ops::Deref;
+/// #
+/// # #[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd, Hash)]
+/// # struct Inst<W: Word> (PhantomData<W>);
+/// #
+/// # impl<W: Word> Instruction<W> for Inst<W> {
+/// #     type Flags = FlagSet;
+/// #
+/// #     fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+/// #         instruction: Self,
+/// #         processor: &mut Processor<STACK_SIZE, Self, P, W, S>
+/// #     ) {}
+/// # }
+/// // The stack size is ignored when using a `DynStack`.
+/// let mut processor = Processor::<0, _, Vec<Inst<I64>>, _, DynStack<_>>::builder()
+///     .with_stack(DynStack::with_limit(1024))
+///     .build();
+///
+/// processor.stack.write(100.into(), 1.into());
+/// assert_eq!(processor.stack.read(100.into()), 1.into());
+/// assert_eq!(processor.stack.len(), 101);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynStack<W> {
+    values: Vec<W>,
+    limit: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> Deref for DynStack<W> {
+    type Target = [W];
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> Default for DynStack<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> Display for DynStack<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", helper::FmtArray(&self.values))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> UpperHex for DynStack<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", helper::FmtHexArray(&self.values))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> DynStack<W> {
+    /// Create a new empty stack that can grow up to [`usize::MAX`] values.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// Create a new empty stack that can grow up to `limit` values.
+    #[must_use]
+    pub const fn with_limit(limit: usize) -> Self {
+        Self {
+            values: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Create a new empty stack that can grow up to `limit` values and has space for `capacity` values
+    /// before it has to reallocate.
+    #[must_use]
+    pub fn with_capacity(capacity: usize, limit: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity.min(limit)),
+            limit,
+        }
+    }
+
+    /// Returns the maximum number of values the stack can grow to.
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Read a value from the stack at the given stack pointer.
+    ///
+    /// Reading past the end of the stack returns zero without growing the stack.
+    ///
+    /// # Panics
+    /// Panics if the stack pointer is not below the limit of the stack.
+    pub fn read(&self, sp: W) -> W {
+        let idx = self.check_bounds(sp);
+        self.get(idx).copied().unwrap_or_default()
+    }
+
+    /// Write a value to the stack at the given stack pointer.
+    ///
+    /// Writing past the end of the stack grows the stack up to the stack pointer.
+    ///
+    /// # Panics
+    /// Panics if the stack pointer is not below the limit of the stack.
+    pub fn write(&mut self, sp: W, value: W) {
+        let idx = self.check_bounds(sp);
+        if idx >= self.values.len() {
+            self.values.resize(idx + 1, W::default());
+        }
+        self.values[idx] = value;
+    }
+
+    /// Formats the stack like [`Display`], but renders every value in hexadecimal, zero-padded to the width of the word.
+    #[must_use]
+    pub fn format_hex(&self) -> String {
+        format!("{self:X}")
+    }
+
+    fn check_bounds(&self, sp: W) -> usize {
+        let idx = sp.into();
+        assert!(
+            idx < self.limit,
+            "Out of bounds stack access. Stack limit: {}, Stack pointer: {sp}",
+            self.limit
+        );
+        idx
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Word> StackMemory<W> for DynStack<W> {
+    #[inline]
+    fn read(&self, sp: W) -> W {
+        Self::read(self, sp)
+    }
+
+    #[inline]
+    fn write(&mut self, sp: W, value: W) {
+        Self::write(self, sp, value);
+    }
+
+    /// Values past the end of the stack cannot be borrowed, so the region has to be within the current length of the stack.
+    fn read_region(&self, addr: usize, len: usize) -> Result<&[W], StackError> {
+        Ok(&self.values[region(addr, len, self.values.len())?])
+    }
+
+    /// Writing past the end of the stack grows it up to its limit.
+    fn write_region(&mut self, addr: usize, data: &[W]) -> Result<(), StackError> {
+        let range = region(addr, data.len(), self.limit)?;
+        if range.end > self.values.len() {
+            self.values.resize(range.end, W::default());
+        }
+        self.values[range].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StackError {
+    #[error("Stack region {addr}..{} is out of bounds. Stack size: {stack_size}", .addr.saturating_add(*.len))]
+    OutOfBounds { addr: usize, len: usize, stack_size: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word::I8;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_format_hex() {
+        use crate::word::I32;
+
+        let mut stack = Stack::<2, I8>::new();
+        stack.write(0.into(), (-1).into());
+        assert_eq!(stack.format_hex(), "[FF, 00]");
+
+        let mut stack = Stack::<2, I32>::new();
+        stack.write(1.into(), 255.into());
+        assert_eq!(stack.format_hex(), "[00000000, 000000FF]");
+    }
+
+    #[test]
+    fn test_regions() {
+        let mut stack = Stack::<4, I8>::new();
+        assert_eq!(
+            stack.write_region(2, &[1.into(), 2.into(), 3.into()]),
+            Err(StackError::OutOfBounds {
+                addr: 2,
+                len: 3,
+                stack_size: 4
+            })
+        );
+        assert_eq!(stack.read_region(0, 4), Ok([0.into(); 4].as_slice()));
+        assert!(stack.read_region(usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_dyn_stack_regions() {
+        let mut stack = DynStack::<I8>::with_limit(8);
+        stack.write_region(4, &[1.into(), 2.into()]).unwrap();
+        assert_eq!(stack.len(), 6);
+        assert_eq!(stack.read_region(3, 3), Ok([0.into(), 1.into(), 2.into()].as_slice()));
+        assert!(stack.read_region(5, 2).is_err());
+        assert!(stack.write_region(7, &[1.into(), 2.into()]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_dyn_stack_grows() {
+        use crate::word::I32;
+
+        let mut stack = DynStack::<I32>::with_capacity(2, 16);
+        for i in 0..8 {
+            stack.write(i.into(), (i * 10).into());
+        }
+
+        assert_eq!(stack.len(), 8);
+        for i in 0..8 {
+            assert_eq!(stack.read(i.into()), (i * 10).into());
+        }
+        // unwritten values are zero
+        assert_eq!(stack.read(15.into()), 0.into());
+        assert_eq!(stack.len(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "Out of bounds stack access")]
+    fn test_dyn_stack_limit() {
+        use crate::word::I32;
+
+        let mut stack = DynStack::<I32>::with_limit(4);
+        stack.write(4.into(), 1.into());
+    }
 }
@@ -1,6 +1,6 @@
 //! The [`Word`] trait, its super traits and its implementations for all signed integer types.
 
-use core::fmt::{Debug, Display};
+use core::fmt::{Binary, Debug, Display, LowerHex, UpperHex};
 use core::num::ParseIntError;
 use core::ops::{
     Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign, Mul, MulAssign,
@@ -9,9 +9,9 @@ use core::ops::{
 
 /// The `WordBase` trait defines the base trait constraints for the Word type.
 /// It has a blanket implementation for all types that implement its super traits.
-pub trait WordBase: Debug + Display + Copy + Eq + Ord + Default {}
+pub trait WordBase: Debug + Display + LowerHex + UpperHex + Binary + Copy + Eq + Ord + Default {}
 
-impl<T> WordBase for T where T: Debug + Display + Copy + Eq + Ord + Default {}
+impl<T> WordBase for T where T: Debug + Display + LowerHex + UpperHex + Binary + Copy + Eq + Ord + Default {}
 
 /// The `WordConvert` trait defines the convertion trait constraints for the Word type.
 /// It has a blanket implementation for all types that implement its super traits.
@@ -96,9 +96,28 @@ impl<T> WordBitOps for T where
 /// - [`I128`]
 /// - [`ISize`]
 ///
+/// It is also implemented for [`Narrow`] words with less than 8 bits, e.g. [`I1`] and [`I4`].
+///
 /// These types use two's complement representation, mirroring how real-world processor architectures work.
 /// To implement custom [`Word`] types, you can define your own type that implements the [`Word`] trait.
 pub trait Word: WordBase + WordConvert + WordOps + WordBitOps {
+    /// The size of the word in bits.
+    const BITS: u32;
+
+    /// The little-endian byte representation of the word, e.g. `[u8; 4]` for a 32-bit word.
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Debug + Copy + Eq + Default;
+
+    /// Returns the memory representation of the word as a byte array in little-endian byte order.
+    #[must_use]
+    fn to_le_bytes(&self) -> Self::Bytes;
+
+    /// Creates a word from its memory representation as a byte slice in little-endian byte order.
+    ///
+    /// # Panics
+    /// Panics if the length of the slice differs from the length of [`Bytes`](Word::Bytes).
+    #[must_use]
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+
     /// This is a wrapper around the [`from_str_radix()`](i32::from_str_radix()) function that is implemented for all of Rust's numeric types.
     ///
     /// # Errors
@@ -136,12 +155,194 @@ pub trait Word: WordBase + WordConvert + WordOps + WordBitOps {
     #[must_use]
     fn overflowing_div(&self, rhs: Self) -> (Self, bool);
 
+    /// Convenience wrapper over Rust's [`saturating_add()`](i32::saturating_add()).
+    #[must_use]
+    fn saturating_add(&self, rhs: Self) -> Self;
+    /// Convenience wrapper over Rust's [`saturating_sub()`](i32::saturating_sub()).
+    #[must_use]
+    fn saturating_sub(&self, rhs: Self) -> Self;
+    /// Convenience wrapper over Rust's [`saturating_mul()`](i32::saturating_mul()).
+    #[must_use]
+    fn saturating_mul(&self, rhs: Self) -> Self;
+
     /// Convenience wrapper over Rust's [`rotate_left()`](i32::rotate_left()).
     #[must_use]
     fn rotate_left(&self, val: u32) -> Self;
     /// Convenience wrapper over Rust's [`rotate_right()`](i32::rotate_right()).
     #[must_use]
     fn rotate_right(&self, val: u32) -> Self;
+    /// Convenience wrapper over Rust's [`reverse_bits()`](i32::reverse_bits()).
+    /// The least significant bit becomes the most significant bit and vice versa.
+    #[must_use]
+    fn reverse_bits(&self) -> Self;
+    /// Convenience wrapper over Rust's [`leading_zeros()`](i32::leading_zeros()).
+    /// Returns [`BITS`](Word::BITS) for zero.
+    #[must_use]
+    fn leading_zeros(&self) -> u32;
+    /// Convenience wrapper over Rust's [`trailing_zeros()`](i32::trailing_zeros()).
+    /// Returns [`BITS`](Word::BITS) for zero.
+    #[must_use]
+    fn trailing_zeros(&self) -> u32;
+    /// Convenience wrapper over Rust's [`swap_bytes()`](i32::swap_bytes()).
+    /// Reverses the order of the bytes, e.g. to convert between little and big endian.
+    #[must_use]
+    fn swap_bytes(&self) -> Self;
+
+    /// Convenience wrapper over Rust's [`signum()`](i32::signum()).
+    /// Returns -1 for negative, 0 for zero and 1 for positive words.
+    #[must_use]
+    fn signum(&self) -> Self;
+
+    /// Convenience wrapper over Rust's [`is_negative()`](i32::is_negative()).
+    /// Returns true if the sign bit of the word is set.
+    #[must_use]
+    fn is_negative(&self) -> bool;
+
+    /// Returns true if all bits of the word are zero.
+    #[must_use]
+    fn is_zero(&self) -> bool;
+
+    /// Convenience wrapper over Rust's [`wrapping_abs()`](i32::wrapping_abs()).
+    /// The absolute value of the minimum wraps around to the minimum itself.
+    #[must_use]
+    fn wrapping_abs(&self) -> Self;
+
+    /// Convenience wrapper over Rust's [`wrapping_pow()`](i32::wrapping_pow()).
+    /// Raises the word to the power of `exp`, wrapping around at the boundary of the word.
+    #[must_use]
+    fn wrapping_pow(&self, exp: u32) -> Self;
+
+    /// Converts the word to an `i128`, which can represent the value of every word without loss.
+    #[must_use]
+    fn to_i128(&self) -> i128;
+
+    /// Converts an `i128` to a word. Returns `None` if the value does not fit into the word.
+    #[must_use]
+    fn try_from_i128(value: i128) -> Option<Self>;
+
+    /// Converts the word to a word type that is at least as wide, keeping its signed value (sign-extending).
+    ///
+    /// # Panics
+    /// Panics if the value does not fit into `T`, which can only happen if `T` is narrower than the word.
+    /// Use [`narrow()`](Word::narrow()) to convert to a narrower word type.
+    #[must_use]
+    fn widen<T: Word>(&self) -> T {
+        T::try_from_i128(self.to_i128())
+            .unwrap_or_else(|| panic!("Cannot widen a {}-bit word to {} bits", Self::BITS, T::BITS))
+    }
+
+    /// Converts the word to a word type that is at most as wide by keeping only its lowest [`T::BITS`](Word::BITS) bits (truncating).
+    ///
+    /// If `T` is at least as wide as the word, this is the same as [`widen()`](Word::widen()).
+    #[must_use]
+    fn narrow<T: Word>(&self) -> T {
+        let shift = i128::BITS - T::BITS.min(Self::BITS);
+        let truncated = (self.to_i128() << shift) >> shift;
+        T::try_from_i128(truncated).unwrap_or_else(|| unreachable!("The truncated value fits into {} bits", T::BITS))
+    }
+
+    /// Multiplies two words and returns the full product, which is twice as wide as the word, as `(low, high)`.
+    ///
+    /// The high word contains the upper [`BITS`](Word::BITS) bits of the signed product.
+    #[must_use]
+    fn widening_mul(&self, rhs: Self) -> (Self, Self);
+
+    /// Rotates the word left by one bit through the carry.
+    ///
+    /// The carry is shifted into the least significant bit.
+    /// Returns the rotated word and the most significant bit that was shifted out as the new carry.
+    #[must_use]
+    fn rotate_left_through_carry(&self, carry: bool) -> (Self, bool);
+    /// Rotates the word right by one bit through the carry.
+    ///
+    /// The carry is shifted into the most significant bit.
+    /// Returns the rotated word and the least significant bit that was shifted out as the new carry.
+    #[must_use]
+    fn rotate_right_through_carry(&self, carry: bool) -> (Self, bool);
+
+    /// Sign-extends the lowest `bits` bits of the word to the full word width.
+    ///
+    /// Bit `bits - 1` is copied into all higher bits. If `bits` is at least [`BITS`](Word::BITS), the word is returned unchanged.
+    #[must_use]
+    fn sign_extend(&self, bits: u32) -> Self {
+        if bits >= Self::BITS {
+            return *self;
+        }
+        if bits == 0 {
+            return Self::from(0);
+        }
+        let shift = Self::from((Self::BITS - bits) as i32);
+        (*self << shift) >> shift
+    }
+
+    /// Zero-extends the lowest `bits` bits of the word to the full word width.
+    ///
+    /// All bits above bit `bits - 1` are cleared. If `bits` is at least [`BITS`](Word::BITS), the word is returned unchanged.
+    #[must_use]
+    fn zero_extend(&self, bits: u32) -> Self {
+        if bits >= Self::BITS {
+            return *self;
+        }
+        if bits == 0 {
+            return Self::from(0);
+        }
+        *self & !(Self::from(-1) << Self::from(bits as i32))
+    }
+}
+
+// Widening multiplication for the primitive types wrapped by the word types.
+trait WideningMul: Sized {
+    fn widening_mul(self, rhs: Self) -> (Self, Self);
+}
+
+// Implements WideningMul for a primitive type by multiplying in the next larger primitive type.
+macro_rules! widening_mul {
+    ($type: ty, $wide: ty $(,)? ) => {
+        impl WideningMul for $type {
+            #[allow(clippy::cast_possible_truncation)]
+            #[allow(clippy::cast_lossless)]
+            fn widening_mul(self, rhs: Self) -> (Self, Self) {
+                let product = self as $wide * rhs as $wide;
+                (product as $type, (product >> <$type>::BITS) as $type)
+            }
+        }
+    };
+}
+
+widening_mul!(i8, i16);
+widening_mul!(i16, i32);
+widening_mul!(i32, i64);
+widening_mul!(i64, i128);
+widening_mul!(isize, i128);
+
+// There is no larger primitive type than i128, so the product is computed from the 64-bit halves of the magnitudes.
+impl WideningMul for i128 {
+    #[allow(clippy::cast_possible_wrap)]
+    fn widening_mul(self, rhs: Self) -> (Self, Self) {
+        const LOW: u128 = u64::MAX as u128;
+
+        let negative = (self < 0) != (rhs < 0);
+        let (a, b) = (self.unsigned_abs(), rhs.unsigned_abs());
+        let (a_low, a_high, b_low, b_high) = (a & LOW, a >> 64, b & LOW, b >> 64);
+
+        let low_low = a_low * b_low;
+        let low_high = a_low * b_high;
+        let high_low = a_high * b_low;
+        let high_high = a_high * b_high;
+
+        let mid = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+        let low = (low_low & LOW) | (mid << 64);
+        let high = high_high + (low_high >> 64) + (high_low >> 64) + (mid >> 64);
+
+        if negative {
+            // Two's complement negation of the 256-bit product.
+            let low = (!low).wrapping_add(1);
+            let high = (!high).wrapping_add(u128::from(low == 0));
+            (low as i128, high as i128)
+        } else {
+            (low as i128, high as i128)
+        }
+    }
 }
 
 // Implements the From<i32> trait for a wrapper struct around another type like i8.
@@ -170,6 +371,22 @@ macro_rules! impl_word {
         pub struct $name($type);
 
         impl Word for $name {
+            const BITS: u32 = <$type>::BITS;
+
+            type Bytes = [u8; ::core::mem::size_of::<$type>()];
+
+            fn to_le_bytes(&self) -> Self::Bytes {
+                self.0.to_le_bytes()
+            }
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                Self(<$type>::from_le_bytes(
+                    bytes
+                        .try_into()
+                        .expect("The number of bytes must match the size of the word."),
+                ))
+            }
+
             fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
                 <$type>::from_str_radix(s, radix).map($name)
             }
@@ -216,6 +433,18 @@ macro_rules! impl_word {
                 (Self(res), overflow)
             }
 
+            fn saturating_add(&self, rhs: Self) -> Self {
+                Self(self.0.saturating_add(rhs.0))
+            }
+
+            fn saturating_sub(&self, rhs: Self) -> Self {
+                Self(self.0.saturating_sub(rhs.0))
+            }
+
+            fn saturating_mul(&self, rhs: Self) -> Self {
+                Self(self.0.saturating_mul(rhs.0))
+            }
+
             fn rotate_left(&self, val: u32) -> Self {
                 Self(self.0.rotate_left(val))
             }
@@ -223,6 +452,68 @@ macro_rules! impl_word {
             fn rotate_right(&self, val: u32) -> Self {
                 Self(self.0.rotate_right(val))
             }
+
+            fn reverse_bits(&self) -> Self {
+                Self(self.0.reverse_bits())
+            }
+
+            fn leading_zeros(&self) -> u32 {
+                self.0.leading_zeros()
+            }
+
+            fn trailing_zeros(&self) -> u32 {
+                self.0.trailing_zeros()
+            }
+
+            fn swap_bytes(&self) -> Self {
+                Self(self.0.swap_bytes())
+            }
+
+            fn signum(&self) -> Self {
+                Self(self.0.signum())
+            }
+
+            fn is_negative(&self) -> bool {
+                self.0.is_negative()
+            }
+
+            fn is_zero(&self) -> bool {
+                self.0 == 0
+            }
+
+            fn wrapping_abs(&self) -> Self {
+                Self(self.0.wrapping_abs())
+            }
+
+            fn wrapping_pow(&self, exp: u32) -> Self {
+                Self(self.0.wrapping_pow(exp))
+            }
+
+            fn to_i128(&self) -> i128 {
+                // no word is wider than 128 bits
+                self.0 as i128
+            }
+
+            fn try_from_i128(value: i128) -> Option<Self> {
+                <$type>::try_from(value).ok().map(Self)
+            }
+
+            fn widening_mul(&self, rhs: Self) -> (Self, Self) {
+                let (low, high) = WideningMul::widening_mul(self.0, rhs.0);
+                (Self(low), Self(high))
+            }
+
+            fn rotate_left_through_carry(&self, carry: bool) -> (Self, bool) {
+                let carry_out = self.0 < 0;
+                (Self((self.0 << 1) | <$type>::from(carry)), carry_out)
+            }
+
+            fn rotate_right_through_carry(&self, carry: bool) -> (Self, bool) {
+                let carry_out = self.0 & 1 != 0;
+                // Masking with MAX clears the sign bit that was copied by the arithmetic shift.
+                let res = (self.0 >> 1) & <$type>::MAX;
+                (Self(if carry { res | <$type>::MIN } else { res }), carry_out)
+            }
         }
 
         impl ::core::fmt::Display for $name {
@@ -231,6 +522,24 @@ macro_rules! impl_word {
             }
         }
 
+        impl ::core::fmt::LowerHex for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+                ::core::fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::UpperHex for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+                ::core::fmt::UpperHex::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::Binary for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+                ::core::fmt::Binary::fmt(&self.0, f)
+            }
+        }
+
         impl ::core::convert::From<$name> for usize {
             #[allow(clippy::cast_sign_loss)]
             #[allow(clippy::cast_possible_truncation)]
@@ -423,3 +732,722 @@ from_i32!(I16, i16);
 from_i32!(I64, i64);
 from_i32!(I128, i128);
 from_i32!(ISize, isize);
+
+/// 1-bit narrow word. See [`Narrow`].
+pub type I1 = Narrow<1>;
+/// 4-bit narrow word. See [`Narrow`].
+pub type I4 = Narrow<4>;
+
+/// A narrow signed word with `BITS` bits (1 to 8).
+/// Represents a `BITS`-bit processor architecture, e.g. for minimal finite-state-machine programs.
+///
+/// The value is stored sign-extended in an [`i8`]. All operations wrap at the `BITS`-bit boundary
+/// and the `overflowing_*` methods report overflow against the `BITS`-bit range.
+///
+/// ```
+/// # use procem::word::{I4, Word};
+/// assert_eq!(I4::MAX.overflowing_add(1.into()), (I4::MIN, true));
+/// assert_eq!(I4::MAX + 1.into(), I4::MIN);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct Narrow<const BITS: u32>(i8);
+
+impl<const BITS: u32> Narrow<BITS> {
+    const SHIFT: u32 = {
+        assert!(BITS >= 1 && BITS <= 8, "Narrow words must have between 1 and 8 bits.");
+        32 - BITS
+    };
+
+    // All bits of the word set, interpreted as unsigned.
+    const MASK: u32 = u32::MAX >> (32 - BITS);
+
+    /// The largest value that can be represented by this word.
+    #[allow(clippy::cast_possible_truncation)]
+    pub const MAX: Self = Self(((1_i32 << (BITS - 1)) - 1) as i8);
+
+    /// The smallest value that can be represented by this word.
+    #[allow(clippy::cast_possible_truncation)]
+    pub const MIN: Self = Self((-(1_i32 << (BITS - 1))) as i8);
+
+    /// Truncates the value to `BITS` bits and sign-extends it.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn wrap(value: i32) -> Self {
+        Self((value.wrapping_shl(Self::SHIFT) >> Self::SHIFT) as i8)
+    }
+
+    /// Returns the bits of the word interpreted as unsigned.
+    #[allow(clippy::cast_sign_loss)]
+    const fn unsigned(self) -> u32 {
+        self.0 as u32 & Self::MASK
+    }
+
+    /// Wraps the value and reports whether it was outside of the `BITS`-bit range.
+    const fn overflowing(value: i32) -> (Self, bool) {
+        let res = Self::wrap(value);
+        (res, res.0 as i32 != value)
+    }
+
+    /// Clamps the value to the `BITS`-bit range.
+    const fn saturating(value: i32) -> Self {
+        Self::wrap(if value > Self::MAX.0 as i32 {
+            Self::MAX.0 as i32
+        } else if value < Self::MIN.0 as i32 {
+            Self::MIN.0 as i32
+        } else {
+            value
+        })
+    }
+
+    // `ParseIntError` and `TryFromIntError` cannot be constructed directly,
+    // so they are created by converting a value that is out of range of `i8`.
+    fn pos_overflow_error() -> ParseIntError {
+        "128".parse::<i8>().expect_err("128 is out of range of i8.")
+    }
+
+    fn neg_overflow_error() -> ParseIntError {
+        "-129".parse::<i8>().expect_err("-129 is out of range of i8.")
+    }
+}
+
+impl<const BITS: u32> Word for Narrow<BITS> {
+    const BITS: u32 = BITS;
+
+    type Bytes = [u8; 1];
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn to_le_bytes(&self) -> Self::Bytes {
+        [self.unsigned() as u8]
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        let [byte] = bytes else {
+            panic!("The number of bytes must match the size of the word.");
+        };
+        Self::wrap(i32::from(*byte))
+    }
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+        let value = i8::from_str_radix(s, radix)?;
+
+        if value > Self::MAX.0 {
+            Err(Self::pos_overflow_error())
+        } else if value < Self::MIN.0 {
+            Err(Self::neg_overflow_error())
+        } else {
+            Ok(Self(value))
+        }
+    }
+
+    fn check_carry_add(&self, rhs: Self) -> bool {
+        self.unsigned() + rhs.unsigned() > Self::MASK
+    }
+
+    fn check_carry_sub(&self, rhs: Self) -> bool {
+        self.unsigned() < rhs.unsigned()
+    }
+
+    fn check_carry_mul(&self, rhs: Self) -> bool {
+        self.unsigned() * rhs.unsigned() > Self::MASK
+    }
+
+    fn check_carry_div(&self, rhs: Self) -> bool {
+        self.overflowing_div(rhs).1
+    }
+
+    fn overflowing_add(&self, rhs: Self) -> (Self, bool) {
+        Self::overflowing(i32::from(self.0) + i32::from(rhs.0))
+    }
+
+    fn overflowing_sub(&self, rhs: Self) -> (Self, bool) {
+        Self::overflowing(i32::from(self.0) - i32::from(rhs.0))
+    }
+
+    fn overflowing_mul(&self, rhs: Self) -> (Self, bool) {
+        Self::overflowing(i32::from(self.0) * i32::from(rhs.0))
+    }
+
+    fn overflowing_div(&self, rhs: Self) -> (Self, bool) {
+        Self::overflowing(i32::from(self.0) / i32::from(rhs.0))
+    }
+
+    fn saturating_add(&self, rhs: Self) -> Self {
+        Self::saturating(i32::from(self.0) + i32::from(rhs.0))
+    }
+
+    fn saturating_sub(&self, rhs: Self) -> Self {
+        Self::saturating(i32::from(self.0) - i32::from(rhs.0))
+    }
+
+    fn saturating_mul(&self, rhs: Self) -> Self {
+        Self::saturating(i32::from(self.0) * i32::from(rhs.0))
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn rotate_left(&self, val: u32) -> Self {
+        let val = val % BITS;
+        let bits = self.unsigned();
+        Self::wrap(((bits << val) | (bits >> (BITS - val))) as i32)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn rotate_right(&self, val: u32) -> Self {
+        let val = val % BITS;
+        let bits = self.unsigned();
+        Self::wrap(((bits >> val) | (bits << (BITS - val))) as i32)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn reverse_bits(&self) -> Self {
+        // the reversed bits of the word end up in the high bits of the u32
+        Self::wrap((self.unsigned().reverse_bits() >> (32 - BITS)) as i32)
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        // the bits above the word are always zero
+        self.unsigned().leading_zeros() - (32 - BITS)
+    }
+
+    fn trailing_zeros(&self) -> u32 {
+        self.unsigned().trailing_zeros().min(BITS)
+    }
+
+    fn swap_bytes(&self) -> Self {
+        // narrow words fit into a single byte
+        *self
+    }
+
+    fn signum(&self) -> Self {
+        // 1 wraps to 0 for 1-bit words, which cannot be positive
+        Self::wrap(i32::from(self.0.signum()))
+    }
+
+    fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn wrapping_abs(&self) -> Self {
+        Self::wrap(i32::from(self.0).abs())
+    }
+
+    fn wrapping_pow(&self, exp: u32) -> Self {
+        // the low bits of the product do not depend on the bits above them
+        Self::wrap(i32::from(self.0).wrapping_pow(exp))
+    }
+
+    fn to_i128(&self) -> i128 {
+        i128::from(self.0)
+    }
+
+    fn try_from_i128(value: i128) -> Option<Self> {
+        i8::try_from(value)
+            .ok()
+            .filter(|value| (Self::MIN.0..=Self::MAX.0).contains(value))
+            .map(Self)
+    }
+
+    fn widening_mul(&self, rhs: Self) -> (Self, Self) {
+        let product = i32::from(self.0) * i32::from(rhs.0);
+        (Self::wrap(product), Self::wrap(product >> BITS))
+    }
+
+    fn rotate_left_through_carry(&self, carry: bool) -> (Self, bool) {
+        let carry_out = self.0 < 0;
+        (Self::wrap((i32::from(self.0) << 1) | i32::from(carry)), carry_out)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn rotate_right_through_carry(&self, carry: bool) -> (Self, bool) {
+        let carry_out = self.0 & 1 != 0;
+        let bits = (self.unsigned() >> 1) | (u32::from(carry) << (BITS - 1));
+        (Self::wrap(bits as i32), carry_out)
+    }
+}
+
+impl<const BITS: u32> ::core::fmt::Display for Narrow<BITS> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const BITS: u32> ::core::fmt::LowerHex for Narrow<BITS> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+        ::core::fmt::LowerHex::fmt(&self.unsigned(), f)
+    }
+}
+
+impl<const BITS: u32> ::core::fmt::UpperHex for Narrow<BITS> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+        ::core::fmt::UpperHex::fmt(&self.unsigned(), f)
+    }
+}
+
+impl<const BITS: u32> ::core::fmt::Binary for Narrow<BITS> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+        ::core::fmt::Binary::fmt(&self.unsigned(), f)
+    }
+}
+
+impl<const BITS: u32> ::core::convert::From<Narrow<BITS>> for usize {
+    #[allow(clippy::cast_sign_loss)]
+    fn from(value: Narrow<BITS>) -> usize {
+        value.0 as usize
+    }
+}
+
+impl<const BITS: u32> ::core::convert::TryFrom<usize> for Narrow<BITS> {
+    type Error = ::core::num::TryFromIntError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        let value = i8::try_from(value)?;
+
+        if value > Self::MAX.0 {
+            // Out of range of i8, to create the `TryFromIntError`.
+            i8::try_from(usize::MAX).map(Self)
+        } else {
+            Ok(Self(value))
+        }
+    }
+}
+
+impl<const BITS: u32> ::core::convert::From<i32> for Narrow<BITS> {
+    fn from(value: i32) -> Self {
+        Self::wrap(value)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Add for Narrow<BITS> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) + i32::from(rhs.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::AddAssign for Narrow<BITS> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Sub for Narrow<BITS> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) - i32::from(rhs.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::SubAssign for Narrow<BITS> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Mul for Narrow<BITS> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) * i32::from(rhs.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::MulAssign for Narrow<BITS> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Div for Narrow<BITS> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) / i32::from(rhs.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::DivAssign for Narrow<BITS> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Neg for Narrow<BITS> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::wrap(-i32::from(self.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Rem for Narrow<BITS> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) % i32::from(rhs.0))
+    }
+}
+
+impl<const BITS: u32> ::core::ops::RemAssign for Narrow<BITS> {
+    fn rem_assign(&mut self, rhs: Self) {
+        *self = *self % rhs;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitAnd for Narrow<BITS> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitOr for Narrow<BITS> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitXor for Narrow<BITS> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Not for Narrow<BITS> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Shl for Narrow<BITS> {
+    type Output = Self;
+
+    fn shl(self, rhs: Self) -> Self {
+        Self::wrap(i32::from(self.0) << rhs.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::Shr for Narrow<BITS> {
+    type Output = Self;
+
+    fn shr(self, rhs: Self) -> Self {
+        Self(self.0 >> rhs.0)
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitAndAssign for Narrow<BITS> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitOrAssign for Narrow<BITS> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::BitXorAssign for Narrow<BITS> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        *self = *self ^ rhs;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::ShlAssign for Narrow<BITS> {
+    fn shl_assign(&mut self, rhs: Self) {
+        *self = *self << rhs;
+    }
+}
+
+impl<const BITS: u32> ::core::ops::ShrAssign for Narrow<BITS> {
+    fn shr_assign(&mut self, rhs: Self) {
+        *self = *self >> rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapping_abs() {
+        assert_eq!(I8::from(-5).wrapping_abs(), I8::from(5));
+        assert_eq!(I8::from(i8::MIN).wrapping_abs(), I8::from(i8::MIN));
+        assert_eq!(I4::from(-7).wrapping_abs(), I4::from(7));
+        assert_eq!(I4::from(-8).wrapping_abs(), I4::from(-8));
+    }
+
+    #[test]
+    fn test_wrapping_pow() {
+        assert_eq!(I16::from(2).wrapping_pow(8), I16::from(256));
+        assert_eq!(I8::from(2).wrapping_pow(7), I8::from(i8::MIN));
+        assert_eq!(I8::from(-3).wrapping_pow(0), I8::from(1));
+        assert_eq!(I4::from(3).wrapping_pow(2), I4::from(-7));
+    }
+
+    #[test]
+    fn test_i128_round_trip() {
+        assert_eq!(I8::from(-1).to_i128(), -1);
+        assert_eq!(I8::try_from_i128(-1), Some(I8::from(-1)));
+        let max = I64::try_from_i128(i128::from(i64::MAX)).unwrap();
+        assert_eq!(max.to_i128(), i128::from(i64::MAX));
+        assert_eq!(I128::try_from_i128(i128::MIN).map(|min| min.to_i128()), Some(i128::MIN));
+
+        assert_eq!(I8::try_from_i128(128), None);
+        assert_eq!(I64::try_from_i128(i128::from(i64::MAX) + 1), None);
+        assert_eq!(I4::try_from_i128(-8), Some(I4::MIN));
+        assert_eq!(I4::try_from_i128(8), None);
+    }
+
+    #[test]
+    fn test_widen_narrow() {
+        assert_eq!(I8::from(-1).widen::<I32>(), I32::from(-1));
+        assert_eq!(I8::from(-128).widen::<I128>().to_i128(), -128);
+        assert_eq!(I4::from(-8).widen::<I8>(), I8::from(-8));
+
+        assert_eq!(I32::from(0x1FF).narrow::<I8>(), I8::from(-1));
+        assert_eq!(I32::from(0x17F).narrow::<I8>(), I8::from(127));
+        assert_eq!(I8::from(0x0F).narrow::<I4>(), I4::from(-1));
+        assert_eq!(I8::from(-3).narrow::<I64>(), I64::from(-3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot widen a 32-bit word to 8 bits")]
+    fn test_widen_to_narrower() {
+        let _ = I32::from(0x1FF).widen::<I8>();
+    }
+
+    #[test]
+    fn test_extend() {
+        assert_eq!(I32::from(0xFF).sign_extend(8), I32::from(-1));
+        assert_eq!(I32::from(0xFF).zero_extend(8), I32::from(255));
+        assert_eq!(I32::from(0x17F).sign_extend(8), I32::from(127));
+        assert_eq!(I32::from(-1).zero_extend(16), I32::from(0xFFFF));
+        assert_eq!(I8::from(-2).sign_extend(8), I8::from(-2));
+        assert_eq!(I4::from(0b0110).sign_extend(3), I4::from(-2));
+        assert_eq!(I4::from(-1).zero_extend(3), I4::from(7));
+        assert_eq!(I4::from(5).zero_extend(0), I4::from(0));
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        assert_eq!(I8::from(0x40).widening_mul(0x40.into()), (0.into(), 0x10.into()));
+        assert_eq!(I8::from(-1).widening_mul(1.into()), ((-1).into(), (-1).into()));
+        assert_eq!(
+            I32::from(i32::MIN).widening_mul((-1).into()),
+            (i32::MIN.into(), 0.into())
+        );
+        assert_eq!(I4::from(4).widening_mul(4.into()), (0.into(), 1.into()));
+    }
+
+    #[test]
+    fn test_widening_mul_i128() {
+        assert_eq!(I128::from(i128::MAX).widening_mul(2.into()), ((-2).into(), 0.into()));
+        assert_eq!(
+            I128::from(i128::MIN).widening_mul(i128::MIN.into()),
+            (0.into(), (1_i128 << 126).into())
+        );
+        assert_eq!(I128::from(-3).widening_mul(5.into()), ((-15).into(), (-1).into()));
+        assert_eq!(
+            I128::from(i128::MIN).widening_mul(1.into()),
+            (i128::MIN.into(), (-1).into())
+        );
+    }
+
+    #[test]
+    fn test_i4_range() {
+        assert_eq!(I4::MAX, 7.into());
+        assert_eq!(I4::MIN, (-8).into());
+        assert_eq!(I1::MAX, 0.into());
+        assert_eq!(I1::MIN, (-1).into());
+    }
+
+    fn assert_le_bytes_round_trip<W: Word>(values: &[i32]) {
+        for &value in values {
+            let word = W::from(value);
+            assert_eq!(W::from_le_bytes(word.to_le_bytes().as_ref()), word);
+        }
+    }
+
+    #[test]
+    fn test_le_bytes_round_trip() {
+        let values = [0, 1, -1, 42, -42, i32::from(i8::MIN), i32::from(i8::MAX)];
+        assert_le_bytes_round_trip::<I8>(&values);
+        assert_le_bytes_round_trip::<I16>(&values);
+        assert_le_bytes_round_trip::<I32>(&[i32::MIN, i32::MAX, -70_000]);
+        assert_le_bytes_round_trip::<I32>(&values);
+        assert_le_bytes_round_trip::<I64>(&values);
+        assert_le_bytes_round_trip::<I128>(&values);
+        assert_le_bytes_round_trip::<ISize>(&values);
+        assert_le_bytes_round_trip::<I4>(&[-8, -1, 0, 7]);
+        assert_le_bytes_round_trip::<I1>(&[-1, 0]);
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        assert_eq!(I16::from(-2).to_le_bytes(), [0xFE, 0xFF]);
+        assert_eq!(I32::from(0x0102_0304).to_le_bytes(), [4, 3, 2, 1]);
+        assert_eq!(I4::from(-1).to_le_bytes(), [0x0F]);
+        assert_eq!(I64::from_le_bytes(&[0xFF; 8]), (-1).into());
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of bytes must match the size of the word.")]
+    fn test_from_le_bytes_wrong_length() {
+        let _ = I32::from_le_bytes(&[0, 0]);
+    }
+
+    #[test]
+    fn test_saturating() {
+        assert_eq!(I8::from(i32::from(i8::MAX)).saturating_add(10.into()), i8::MAX.into());
+        assert_eq!(I8::from(i32::from(i8::MIN)).saturating_sub(1.into()), i8::MIN.into());
+        assert_eq!(I16::from(300).saturating_mul(300.into()), i16::MAX.into());
+        assert_eq!(I32::from(5).saturating_add((-7).into()), (-2).into());
+        assert_eq!(I4::from(6).saturating_add(6.into()), I4::MAX);
+        assert_eq!(I4::from(-6).saturating_sub(6.into()), I4::MIN);
+        assert_eq!(I4::from(-3).saturating_mul(4.into()), I4::MIN);
+        assert_eq!(I1::from(-1).saturating_mul((-1).into()), I1::MAX);
+    }
+
+    #[test]
+    fn test_reverse_bits() {
+        assert_eq!(I8::from(0).reverse_bits(), 0.into());
+        assert_eq!(I8::from(0x01).reverse_bits(), i8::MIN.into());
+        assert_eq!(I8::from(0b0110_0110).reverse_bits(), 0b0110_0110.into());
+        assert_eq!(I32::from(0x0F).reverse_bits(), (-0x1000_0000).into());
+        assert_eq!(I4::from(0b0001).reverse_bits(), I4::MIN);
+        assert_eq!(I4::from(0b0110).reverse_bits(), 0b0110.into());
+        assert_eq!(I1::from(-1).reverse_bits(), (-1).into());
+    }
+
+    #[test]
+    fn test_leading_trailing_zeros() {
+        assert_eq!(I8::from(0).leading_zeros(), 8);
+        assert_eq!(I8::from(0).trailing_zeros(), 8);
+        assert_eq!(I8::from(1).leading_zeros(), 7);
+        assert_eq!(I8::from(1).trailing_zeros(), 0);
+        assert_eq!(I8::from(i8::MIN).leading_zeros(), 0);
+        assert_eq!(I8::from(i8::MIN).trailing_zeros(), 7);
+        assert_eq!(I64::from(0).leading_zeros(), 64);
+        assert_eq!(I4::from(0).leading_zeros(), 4);
+        assert_eq!(I4::from(0).trailing_zeros(), 4);
+        assert_eq!(I4::from(1).leading_zeros(), 3);
+        assert_eq!(I4::from(-8).leading_zeros(), 0);
+        assert_eq!(I4::from(-8).trailing_zeros(), 3);
+        assert_eq!(I1::from(-1).leading_zeros(), 0);
+    }
+
+    #[test]
+    fn test_hex_binary_format() {
+        use alloc::format;
+
+        assert_eq!(format!("{:x}", I8::from(-1)), "ff");
+        assert_eq!(format!("{:X}", I16::from(-2)), "FFFE");
+        assert_eq!(format!("{:x}", I32::from(255)), "ff");
+        assert_eq!(format!("{:08b}", I8::from(-1)), "11111111");
+        assert_eq!(format!("{:08b}", I8::from(5)), "00000101");
+        assert_eq!(format!("{:#b}", I8::from(i8::MIN)), "0b10000000");
+        assert_eq!(format!("{:b}", I4::from(-1)), "1111");
+        assert_eq!(format!("{:x}", I4::from(-8)), "8");
+    }
+
+    #[test]
+    fn test_swap_bytes() {
+        assert_eq!(I32::from(0x0102_0304).swap_bytes(), 0x0403_0201.into());
+        assert_eq!(I16::from(0x00FF).swap_bytes(), (-0x0100).into());
+        assert_eq!(I8::from(0x12).swap_bytes(), 0x12.into());
+        assert_eq!(I4::from(-3).swap_bytes(), (-3).into());
+    }
+
+    #[test]
+    fn test_signum() {
+        assert_eq!(I8::from(42).signum(), 1.into());
+        assert_eq!(I32::from(i32::MIN).signum(), (-1).into());
+        assert_eq!(I64::from(0).signum(), 0.into());
+        assert_eq!(I4::from(-3).signum(), (-1).into());
+        assert_eq!(I1::from(-1).signum(), (-1).into());
+    }
+
+    #[test]
+    fn test_is_negative_is_zero() {
+        fn assert_predicates<W: Word>() {
+            for (val, negative, zero) in [(-1, true, false), (0, false, true), (1, false, false)] {
+                let word = W::from(val);
+                // 1 wraps to -1 for 1-bit words
+                let negative = negative || word == W::from(-1);
+                assert_eq!(word.is_negative(), negative, "{val} as {}-bit word", W::BITS);
+                assert_eq!(word.is_zero(), zero, "{val} as {}-bit word", W::BITS);
+            }
+        }
+
+        assert_predicates::<I1>();
+        assert_predicates::<I4>();
+        assert_predicates::<I8>();
+        assert_predicates::<I16>();
+        assert_predicates::<I32>();
+        assert_predicates::<I64>();
+        assert_predicates::<I128>();
+        assert_predicates::<ISize>();
+
+        assert!(I8::from(i8::MIN).is_negative());
+        assert!(!I8::from(i8::MAX).is_negative());
+        assert!(I4::MIN.is_negative());
+        assert!(!I4::MAX.is_negative());
+    }
+
+    #[test]
+    fn test_i4_wrapping() {
+        assert_eq!(I4::MAX + 1.into(), I4::MIN);
+        assert_eq!(I4::MIN - 1.into(), I4::MAX);
+        assert_eq!(I4::from(9), (-7).into());
+        assert_eq!(I4::from(3) * 4.into(), (-4).into());
+    }
+
+    #[test]
+    fn test_i4_overflowing_add() {
+        assert_eq!(I4::MAX.overflowing_add(1.into()), (I4::MIN, true));
+        assert_eq!(I4::from(3).overflowing_add(4.into()), (I4::MAX, false));
+        assert_eq!(I4::MIN.overflowing_sub(1.into()), (I4::MAX, true));
+        assert_eq!(I4::MIN.overflowing_div((-1).into()), (I4::MIN, true));
+    }
+
+    #[test]
+    fn test_i4_carry() {
+        assert!(I4::from(-1).check_carry_add(1.into()));
+        assert!(!I4::from(6).check_carry_add(1.into()));
+        assert!(I4::from(0).check_carry_sub(1.into()));
+    }
+
+    #[test]
+    fn test_i4_rotate() {
+        assert_eq!(I4::from(0b0001).rotate_right(1), I4::MIN);
+        assert_eq!(I4::MIN.rotate_left(1), 1.into());
+        assert_eq!(I4::MIN.rotate_left_through_carry(false), (0.into(), true));
+        assert_eq!(I4::from(1).rotate_right_through_carry(true), (I4::MIN, true));
+    }
+
+    #[test]
+    fn test_i4_from_str_radix() {
+        assert_eq!(I4::from_str_radix("7", 10), Ok(I4::MAX));
+        assert_eq!(I4::from_str_radix("-8", 10), Ok(I4::MIN));
+        assert!(I4::from_str_radix("8", 10).is_err());
+        assert!(I4::from_str_radix("-9", 10).is_err());
+    }
+
+    #[test]
+    fn test_i4_try_from_usize() {
+        assert_eq!(I4::try_from(7_usize), Ok(I4::MAX));
+        assert!(I4::try_from(8_usize).is_err());
+    }
+}
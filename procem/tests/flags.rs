@@ -0,0 +1,113 @@
+//! Runs an instruction set that defines its own flags instead of the default carry, signed, overflow and zero flags.
+
+use core::fmt::{Display, Formatter};
+use core::ops::Deref;
+
+use procem::{
+    instruction::Instruction,
+    processor::Processor,
+    program::Program,
+    register::{FlagRegister, Register},
+    stack::StackMemory,
+    word::{I16, Word},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+enum Flag {
+    Parity,
+    Zero,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+struct ParityFlags {
+    parity: bool,
+    zero: bool,
+}
+
+impl FlagRegister for ParityFlags {
+    type Flag = Flag;
+
+    const ALL: &'static [Flag] = &[Flag::Parity, Flag::Zero];
+
+    fn get(&self, flag: Flag) -> bool {
+        match flag {
+            Flag::Parity => self.parity,
+            Flag::Zero => self.zero,
+        }
+    }
+
+    fn set(&mut self, flag: Flag, val: bool) {
+        match flag {
+            Flag::Parity => self.parity = val,
+            Flag::Zero => self.zero = val,
+        }
+    }
+}
+
+impl Display for ParityFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[P: {}, Z: {}]", self.parity, self.zero)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+enum Inst {
+    Mov { to: Register, val: I16 },
+    // Sets the parity flag if the number of set bits is even and the zero flag if the value is zero.
+    Test { reg: Register },
+}
+
+impl Instruction<I16> for Inst {
+    type Flags = ParityFlags;
+
+    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<I16>>(
+        instruction: Self,
+        processor: &mut Processor<STACK_SIZE, Self, P, I16, S>,
+    ) {
+        match instruction {
+            Self::Mov { to, val } => processor.registers.set_reg(to, val),
+            Self::Test { reg } => {
+                let val = processor.registers.get_reg(reg);
+                processor
+                    .registers
+                    .set_flag(Flag::Parity, usize::from(val).count_ones() % 2 == 0);
+                processor.registers.set_flag(Flag::Zero, val.is_zero());
+            }
+        }
+    }
+}
+
+#[test]
+fn run_program_with_custom_flags() {
+    let instructions = vec![
+        Inst::Mov {
+            to: Register::R0,
+            val: 0b1011.into(),
+        },
+        Inst::Test { reg: Register::R0 },
+    ];
+    let program = Program::new(instructions);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.registers.flags(), ParityFlags::default());
+    assert_eq!(processor.run_program(), Ok(()));
+    assert!(!processor.registers.get_flag(Flag::Parity));
+    assert!(!processor.registers.get_flag(Flag::Zero));
+
+    processor.registers.set_reg(Register::PC, 1.into());
+    processor.registers.set_reg(Register::R0, 0.into());
+    assert_eq!(processor.run_program(), Ok(()));
+    assert_eq!(
+        processor.registers.flags(),
+        ParityFlags {
+            parity: true,
+            zero: true,
+        }
+    );
+    assert!(
+        processor
+            .registers
+            .to_string()
+            .ends_with("flags:\t\t[P: true, Z: true]\n")
+    );
+}
@@ -0,0 +1,133 @@
+//! Runs an instruction set with an instruction that halts the processor with every method that runs a program.
+
+use core::ops::Deref;
+
+use procem::{
+    instruction::Instruction,
+    processor::{Processor, RunOutcome},
+    program::{Program, ProgramError},
+    register::{FlagSet, Register},
+    stack::{Stack, StackMemory},
+    word::I16,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+enum Inst {
+    Inc { reg: Register },
+    Jump { to: I16 },
+    Halt,
+}
+
+impl Instruction<I16> for Inst {
+    type Flags = FlagSet;
+
+    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<I16>>(
+        instruction: Self,
+        processor: &mut Processor<STACK_SIZE, Self, P, I16, S>,
+    ) {
+        match instruction {
+            Self::Inc { reg } => processor.registers.inc(reg),
+            Self::Jump { to } => processor.jump(to),
+            Self::Halt => processor.halt(),
+        }
+    }
+}
+
+const HALTING: [Inst; 3] = [
+    Inst::Inc { reg: Register::R0 },
+    Inst::Halt,
+    Inst::Inc { reg: Register::R0 },
+];
+const COMPLETING: [Inst; 2] = [Inst::Inc { reg: Register::R0 }, Inst::Inc { reg: Register::R0 }];
+
+#[test]
+fn run_program_detailed_reports_halt() {
+    let program = Program::new(&HALTING[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.run_program_detailed(), RunOutcome::Halted { steps: 2 });
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+    assert_eq!(processor.registers.pc(), 2.into());
+}
+
+#[test]
+fn program_as_long_as_the_limit_completes() {
+    let program = Program::new(&COMPLETING[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program_with_limit(2), Ok(()));
+    assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(
+        processor.run_program_detailed_with_limit(2),
+        RunOutcome::Completed { steps: 2 }
+    );
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_n(2), Ok(2));
+    assert_eq!(processor.run_n(2), Ok(0));
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_to_pc(1, 2), Ok(true));
+    assert_eq!(processor.run_to_pc(0, 1), Ok(false));
+    assert_eq!(processor.registers.pc(), 2.into());
+
+    // a halt on the last allowed instruction is not a step limit either
+    let program = Program::new(&HALTING[..2]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program_with_limit(2), Ok(()));
+}
+
+#[test]
+fn run_n_stops_at_halt_and_completion() {
+    let program = Program::new(&HALTING[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.run_n(10), Ok(2));
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+
+    // halting does not stop the next run
+    assert_eq!(processor.run_n(10), Ok(1));
+    assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+    assert_eq!(processor.run_n(10), Ok(0));
+
+    // only the pc directly behind the last instruction completes the program
+    let jump = [Inst::Jump { to: 2.into() }];
+    let program = Program::new(&jump[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    processor.registers.set_reg(Register::PC, 5.into());
+    assert_eq!(
+        processor.run_n(10),
+        Err(ProgramError::PCOutOfBounds { pc: 5, program_len: 1 })
+    );
+}
+
+#[test]
+fn run_program_dispatched_stops_at_halt_and_completion() {
+    let table = HALTING.map(|instruction| instruction.handler::<8, &[Inst], Stack<8, I16>>());
+
+    let program = Program::new(&HALTING[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program_dispatched(&table), Ok(()));
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+    assert_eq!(processor.registers.pc(), 2.into());
+
+    let program = Program::new(&COMPLETING[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program_dispatched(&table[..2]), Ok(()));
+    assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+
+    let jump = [Inst::Jump { to: 3.into() }];
+    let program = Program::new(&jump[..]);
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(
+        processor.run_program_dispatched(&[]),
+        Err(ProgramError::InvalidJumpTarget { pc: 3 })
+    );
+}
+
+#[test]
+fn default_mnemonic_is_a_placeholder() {
+    assert_eq!(Inst::Halt.mnemonic(), "?");
+    assert_eq!(Inst::Inc { reg: Register::R0 }.mnemonic(), "?");
+}
@@ -0,0 +1,102 @@
+//! Runs a processor without `std` and `alloc`, like on a microcontroller.
+//!
+//! The program is built by hand from a slice, as the assembler of `procem_default` requires `std`.
+//! Run `cargo test -p procem --no-default-features` to make sure the crate is tested without the `alloc` feature,
+//! which is enabled by `procem_default` when testing the whole workspace.
+#![no_std]
+
+use core::ops::Deref;
+
+use procem::{
+    instruction::Instruction,
+    processor::Processor,
+    program::Program,
+    register::{FlagSet, Register},
+    stack::{Stack, StackMemory},
+    word::I16,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+enum Inst {
+    Mov { to: Register, val: I16 },
+    Add { acc: Register, rhs: Register },
+    Push { from: Register },
+}
+
+impl Instruction<I16> for Inst {
+    type Flags = FlagSet;
+
+    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<I16>>(
+        instruction: Self,
+        processor: &mut Processor<STACK_SIZE, Self, P, I16, S>,
+    ) {
+        match instruction {
+            Self::Mov { to, val } => processor.registers.set_reg(to, val),
+            Self::Add { acc, rhs } => {
+                let sum = processor.registers.get_reg(acc) + processor.registers.get_reg(rhs);
+                processor.registers.set_reg(acc, sum);
+            }
+            Self::Push { from } => {
+                processor.registers.inc(Register::SP);
+                let sp = processor.registers.sp();
+                processor.stack.write(sp, processor.registers.get_reg(from));
+            }
+        }
+    }
+}
+
+#[test]
+fn run_program_without_alloc() {
+    let instructions = [
+        Inst::Mov {
+            to: Register::R0,
+            val: 40.into(),
+        },
+        Inst::Mov {
+            to: Register::R1,
+            val: 2.into(),
+        },
+        Inst::Add {
+            acc: Register::R0,
+            rhs: Register::R1,
+        },
+        Inst::Push { from: Register::R0 },
+    ];
+
+    let program = Program::new(&instructions[..]);
+    let mut processor = Processor::<8, _, _, _, Stack<8, I16>>::builder()
+        .with_program(&program)
+        .build();
+
+    assert_eq!(processor.run_program(), Ok(()));
+    assert_eq!(processor.registers.get_reg(Register::R0), 42.into());
+    assert_eq!(processor.stack.read(processor.registers.sp()), 42.into());
+}
+
+#[test]
+fn program_from_static_slice() {
+    // neither building nor running this program needs an allocator
+    static INSTRUCTIONS: [Inst; 3] = [
+        Inst::Add {
+            acc: Register::R0,
+            rhs: Register::R1,
+        },
+        Inst::Add {
+            acc: Register::R0,
+            rhs: Register::R1,
+        },
+        Inst::Push { from: Register::R0 },
+    ];
+    static PROGRAM: Program<Inst, &'static [Inst], I16> = Program::new(&INSTRUCTIONS);
+
+    assert_eq!(Program::from(INSTRUCTIONS.as_slice()), PROGRAM);
+
+    let mut processor = Processor::<8, _, _, _, Stack<8, I16>>::builder()
+        .with_program(&PROGRAM)
+        .build();
+    processor.registers.set_reg(Register::R1, 3.into());
+
+    assert_eq!(processor.run_program(), Ok(()));
+    assert_eq!(processor.registers.get_reg(Register::R0), 6.into());
+    assert_eq!(processor.stack.read(processor.registers.sp()), 6.into());
+}
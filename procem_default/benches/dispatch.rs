@@ -0,0 +1,67 @@
+//! Compares the execution of a program with [`Processor::run_program`] and [`Processor::run_program_dispatched`].
+//!
+//! Run with `cargo bench -p procem_default`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use procem::{processor::Processor, word::I64};
+use procem_default::{AssembledProgram, assemble};
+
+const STACK_SIZE: usize = 1024;
+const ITERATIONS: u32 = 200;
+
+fn program() -> AssembledProgram<I64> {
+    assemble::<I64>(
+        "
+        mov R0, #10000
+        mov R1, #0
+        .loop
+        add R1, R0
+        xor R2, R1
+        shl R2, #1
+        subs R0, #1
+        jnz .loop
+        ",
+    )
+    .unwrap()
+}
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up.
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{name:<12} {:>12?}/iter",
+        Duration::from_nanos(u64::try_from(elapsed.as_nanos() / u128::from(ITERATIONS)).unwrap_or(u64::MAX))
+    );
+}
+
+fn main() {
+    let program = program();
+    let table = program.dispatch_table::<STACK_SIZE>();
+
+    bench("match", || {
+        let mut processor = Processor::<STACK_SIZE, _, _, _>::builder()
+            .with_program(&program)
+            .build();
+        let _ = black_box(processor.run_program());
+        black_box(&processor);
+    });
+
+    bench("dispatched", || {
+        let mut processor = Processor::<STACK_SIZE, _, _, _>::builder()
+            .with_program(&program)
+            .build();
+        let _ = black_box(processor.run_program_dispatched(&table));
+        black_box(&processor);
+    });
+}
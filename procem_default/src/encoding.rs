@@ -0,0 +1,908 @@
+use procem::{program::Program, register::Register, word::Word};
+use thiserror::Error;
+
+use crate::AssembledProgram;
+use crate::instruction::{Instruction, jump_condition::JumpCondition, operand::Operand, stack_op::StackOp};
+
+/// Identifies the bytes as an encoded program.
+const MAGIC: [u8; 4] = *b"PRCM";
+
+/// Version of the binary format. Has to be incremented whenever the layout changes.
+const VERSION: u8 = 5;
+
+// Registers, jump conditions and stack operations are encoded as their index in these arrays.
+const REGISTERS: [Register; 18] = [
+    Register::R0,
+    Register::R1,
+    Register::R2,
+    Register::R3,
+    Register::R4,
+    Register::R5,
+    Register::R6,
+    Register::R7,
+    Register::R8,
+    Register::R9,
+    Register::R10,
+    Register::R11,
+    Register::R12,
+    Register::R13,
+    Register::R14,
+    Register::R15,
+    Register::PC,
+    Register::SP,
+];
+
+const CONDITIONS: [JumpCondition; 13] = [
+    JumpCondition::Unconditional,
+    JumpCondition::Zero,
+    JumpCondition::NotZero,
+    JumpCondition::Carry,
+    JumpCondition::NotCarry,
+    JumpCondition::Signed,
+    JumpCondition::NotSigned,
+    JumpCondition::Greater,
+    JumpCondition::Less,
+    JumpCondition::GreaterOrEq,
+    JumpCondition::LessOrEq,
+    JumpCondition::ParityEven,
+    JumpCondition::ParityOdd,
+];
+
+const STACK_OPS: [StackOp; 4] = [StackOp::Add, StackOp::Sub, StackOp::Mul, StackOp::Div];
+
+mod opcode {
+    pub(super) const NOP: u8 = 0x00;
+    pub(super) const MOV: u8 = 0x01;
+    pub(super) const PUSH: u8 = 0x02;
+    pub(super) const POP: u8 = 0x03;
+    pub(super) const SWP: u8 = 0x04;
+    pub(super) const LDR: u8 = 0x05;
+    pub(super) const STR: u8 = 0x06;
+    pub(super) const CALL: u8 = 0x07;
+    pub(super) const RET: u8 = 0x08;
+    pub(super) const BIN_STACK: u8 = 0x09;
+    pub(super) const ADD: u8 = 0x0A;
+    pub(super) const SUB: u8 = 0x0B;
+    pub(super) const MUL: u8 = 0x0C;
+    pub(super) const MUL_HIGH: u8 = 0x0D;
+    pub(super) const ADD_SAT: u8 = 0x0E;
+    pub(super) const SUB_SAT: u8 = 0x0F;
+    pub(super) const SGN: u8 = 0x10;
+    pub(super) const DIV: u8 = 0x11;
+    pub(super) const INC: u8 = 0x12;
+    pub(super) const DEC: u8 = 0x13;
+    pub(super) const JUMP: u8 = 0x14;
+    pub(super) const SET_IF: u8 = 0x15;
+    pub(super) const CMOV_IF: u8 = 0x16;
+    pub(super) const CMP: u8 = 0x17;
+    pub(super) const XOR: u8 = 0x18;
+    pub(super) const AND: u8 = 0x19;
+    pub(super) const OR: u8 = 0x1A;
+    pub(super) const NOT: u8 = 0x1B;
+    pub(super) const SHL: u8 = 0x1C;
+    pub(super) const SHR: u8 = 0x1D;
+    pub(super) const ROL: u8 = 0x1E;
+    pub(super) const ROR: u8 = 0x1F;
+    pub(super) const RCL: u8 = 0x20;
+    pub(super) const RCR: u8 = 0x21;
+    pub(super) const NAND: u8 = 0x22;
+    pub(super) const NOR: u8 = 0x23;
+    pub(super) const XNOR: u8 = 0x24;
+    pub(super) const MOVH: u8 = 0x25;
+    pub(super) const MOVL: u8 = 0x26;
+    pub(super) const SXT: u8 = 0x27;
+    pub(super) const ZXT: u8 = 0x28;
+    pub(super) const JUMP_REL: u8 = 0x29;
+    pub(super) const ABS: u8 = 0x2A;
+    pub(super) const MIN: u8 = 0x2B;
+    pub(super) const MAX: u8 = 0x2C;
+    pub(super) const POW: u8 = 0x2D;
+    pub(super) const IN: u8 = 0x2E;
+    pub(super) const OUT: u8 = 0x2F;
+    pub(super) const BREV: u8 = 0x30;
+    pub(super) const CLZ: u8 = 0x31;
+    pub(super) const CTZ: u8 = 0x32;
+    pub(super) const BSWAP: u8 = 0x33;
+}
+
+/// Extension trait to encode an [`AssembledProgram`] into a compact binary format and decode it again.
+///
+/// Loading a decoded program is considerably faster than assembling it from source.
+///
+/// The format starts with a header:
+/// - the magic number `PRCM` (4 bytes),
+/// - the format version (1 byte),
+/// - the number of bits of the word (1 byte),
+/// - the number of instructions (8 bytes, little-endian).
+///
+/// Every instruction is encoded as a one byte opcode followed by its arguments:
+/// - registers, jump conditions, stack operations and whether an instruction sets the flags take one byte,
+/// - words are encoded using [`Word::to_le_bytes`],
+/// - operands start with a tag byte (`0` register, `1` value, `2` indexed), followed by the register and/or word,
+/// - predicated instructions end with their condition (one byte).
+pub trait BinaryProgram: Sized {
+    /// Encodes the program into bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use procem::word::I32;
+    /// use procem_default::{AssembledProgram, TryFromSource, encoding::BinaryProgram};
+    ///
+    /// let program = AssembledProgram::<I32>::try_from_source("mov R0, #4\nmul R0, #5").unwrap();
+    ///
+    /// let bytes = program.encode();
+    ///
+    /// assert_eq!(AssembledProgram::<I32>::decode(&bytes), Ok(program));
+    /// ```
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes a program from bytes that were created by [`encode`](BinaryProgram::encode).
+    ///
+    /// # Errors
+    /// Returns a [`DecodeError`] if the header does not match the format version or the word,
+    /// or if the bytes do not describe a valid sequence of instructions.
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+impl<W: Word> BinaryProgram for AssembledProgram<W> {
+    fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::default();
+
+        encoder.bytes.extend_from_slice(&MAGIC);
+        encoder.u8(VERSION);
+        encoder.u8(word_bits::<W>());
+        encoder.bytes.extend_from_slice(&(self.len() as u64).to_le_bytes());
+
+        for instruction in self.iter() {
+            encoder.instruction(instruction);
+        }
+
+        encoder.bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder { bytes, idx: 0 };
+
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::InvalidMagic);
+        }
+
+        let version = decoder.u8()?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion {
+                version,
+                supported: VERSION,
+            });
+        }
+
+        let bits = decoder.u8()?;
+        if bits != word_bits::<W>() {
+            return Err(DecodeError::WordSizeMismatch {
+                expected: W::BITS,
+                found: bits,
+            });
+        }
+
+        let count = u64::from_le_bytes(decoder.array()?);
+
+        // every instruction takes at least one byte, so this does not allocate more than the input size
+        let mut instructions = Vec::with_capacity(usize::try_from(count).unwrap_or(usize::MAX).min(bytes.len()));
+
+        for _ in 0..count {
+            instructions.push(decoder.instruction()?);
+        }
+
+        if decoder.idx < bytes.len() {
+            return Err(DecodeError::TrailingBytes {
+                count: bytes.len() - decoder.idx,
+            });
+        }
+
+        Ok(Program::new(instructions))
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn word_bits<W: Word>() -> u8 {
+    // the largest words have 128 bits
+    W::BITS as u8
+}
+
+#[derive(Debug, Default)]
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn u8(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    fn bool(&mut self, val: bool) {
+        self.u8(val.into());
+    }
+
+    fn word<W: Word>(&mut self, word: W) {
+        self.bytes.extend_from_slice(word.to_le_bytes().as_ref());
+    }
+
+    fn register(&mut self, reg: Register) {
+        self.u8(reg as u8);
+    }
+
+    fn condition(&mut self, condition: JumpCondition) {
+        self.u8(condition as u8);
+    }
+
+    fn operand<W: Word>(&mut self, operand: Operand<W>) {
+        match operand {
+            Operand::Register(reg) => {
+                self.u8(0);
+                self.register(reg);
+            }
+            Operand::Value(val) => {
+                self.u8(1);
+                self.word(val);
+            }
+            Operand::Indexed { base, offset } => {
+                self.u8(2);
+                self.register(base);
+                self.word(offset);
+            }
+        }
+    }
+
+    fn reg_operand<W: Word>(&mut self, opcode: u8, reg: Register, operand: Operand<W>) {
+        self.u8(opcode);
+        self.register(reg);
+        self.operand(operand);
+    }
+
+    fn set_flags_reg_operand<W: Word>(&mut self, opcode: u8, reg: Register, operand: Operand<W>, set_flags: bool) {
+        self.reg_operand(opcode, reg, operand);
+        self.bool(set_flags);
+    }
+
+    fn instruction<W: Word>(&mut self, instruction: &Instruction<W>) {
+        match *instruction {
+            Instruction::Nop => self.u8(opcode::NOP),
+            Instruction::Mov { to, from, .. } => self.reg_operand(opcode::MOV, to, from),
+            Instruction::Movh { to, val, .. } => {
+                self.u8(opcode::MOVH);
+                self.register(to);
+                self.word(val);
+            }
+            Instruction::Movl { to, val, .. } => {
+                self.u8(opcode::MOVL);
+                self.register(to);
+                self.word(val);
+            }
+            Instruction::Push { from, .. } => {
+                self.u8(opcode::PUSH);
+                self.operand(from);
+            }
+            Instruction::Pop { to, .. } => {
+                self.u8(opcode::POP);
+                self.register(to);
+            }
+            Instruction::Swp { reg, addr, .. } => self.reg_operand(opcode::SWP, reg, addr),
+            Instruction::Ldr { reg, addr, .. } => self.reg_operand(opcode::LDR, reg, addr),
+            Instruction::Str { reg, addr, .. } => self.reg_operand(opcode::STR, reg, addr),
+            Instruction::In { to, port, .. } => {
+                self.u8(opcode::IN);
+                self.register(to);
+                self.word(port);
+            }
+            Instruction::Out { from, port, .. } => {
+                self.u8(opcode::OUT);
+                self.word(port);
+                self.operand(from);
+            }
+            Instruction::Call { addr, .. } => {
+                self.u8(opcode::CALL);
+                self.operand(addr);
+            }
+            Instruction::Ret { .. } => self.u8(opcode::RET),
+            Instruction::BinStack { op, .. } => {
+                self.u8(opcode::BIN_STACK);
+                self.u8(op as u8);
+            }
+            Instruction::Add {
+                acc, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::ADD, acc, rhs, set_flags),
+            Instruction::Sub {
+                acc, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::SUB, acc, rhs, set_flags),
+            Instruction::Mul {
+                acc, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::MUL, acc, rhs, set_flags),
+            Instruction::MulHigh { acc, rhs, dst_hi, .. } => {
+                self.reg_operand(opcode::MUL_HIGH, acc, rhs);
+                self.register(dst_hi);
+            }
+            Instruction::AddSat { acc, rhs, .. } => self.reg_operand(opcode::ADD_SAT, acc, rhs),
+            Instruction::Min { acc, rhs, unsigned, .. } => self.set_flags_reg_operand(opcode::MIN, acc, rhs, unsigned),
+            Instruction::Max { acc, rhs, unsigned, .. } => self.set_flags_reg_operand(opcode::MAX, acc, rhs, unsigned),
+            Instruction::Pow { reg, exp, .. } => self.reg_operand(opcode::POW, reg, exp),
+            Instruction::SubSat { acc, rhs, .. } => self.reg_operand(opcode::SUB_SAT, acc, rhs),
+            Instruction::Sgn { reg, .. } => {
+                self.u8(opcode::SGN);
+                self.register(reg);
+            }
+            Instruction::Abs { reg, .. } => {
+                self.u8(opcode::ABS);
+                self.register(reg);
+            }
+            Instruction::Sxt { reg, .. } => {
+                self.u8(opcode::SXT);
+                self.register(reg);
+            }
+            Instruction::Zxt { reg, .. } => {
+                self.u8(opcode::ZXT);
+                self.register(reg);
+            }
+            Instruction::Brev { reg, .. } => {
+                self.u8(opcode::BREV);
+                self.register(reg);
+            }
+            Instruction::Clz { reg, .. } => {
+                self.u8(opcode::CLZ);
+                self.register(reg);
+            }
+            Instruction::Ctz { reg, .. } => {
+                self.u8(opcode::CTZ);
+                self.register(reg);
+            }
+            Instruction::Bswap { reg, .. } => {
+                self.u8(opcode::BSWAP);
+                self.register(reg);
+            }
+            Instruction::Div {
+                acc, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::DIV, acc, rhs, set_flags),
+            Instruction::Inc { reg, set_flags, .. } => {
+                self.u8(opcode::INC);
+                self.register(reg);
+                self.bool(set_flags);
+            }
+            Instruction::Dec { reg, set_flags, .. } => {
+                self.u8(opcode::DEC);
+                self.register(reg);
+                self.bool(set_flags);
+            }
+            Instruction::Jump { to, condition } => {
+                self.u8(opcode::JUMP);
+                self.word(to);
+                self.condition(condition);
+            }
+            Instruction::JumpRel { offset, condition } => {
+                self.u8(opcode::JUMP_REL);
+                self.word(offset);
+                self.condition(condition);
+            }
+            Instruction::SetIf { reg, condition } => {
+                self.u8(opcode::SET_IF);
+                self.register(reg);
+                self.condition(condition);
+            }
+            Instruction::CmovIf { to, from, condition } => {
+                self.reg_operand(opcode::CMOV_IF, to, from);
+                self.condition(condition);
+            }
+            Instruction::Cmp { lhs, rhs, .. } => {
+                self.u8(opcode::CMP);
+                self.operand(lhs);
+                self.operand(rhs);
+            }
+            Instruction::Xor {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::XOR, reg, rhs, set_flags),
+            Instruction::And {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::AND, reg, rhs, set_flags),
+            Instruction::Or {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::OR, reg, rhs, set_flags),
+            Instruction::Nand {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::NAND, reg, rhs, set_flags),
+            Instruction::Nor {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::NOR, reg, rhs, set_flags),
+            Instruction::Xnor {
+                reg, rhs, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::XNOR, reg, rhs, set_flags),
+            Instruction::Not { reg, set_flags, .. } => {
+                self.u8(opcode::NOT);
+                self.register(reg);
+                self.bool(set_flags);
+            }
+            Instruction::Shl {
+                reg, val, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::SHL, reg, val, set_flags),
+            Instruction::Shr {
+                reg, val, set_flags, ..
+            } => self.set_flags_reg_operand(opcode::SHR, reg, val, set_flags),
+            Instruction::Rol { reg, val, .. } => self.reg_operand(opcode::ROL, reg, val),
+            Instruction::Ror { reg, val, .. } => self.reg_operand(opcode::ROR, reg, val),
+            Instruction::Rcl { reg, val, .. } => self.reg_operand(opcode::RCL, reg, val),
+            Instruction::Rcr { reg, val, .. } => self.reg_operand(opcode::RCR, reg, val),
+        }
+
+        if let Some(cond) = instruction.cond() {
+            self.condition(cond);
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    idx: usize,
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        let bytes = self
+            .bytes
+            .get(self.idx..self.idx + len)
+            .ok_or(DecodeError::UnexpectedEnd { idx: self.bytes.len() })?;
+        self.idx += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    /// Reads a byte and uses it as an index into `values`.
+    fn indexed<T: Copy>(&mut self, values: &[T], kind: &'static str) -> Result<T, DecodeError> {
+        let idx = self.idx;
+        let value = self.u8()?;
+        values
+            .get(usize::from(value))
+            .copied()
+            .ok_or(DecodeError::InvalidValue { kind, value, idx })
+    }
+
+    fn bool(&mut self) -> Result<bool, DecodeError> {
+        self.indexed(&[false, true], "bool")
+    }
+
+    fn register(&mut self) -> Result<Register, DecodeError> {
+        self.indexed(&REGISTERS, "register")
+    }
+
+    fn condition(&mut self) -> Result<JumpCondition, DecodeError> {
+        self.indexed(&CONDITIONS, "jump condition")
+    }
+
+    fn word<W: Word>(&mut self) -> Result<W, DecodeError> {
+        let len = W::Bytes::default().as_ref().len();
+        self.take(len).map(W::from_le_bytes)
+    }
+
+    fn operand<W: Word>(&mut self) -> Result<Operand<W>, DecodeError> {
+        let idx = self.idx;
+        match self.u8()? {
+            0 => Ok(Operand::Register(self.register()?)),
+            1 => Ok(Operand::Value(self.word()?)),
+            2 => Ok(Operand::Indexed {
+                base: self.register()?,
+                offset: self.word()?,
+            }),
+            value => Err(DecodeError::InvalidValue {
+                kind: "operand tag",
+                value,
+                idx,
+            }),
+        }
+    }
+
+    fn instruction<W: Word>(&mut self) -> Result<Instruction<W>, DecodeError> {
+        let idx = self.idx;
+
+        let instruction = match self.u8()? {
+            opcode::NOP => Instruction::Nop,
+            opcode::MOV => Instruction::Mov {
+                to: self.register()?,
+                from: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::MOVH => Instruction::Movh {
+                to: self.register()?,
+                val: self.word()?,
+                cond: self.condition()?,
+            },
+            opcode::MOVL => Instruction::Movl {
+                to: self.register()?,
+                val: self.word()?,
+                cond: self.condition()?,
+            },
+            opcode::PUSH => Instruction::Push {
+                from: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::POP => Instruction::Pop {
+                to: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::SWP => Instruction::Swp {
+                reg: self.register()?,
+                addr: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::LDR => Instruction::Ldr {
+                reg: self.register()?,
+                addr: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::STR => Instruction::Str {
+                reg: self.register()?,
+                addr: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::IN => Instruction::In {
+                to: self.register()?,
+                port: self.word()?,
+                cond: self.condition()?,
+            },
+            opcode::OUT => Instruction::Out {
+                port: self.word()?,
+                from: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::CALL => Instruction::Call {
+                addr: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::RET => Instruction::Ret {
+                cond: self.condition()?,
+            },
+            opcode::BIN_STACK => Instruction::BinStack {
+                op: self.indexed(&STACK_OPS, "stack operation")?,
+                cond: self.condition()?,
+            },
+            opcode::ADD => Instruction::Add {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::SUB => Instruction::Sub {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::MUL => Instruction::Mul {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::MUL_HIGH => Instruction::MulHigh {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                dst_hi: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::ADD_SAT => Instruction::AddSat {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::MIN => Instruction::Min {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                unsigned: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::MAX => Instruction::Max {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                unsigned: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::POW => Instruction::Pow {
+                reg: self.register()?,
+                exp: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::SUB_SAT => Instruction::SubSat {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::SGN => Instruction::Sgn {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::ABS => Instruction::Abs {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::SXT => Instruction::Sxt {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::ZXT => Instruction::Zxt {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::BREV => Instruction::Brev {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::CLZ => Instruction::Clz {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::CTZ => Instruction::Ctz {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::BSWAP => Instruction::Bswap {
+                reg: self.register()?,
+                cond: self.condition()?,
+            },
+            opcode::DIV => Instruction::Div {
+                acc: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::INC => Instruction::Inc {
+                reg: self.register()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::DEC => Instruction::Dec {
+                reg: self.register()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::JUMP => Instruction::Jump {
+                to: self.word()?,
+                condition: self.condition()?,
+            },
+            opcode::JUMP_REL => Instruction::JumpRel {
+                offset: self.word()?,
+                condition: self.condition()?,
+            },
+            opcode::SET_IF => Instruction::SetIf {
+                reg: self.register()?,
+                condition: self.condition()?,
+            },
+            opcode::CMOV_IF => Instruction::CmovIf {
+                to: self.register()?,
+                from: self.operand()?,
+                condition: self.condition()?,
+            },
+            opcode::CMP => Instruction::Cmp {
+                lhs: self.operand()?,
+                rhs: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::XOR => Instruction::Xor {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::AND => Instruction::And {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::OR => Instruction::Or {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::NAND => Instruction::Nand {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::NOR => Instruction::Nor {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::XNOR => Instruction::Xnor {
+                reg: self.register()?,
+                rhs: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::NOT => Instruction::Not {
+                reg: self.register()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::SHL => Instruction::Shl {
+                reg: self.register()?,
+                val: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::SHR => Instruction::Shr {
+                reg: self.register()?,
+                val: self.operand()?,
+                set_flags: self.bool()?,
+                cond: self.condition()?,
+            },
+            opcode::ROL => Instruction::Rol {
+                reg: self.register()?,
+                val: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::ROR => Instruction::Ror {
+                reg: self.register()?,
+                val: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::RCL => Instruction::Rcl {
+                reg: self.register()?,
+                val: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode::RCR => Instruction::Rcr {
+                reg: self.register()?,
+                val: self.operand()?,
+                cond: self.condition()?,
+            },
+            opcode => return Err(DecodeError::InvalidOpcode { opcode, idx }),
+        };
+
+        Ok(instruction)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("Invalid magic number. The bytes are not an encoded program.")]
+    InvalidMagic,
+    #[error("Unsupported format version {version}. Supported version: {supported}.")]
+    UnsupportedVersion { version: u8, supported: u8 },
+    #[error("The program was encoded for {found}-bit words, but {expected}-bit words are expected.")]
+    WordSizeMismatch { expected: u32, found: u8 },
+    #[error("Unexpected end of input at byte {idx}.")]
+    UnexpectedEnd { idx: usize },
+    #[error("Invalid opcode {opcode} at byte {idx}.")]
+    InvalidOpcode { opcode: u8, idx: usize },
+    #[error("Invalid {kind} {value} at byte {idx}.")]
+    InvalidValue { kind: &'static str, value: u8, idx: usize },
+    #[error("{count} trailing bytes after the last instruction.")]
+    TrailingBytes { count: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assemble;
+    use procem::word::{I8, I32};
+
+    fn encoded_program() -> Vec<u8> {
+        assemble::<I32>("mov R0, #1\npush R0").unwrap().encode()
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = encoded_program();
+        assert_eq!(bytes[..4], MAGIC);
+        assert_eq!(bytes[4], VERSION);
+        assert_eq!(bytes[5], 32);
+        assert_eq!(bytes[6..14], 2_u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_round_trip_all_operands() {
+        let program = assemble::<I32>(
+            "
+            .start
+            ldr R1, [R2 + #-4]
+            cmovge R3, #0x7fffffff
+            setnz R5
+            rcr R15, #3
+            rol R4, R5
+            shrs R4, #2
+            shl R5, R6
+            mulh R7, R8, R9
+            sdiv
+            cmp R6, R0
+            addsne R7, #1
+            xnors R8, R9
+            movh R10, #0x1234
+            sxteq R11
+            zxt R12
+            brevne R12
+            clz R13
+            ctzpo R14
+            bswaphs R15
+            absmi R13
+            minu R14, #3
+            maxne R15, R1
+            poweq R2, R3
+            in R3, #1
+            outeq #0, R1
+            strne R4, [R1 + #2]
+            retge
+            jrle .start
+            jle .start
+            jrpo .start
+            cmovpe R3, R4
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(AssembledProgram::<I32>::decode(&program.encode()), Ok(program));
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        let mut bytes = encoded_program();
+        bytes[0] = b'X';
+        assert_eq!(AssembledProgram::<I32>::decode(&bytes), Err(DecodeError::InvalidMagic));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut bytes = encoded_program();
+        bytes[4] = VERSION + 1;
+        assert_eq!(
+            AssembledProgram::<I32>::decode(&bytes),
+            Err(DecodeError::UnsupportedVersion {
+                version: VERSION + 1,
+                supported: VERSION
+            })
+        );
+    }
+
+    #[test]
+    fn test_word_size_mismatch() {
+        assert_eq!(
+            AssembledProgram::<I8>::decode(&encoded_program()),
+            Err(DecodeError::WordSizeMismatch { expected: 8, found: 32 })
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = encoded_program();
+        assert_eq!(
+            AssembledProgram::<I32>::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd { idx: bytes.len() - 1 })
+        );
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut bytes = encoded_program();
+        bytes.push(0);
+        assert_eq!(
+            AssembledProgram::<I32>::decode(&bytes),
+            Err(DecodeError::TrailingBytes { count: 1 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_opcode() {
+        let mut bytes = encoded_program();
+        bytes[14] = 0xFF;
+        assert_eq!(
+            AssembledProgram::<I32>::decode(&bytes),
+            Err(DecodeError::InvalidOpcode { opcode: 0xFF, idx: 14 })
+        );
+    }
+}
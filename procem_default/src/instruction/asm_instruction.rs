@@ -1,3 +1,11 @@
+use crate::instruction::jump_condition::JumpCondition;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum ASMHalfWordInstruction {
+    Movh,
+    Movl,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMJumpInstruction {
     Jmp,
@@ -11,32 +19,74 @@ pub enum ASMJumpInstruction {
     Jge,
     Jl,
     Jle,
+    Jpe,
+    Jpo,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMNoArgInstruction {
     Nop,
     Ret,
+    SAdd,
+    SDiv,
+    SMul,
+    SSub,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum ASMPortInstruction {
+    In,
+    Out,
+}
+
+/// Pseudo-instructions, which are expanded into a real instruction by the parser.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum ASMPseudoInstruction {
+    // CLR R0 is MOV R0, #0
+    Clr,
+    // TST R0 is CMP R0, #0
+    Tst,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMRegOperandInstruction {
     Add,
     AddS,
+    AddSat,
     And,
+    AndS,
     Div,
     DivS,
+    Ldr,
+    Max,
+    MaxU,
+    Min,
+    MinU,
     Mov,
     Mul,
     MulS,
+    Nand,
+    NandS,
+    Nor,
+    NorS,
     Or,
+    OrS,
+    Pow,
+    Str,
     Sub,
     SubS,
+    SubSat,
+    Swp,
+    Xnor,
+    XnorS,
     Xor,
+    XorS,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMRotateInstruction {
+    Rcl,
+    Rcr,
     Rol,
     Ror,
 }
@@ -44,7 +94,9 @@ pub enum ASMRotateInstruction {
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMShiftInstruction {
     Shl,
+    ShlS,
     Shr,
+    ShrS,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
@@ -55,12 +107,21 @@ pub enum ASMSingleOperandInstruction {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMSingleRegInstruction {
+    Abs,
+    Brev,
+    Bswap,
+    Clz,
+    Ctz,
     Dec,
     DecS,
     Inc,
     IncS,
     Not,
+    NotS,
     Pop,
+    Sgn,
+    Sxt,
+    Zxt,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
@@ -70,30 +131,126 @@ pub enum ASMTwoOperandInstruction {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum ASMInstruction {
+    // CMOVcc shares its conditions with the jump instructions.
+    Cmov(ASMJumpInstruction),
+    HalfWord(ASMHalfWordInstruction),
     Jump(ASMJumpInstruction),
+    // JRcc shares its conditions with the jump instructions.
+    JumpRel(ASMJumpInstruction),
+    // MULH is the only instruction with two registers and an operand.
+    MulHigh,
     NoArg(ASMNoArgInstruction),
+    Port(ASMPortInstruction),
+    Pseudo(ASMPseudoInstruction),
     RegOperand(ASMRegOperandInstruction),
     Rotate(ASMRotateInstruction),
+    // SETcc shares its conditions with the jump instructions.
+    Set(ASMJumpInstruction),
     Shift(ASMShiftInstruction),
     SingleOperand(ASMSingleOperandInstruction),
     SingleReg(ASMSingleRegInstruction),
     TwoOperand(ASMTwoOperandInstruction),
 }
 
+impl ASMInstruction {
+    /// Length of the longest mnemonic.
+    const MAX_MNEMONIC_LEN: usize = 6;
+
+    /// The conditions that can be appended to the mnemonic of a predicated instruction.
+    const PREDICATES: [JumpCondition; 12] = [
+        JumpCondition::Zero,
+        JumpCondition::NotZero,
+        JumpCondition::Carry,
+        JumpCondition::NotCarry,
+        JumpCondition::Signed,
+        JumpCondition::NotSigned,
+        JumpCondition::Greater,
+        JumpCondition::Less,
+        JumpCondition::GreaterOrEq,
+        JumpCondition::LessOrEq,
+        JumpCondition::ParityEven,
+        JumpCondition::ParityOdd,
+    ];
+
+    /// Returns true if the instruction can be predicated with a condition.
+    const fn is_predicable(self) -> bool {
+        !matches!(
+            self,
+            Self::Jump(_) | Self::JumpRel(_) | Self::Set(_) | Self::Cmov(_) | Self::NoArg(ASMNoArgInstruction::Nop)
+        )
+    }
+
+    /// Converts a mnemonic in any case, which is optionally followed by a condition code (e.g. `ADDNE`),
+    /// into the corresponding instruction and its condition.
+    ///
+    /// The condition codes are the [`predicate_suffix`](JumpCondition::predicate_suffix) of the conditions,
+    /// so e.g. `ADDS` is always the flag setting `ADD` and `ADDMI` is an `ADD` that is executed if the signed flag is set.
+    pub(crate) fn parse_predicated(value: &str) -> Result<(Self, JumpCondition), ()> {
+        if let Ok(inst) = Self::try_from(value) {
+            return Ok((inst, JumpCondition::Unconditional));
+        }
+
+        Self::PREDICATES
+            .into_iter()
+            .find_map(|cond| {
+                let suffix = cond.predicate_suffix();
+                let split = value.len().checked_sub(suffix.len())?;
+
+                if !value.get(split..)?.eq_ignore_ascii_case(suffix) {
+                    return None;
+                }
+
+                Self::try_from(&value[..split])
+                    .ok()
+                    .filter(|inst| inst.is_predicable())
+                    .map(|inst| (inst, cond))
+            })
+            .ok_or(())
+    }
+}
+
 impl TryFrom<&str> for ASMInstruction {
     type Error = ();
 
+    /// Converts a mnemonic in any case into the corresponding instruction.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let inst = match value {
+        // uppercase on the stack to avoid allocating for every instruction
+        let mut buf = [0; Self::MAX_MNEMONIC_LEN];
+        let upper = buf.get_mut(..value.len()).ok_or(())?;
+        upper.copy_from_slice(value.as_bytes());
+        upper.make_ascii_uppercase();
+
+        let inst = match core::str::from_utf8(upper).map_err(|_| ())? {
+            "ABS" => Self::SingleReg(ASMSingleRegInstruction::Abs),
             "ADD" => Self::RegOperand(ASMRegOperandInstruction::Add),
             "ADDS" => Self::RegOperand(ASMRegOperandInstruction::AddS),
+            "ADDSAT" => Self::RegOperand(ASMRegOperandInstruction::AddSat),
             "AND" => Self::RegOperand(ASMRegOperandInstruction::And),
+            "ANDS" => Self::RegOperand(ASMRegOperandInstruction::AndS),
+            "BREV" => Self::SingleReg(ASMSingleRegInstruction::Brev),
+            "BSWAP" => Self::SingleReg(ASMSingleRegInstruction::Bswap),
             "CALL" => Self::SingleOperand(ASMSingleOperandInstruction::Call),
+            "CLR" => Self::Pseudo(ASMPseudoInstruction::Clr),
+            "CLZ" => Self::SingleReg(ASMSingleRegInstruction::Clz),
+            "CMOVC" => Self::Cmov(ASMJumpInstruction::Jc),
+            "CMOVG" => Self::Cmov(ASMJumpInstruction::Jg),
+            "CMOVGE" => Self::Cmov(ASMJumpInstruction::Jge),
+            "CMOVL" => Self::Cmov(ASMJumpInstruction::Jl),
+            "CMOVLE" => Self::Cmov(ASMJumpInstruction::Jle),
+            "CMOVNC" => Self::Cmov(ASMJumpInstruction::Jnc),
+            "CMOVNS" => Self::Cmov(ASMJumpInstruction::Jns),
+            "CMOVNZ" => Self::Cmov(ASMJumpInstruction::Jnz),
+            "CMOVPE" => Self::Cmov(ASMJumpInstruction::Jpe),
+            "CMOVPO" => Self::Cmov(ASMJumpInstruction::Jpo),
+            "CMOVS" => Self::Cmov(ASMJumpInstruction::Js),
+            "CMOVZ" => Self::Cmov(ASMJumpInstruction::Jz),
             "CMP" => Self::TwoOperand(ASMTwoOperandInstruction::Cmp),
+            "CTZ" => Self::SingleReg(ASMSingleRegInstruction::Ctz),
             "DEC" => Self::SingleReg(ASMSingleRegInstruction::Dec),
             "DECS" => Self::SingleReg(ASMSingleRegInstruction::DecS),
             "DIV" => Self::RegOperand(ASMRegOperandInstruction::Div),
             "DIVS" => Self::RegOperand(ASMRegOperandInstruction::DivS),
+            "IN" => Self::Port(ASMPortInstruction::In),
             "INC" => Self::SingleReg(ASMSingleRegInstruction::Inc),
             "INCS" => Self::SingleReg(ASMSingleRegInstruction::IncS),
             "JC" => Self::Jump(ASMJumpInstruction::Jc),
@@ -105,24 +262,85 @@ impl TryFrom<&str> for ASMInstruction {
             "JNC" => Self::Jump(ASMJumpInstruction::Jnc),
             "JNS" => Self::Jump(ASMJumpInstruction::Jns),
             "JNZ" => Self::Jump(ASMJumpInstruction::Jnz),
+            "JPE" => Self::Jump(ASMJumpInstruction::Jpe),
+            "JPO" => Self::Jump(ASMJumpInstruction::Jpo),
+            "JR" => Self::JumpRel(ASMJumpInstruction::Jmp),
+            "JRC" => Self::JumpRel(ASMJumpInstruction::Jc),
+            "JRG" => Self::JumpRel(ASMJumpInstruction::Jg),
+            "JRGE" => Self::JumpRel(ASMJumpInstruction::Jge),
+            "JRL" => Self::JumpRel(ASMJumpInstruction::Jl),
+            "JRLE" => Self::JumpRel(ASMJumpInstruction::Jle),
+            "JRNC" => Self::JumpRel(ASMJumpInstruction::Jnc),
+            "JRNS" => Self::JumpRel(ASMJumpInstruction::Jns),
+            "JRNZ" => Self::JumpRel(ASMJumpInstruction::Jnz),
+            "JRPE" => Self::JumpRel(ASMJumpInstruction::Jpe),
+            "JRPO" => Self::JumpRel(ASMJumpInstruction::Jpo),
+            "JRS" => Self::JumpRel(ASMJumpInstruction::Js),
+            "JRZ" => Self::JumpRel(ASMJumpInstruction::Jz),
             "JS" => Self::Jump(ASMJumpInstruction::Js),
             "JZ" => Self::Jump(ASMJumpInstruction::Jz),
+            "LDR" => Self::RegOperand(ASMRegOperandInstruction::Ldr),
+            "MAX" => Self::RegOperand(ASMRegOperandInstruction::Max),
+            "MAXU" => Self::RegOperand(ASMRegOperandInstruction::MaxU),
+            "MIN" => Self::RegOperand(ASMRegOperandInstruction::Min),
+            "MINU" => Self::RegOperand(ASMRegOperandInstruction::MinU),
             "MOV" => Self::RegOperand(ASMRegOperandInstruction::Mov),
+            "MOVH" => Self::HalfWord(ASMHalfWordInstruction::Movh),
+            "MOVL" => Self::HalfWord(ASMHalfWordInstruction::Movl),
             "MUL" => Self::RegOperand(ASMRegOperandInstruction::Mul),
+            "MULH" => Self::MulHigh,
             "MULS" => Self::RegOperand(ASMRegOperandInstruction::MulS),
+            "NAND" => Self::RegOperand(ASMRegOperandInstruction::Nand),
+            "NANDS" => Self::RegOperand(ASMRegOperandInstruction::NandS),
             "NOP" => Self::NoArg(ASMNoArgInstruction::Nop),
+            "NOR" => Self::RegOperand(ASMRegOperandInstruction::Nor),
+            "NORS" => Self::RegOperand(ASMRegOperandInstruction::NorS),
             "NOT" => Self::SingleReg(ASMSingleRegInstruction::Not),
+            "NOTS" => Self::SingleReg(ASMSingleRegInstruction::NotS),
             "OR" => Self::RegOperand(ASMRegOperandInstruction::Or),
+            "ORS" => Self::RegOperand(ASMRegOperandInstruction::OrS),
+            "OUT" => Self::Port(ASMPortInstruction::Out),
             "POP" => Self::SingleReg(ASMSingleRegInstruction::Pop),
+            "POW" => Self::RegOperand(ASMRegOperandInstruction::Pow),
             "PUSH" => Self::SingleOperand(ASMSingleOperandInstruction::Push),
+            "RCL" => Self::Rotate(ASMRotateInstruction::Rcl),
+            "RCR" => Self::Rotate(ASMRotateInstruction::Rcr),
             "RET" => Self::NoArg(ASMNoArgInstruction::Ret),
             "ROL" => Self::Rotate(ASMRotateInstruction::Rol),
             "ROR" => Self::Rotate(ASMRotateInstruction::Ror),
+            "SADD" => Self::NoArg(ASMNoArgInstruction::SAdd),
+            "SDIV" => Self::NoArg(ASMNoArgInstruction::SDiv),
+            "SETC" => Self::Set(ASMJumpInstruction::Jc),
+            "SETG" => Self::Set(ASMJumpInstruction::Jg),
+            "SETGE" => Self::Set(ASMJumpInstruction::Jge),
+            "SETL" => Self::Set(ASMJumpInstruction::Jl),
+            "SETLE" => Self::Set(ASMJumpInstruction::Jle),
+            "SETNC" => Self::Set(ASMJumpInstruction::Jnc),
+            "SETNS" => Self::Set(ASMJumpInstruction::Jns),
+            "SETNZ" => Self::Set(ASMJumpInstruction::Jnz),
+            "SETPE" => Self::Set(ASMJumpInstruction::Jpe),
+            "SETPO" => Self::Set(ASMJumpInstruction::Jpo),
+            "SETS" => Self::Set(ASMJumpInstruction::Js),
+            "SETZ" => Self::Set(ASMJumpInstruction::Jz),
+            "SGN" => Self::SingleReg(ASMSingleRegInstruction::Sgn),
             "SHL" => Self::Shift(ASMShiftInstruction::Shl),
+            "SHLS" => Self::Shift(ASMShiftInstruction::ShlS),
             "SHR" => Self::Shift(ASMShiftInstruction::Shr),
+            "SHRS" => Self::Shift(ASMShiftInstruction::ShrS),
+            "SMUL" => Self::NoArg(ASMNoArgInstruction::SMul),
+            "SSUB" => Self::NoArg(ASMNoArgInstruction::SSub),
+            "STR" => Self::RegOperand(ASMRegOperandInstruction::Str),
             "SUB" => Self::RegOperand(ASMRegOperandInstruction::Sub),
             "SUBS" => Self::RegOperand(ASMRegOperandInstruction::SubS),
+            "SUBSAT" => Self::RegOperand(ASMRegOperandInstruction::SubSat),
+            "SWP" => Self::RegOperand(ASMRegOperandInstruction::Swp),
+            "SXT" => Self::SingleReg(ASMSingleRegInstruction::Sxt),
+            "TST" => Self::Pseudo(ASMPseudoInstruction::Tst),
+            "XNOR" => Self::RegOperand(ASMRegOperandInstruction::Xnor),
+            "XNORS" => Self::RegOperand(ASMRegOperandInstruction::XnorS),
             "XOR" => Self::RegOperand(ASMRegOperandInstruction::Xor),
+            "XORS" => Self::RegOperand(ASMRegOperandInstruction::XorS),
+            "ZXT" => Self::SingleReg(ASMSingleRegInstruction::Zxt),
             _ => return Err(()),
         };
 
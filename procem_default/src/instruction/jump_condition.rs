@@ -1,8 +1,36 @@
+use core::fmt::{Display, Formatter};
 use core::ops::Deref;
+use core::str::FromStr;
 
-use procem::{processor::Processor, register::Flag, word::Word};
+use procem::{processor::Processor, register::Flag, stack::StackMemory, word::Word};
 
-use crate::instruction::Instruction;
+use crate::instruction::{Instruction, asm_instruction::ASMJumpInstruction, has_even_parity};
+use crate::parser::ParserError;
+
+/// Creates a method that returns the mnemonic of an instruction, which consists of the prefix and the condition.
+/// The mnemonic for [`Unconditional`](JumpCondition::Unconditional) is provided separately.
+macro_rules! mnemonic_with_condition {
+    ($(#[$attr: meta])* $name: ident, $prefix: literal, $unconditional: literal) => {
+        $(#[$attr])*
+        pub(crate) const fn $name(self) -> &'static str {
+            match self {
+                Self::Unconditional => $unconditional,
+                Self::Zero => concat!($prefix, "Z"),
+                Self::NotZero => concat!($prefix, "NZ"),
+                Self::Carry => concat!($prefix, "C"),
+                Self::NotCarry => concat!($prefix, "NC"),
+                Self::Signed => concat!($prefix, "S"),
+                Self::NotSigned => concat!($prefix, "NS"),
+                Self::Greater => concat!($prefix, "G"),
+                Self::Less => concat!($prefix, "L"),
+                Self::GreaterOrEq => concat!($prefix, "GE"),
+                Self::LessOrEq => concat!($prefix, "LE"),
+                Self::ParityEven => concat!($prefix, "PE"),
+                Self::ParityOdd => concat!($prefix, "PO"),
+            }
+        }
+    };
+}
 
 /// Jump condition for the instruction set.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -29,18 +57,111 @@ pub enum JumpCondition {
     GreaterOrEq,
     /// If zero flag or signed flag is set. \[JLE\]
     LessOrEq,
+    /// If parity flag is set. \[JPE\]
+    ParityEven,
+    /// If parity flag is not set. \[JPO\]
+    ParityOdd,
 }
 
 impl JumpCondition {
+    /// All jump conditions in the order they are declared.
+    pub(crate) const ALL: [Self; 13] = [
+        Self::Unconditional,
+        Self::Zero,
+        Self::NotZero,
+        Self::Carry,
+        Self::NotCarry,
+        Self::Signed,
+        Self::NotSigned,
+        Self::Greater,
+        Self::Less,
+        Self::GreaterOrEq,
+        Self::LessOrEq,
+        Self::ParityEven,
+        Self::ParityOdd,
+    ];
+
+    /// Maps a jump mnemonic to its condition.
+    pub(crate) const fn from_jump_instruction(instr: ASMJumpInstruction) -> Self {
+        use ASMJumpInstruction::{Jc, Jg, Jge, Jl, Jle, Jmp, Jnc, Jns, Jnz, Jpe, Jpo, Js, Jz};
+
+        match instr {
+            Jmp => Self::Unconditional,
+            Jz => Self::Zero,
+            Jnz => Self::NotZero,
+            Jc => Self::Carry,
+            Jnc => Self::NotCarry,
+            Js => Self::Signed,
+            Jns => Self::NotSigned,
+            Jg => Self::Greater,
+            Jl => Self::Less,
+            Jge => Self::GreaterOrEq,
+            Jle => Self::LessOrEq,
+            Jpe => Self::ParityEven,
+            Jpo => Self::ParityOdd,
+        }
+    }
+
+    mnemonic_with_condition! {
+        /// The condition part of the mnemonic, e.g. `NZ` for [`NotZero`](JumpCondition::NotZero).
+        /// It is empty for [`Unconditional`](JumpCondition::Unconditional).
+        suffix, "", ""
+    }
+
+    mnemonic_with_condition! {
+        /// The mnemonic of the jump instruction with this condition, e.g. `JNZ`.
+        jump_mnemonic, "J", "JMP"
+    }
+
+    mnemonic_with_condition! {
+        /// The mnemonic of the relative jump instruction with this condition, e.g. `JRNZ`.
+        rel_jump_mnemonic, "JR", "JR"
+    }
+
+    mnemonic_with_condition! {
+        /// The mnemonic of the set instruction with this condition, e.g. `SETNZ`.
+        set_mnemonic, "SET", "SET"
+    }
+
+    mnemonic_with_condition! {
+        /// The mnemonic of the conditional move instruction with this condition, e.g. `CMOVNZ`.
+        cmov_mnemonic, "CMOV", "CMOV"
+    }
+
+    /// The condition code of a predicated instruction, e.g. `NE` in `ADDNE` for [`NotZero`](JumpCondition::NotZero).
+    /// It is empty for [`Unconditional`](JumpCondition::Unconditional).
+    ///
+    /// These differ from the [`suffix`](JumpCondition::suffix) of the jump mnemonics, because a suffix like `S` or `L`
+    /// would turn `ADD` into `ADDS` and `MOV` into `MOVL`. Every code has two letters and no predicable mnemonic
+    /// followed by a code is another mnemonic, so a predicated instruction can never be read as another instruction.
+    pub(crate) const fn predicate_suffix(self) -> &'static str {
+        match self {
+            Self::Unconditional => "",
+            Self::Zero => "EQ",
+            Self::NotZero => "NE",
+            Self::Carry => "HS",
+            Self::NotCarry => "LO",
+            Self::Signed => "MI",
+            Self::NotSigned => "PL",
+            Self::Greater => "GT",
+            Self::Less => "LT",
+            Self::GreaterOrEq => "GE",
+            Self::LessOrEq => "LE",
+            Self::ParityEven => "PE",
+            Self::ParityOdd => "PO",
+        }
+    }
+
     /// Check the jump condition.
     #[inline]
-    pub(crate) const fn check<const STACK_SIZE: usize, W, P>(
+    pub(crate) fn check<const STACK_SIZE: usize, W, P, S>(
         self,
-        processor: &Processor<STACK_SIZE, Instruction<W>, P, W>,
+        processor: &Processor<STACK_SIZE, Instruction<W>, P, W, S>,
     ) -> bool
     where
         W: Word,
         P: Deref<Target = [Instruction<W>]>,
+        S: StackMemory<W>,
     {
         let flags = &processor.registers;
         match self {
@@ -55,6 +176,60 @@ impl JumpCondition {
             Self::Less => !flags.get_flag(Flag::Z) && flags.get_flag(Flag::S),
             Self::GreaterOrEq => flags.get_flag(Flag::Z) || !flags.get_flag(Flag::S),
             Self::LessOrEq => flags.get_flag(Flag::Z) || flags.get_flag(Flag::S),
+            Self::ParityEven => flags.get_flag(Flag::P),
+            Self::ParityOdd => !flags.get_flag(Flag::P),
         }
     }
+
+    /// Check the jump condition directly on a value instead of the flags, e.g. for a jump that tests a register.
+    /// The condition is evaluated as if the zero, signed and parity flags were set from the value.
+    ///
+    /// Returns `None` for the carry conditions, as the carry cannot be derived from a value.
+    #[inline]
+    #[must_use]
+    pub fn check_value<W: Word>(self, val: W) -> Option<bool> {
+        let (zero, signed, parity) = (val.is_zero(), val.is_negative(), has_even_parity(val));
+        Some(match self {
+            Self::Carry | Self::NotCarry => return None,
+            Self::Unconditional => true,
+            Self::Zero => zero,
+            Self::NotZero => !zero,
+            Self::Signed => signed,
+            Self::NotSigned => !signed,
+            Self::Greater => !zero && !signed,
+            Self::Less => !zero && signed,
+            Self::GreaterOrEq => zero || !signed,
+            Self::LessOrEq => zero || signed,
+            Self::ParityEven => parity,
+            Self::ParityOdd => !parity,
+        })
+    }
+}
+
+impl Display for JumpCondition {
+    /// Formats the jump condition as the mnemonic of the corresponding jump instruction.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.jump_mnemonic())
+    }
+}
+
+impl FromStr for JumpCondition {
+    type Err = ParserError;
+
+    /// Parses the condition part of a mnemonic in any case, e.g. `NZ`, or the mnemonic of the jump instruction
+    /// as it is formatted by [`Display`], e.g. `JNZ`. The empty suffix is [`Unconditional`](JumpCondition::Unconditional).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|cond| cond.suffix().eq_ignore_ascii_case(s) || cond.jump_mnemonic().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParserError::UnknownJumpCondition { cond: s.to_string() })
+    }
+}
+
+impl TryFrom<&str> for JumpCondition {
+    type Error = ParserError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
 }
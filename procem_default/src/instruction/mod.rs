@@ -1,182 +1,846 @@
 pub(crate) mod asm_instruction;
 pub mod jump_condition;
 pub mod operand;
+pub mod stack_op;
 
-use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
 use std::ops::Deref;
 
 use procem::{
-    instruction::Instruction as InstructionTrait,
+    instruction::{Handler, Instruction as InstructionTrait},
     processor::Processor,
-    register::{Flag, Register},
+    program::ProgramError,
+    register::{Flag, FlagSet, Register},
+    stack::StackMemory,
     word::Word,
 };
 
 use crate::instruction::{
     asm_instruction::{
-        ASMJumpInstruction, ASMRegOperandInstruction, ASMRotateInstruction, ASMShiftInstruction,
-        ASMSingleOperandInstruction, ASMSingleRegInstruction, ASMTwoOperandInstruction,
+        ASMHalfWordInstruction, ASMJumpInstruction, ASMPseudoInstruction, ASMRegOperandInstruction,
+        ASMRotateInstruction, ASMShiftInstruction, ASMSingleOperandInstruction, ASMSingleRegInstruction,
+        ASMTwoOperandInstruction,
     },
     jump_condition::JumpCondition,
     operand::Operand,
+    stack_op::StackOp,
 };
 
 /// A default instruction set implementation, that can be used for the [procem](../../procem/index.html) crate.
+///
+/// Like in ARM, most instructions are predicated: they are only executed if their condition (`cond`) is met
+/// and are skipped otherwise. Instructions that are always executed use [`JumpCondition::Unconditional`].
+/// In assembly the condition is appended to the mnemonic, e.g. `ADDEQ R0, #1` or `RETNE`.
+/// The instructions that already have a condition (jumps, `SETcc`, `CMOVcc`) and `NOP` are not predicated.
+///
+/// Like the `S` suffix in ARM, `set_flags` only decides whether an instruction updates the flags.
+/// The arithmetic is always performed on two's complement words, so e.g. `ADD` and `ADDS` store the same result.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum Instruction<W> {
     /// No operation. (NOP)
     Nop,
     /// Copy a value from the operand to the register. (MOV)
-    Mov { to: Register, from: Operand<W> },
+    Mov {
+        to: Register,
+        from: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Set the high half of the register to the low half of the value, keeping the low half of the register. (MOVH)
+    Movh { to: Register, val: W, cond: JumpCondition },
+    /// Set the low half of the register to the low half of the value and clear the high half of the register. (MOVL)
+    Movl { to: Register, val: W, cond: JumpCondition },
     /// Push a value from the operand to the stack. (PUSH)
-    Push { from: Operand<W> },
+    Push { from: Operand<W>, cond: JumpCondition },
     /// Pop a value from the stack to the register. (POP)
-    Pop { to: Register },
+    Pop { to: Register, cond: JumpCondition },
+    /// Swap the value of the register with the value on the stack at the address specified by the operand. (SWP)
+    ///
+    /// The stack is the processor's memory. The value at the address is read into a temporary,
+    /// the old value of the register is written to the address and then the temporary is written to the register.
+    /// Like ARM's `SWP` the exchange is atomic: no other instruction can observe or modify the memory in between.
+    /// As the processor is single-threaded this is trivially the case.
+    Swp {
+        reg: Register,
+        addr: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Load the value on the stack at the address specified by the operand into the register. (LDR)
+    ///
+    /// Besides a register or literal address, the address can be given as base register plus offset, e.g. `[R1 + #4]`.
+    Ldr {
+        reg: Register,
+        addr: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Store the value of the register on the stack at the address specified by the operand. (STR)
+    ///
+    /// Besides a register or literal address, the address can be given as base register plus offset, e.g. `[R1 + #4]`.
+    Str {
+        reg: Register,
+        addr: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Read a value from the I/O port into the register. (IN)
+    ///
+    /// Faults with [`ProgramError::UnconnectedPort`] if no input handler is connected to the port.
+    In { to: Register, port: W, cond: JumpCondition },
+    /// Write the value of the operand to the I/O port. (OUT)
+    ///
+    /// Faults with [`ProgramError::UnconnectedPort`] if no output handler is connected to the port.
+    Out {
+        from: Operand<W>,
+        port: W,
+        cond: JumpCondition,
+    },
     /// Call a subroutine at the program address specified by the operand.
     /// Pushes the current program counter onto the stack and sets the program counter to the address of the subroutine. (CALL)
-    Call { addr: Operand<W> },
+    Call { addr: Operand<W>, cond: JumpCondition },
     /// Return from a subroutine.
     /// Pops the return address from the stack and sets the program counter to the popped value. (RET)
-    Ret,
+    Ret { cond: JumpCondition },
+    /// Pop the two topmost values `b` and then `a` from the stack and push the result of `a op b`. (SADD, SSUB, SMUL, SDIV)
+    /// The flags are not affected.
+    BinStack { op: StackOp, cond: JumpCondition },
     /// Add the value of the operand (rhs) to the register (acc).
     /// The result is stored in acc. (ADD\[S\])
     Add {
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
+        set_flags: bool,
+        cond: JumpCondition,
     },
     /// Subtract the value of the operand (rhs) from the register (acc).
     /// The result is stored in acc. (SUB\[S\])
     Sub {
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
+        set_flags: bool,
+        cond: JumpCondition,
     },
     /// Multiply the value of the operand (rhs) with the value of the register (acc).
     /// The result is stored in acc. (MUL\[S\])
     Mul {
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Multiply the value of the operand (rhs) with the value of the register (acc).
+    /// The lower half of the double-width product is stored in acc and the upper half in dst_hi.
+    /// If acc and dst_hi are the same register, it holds the upper half. (MULH)
+    MulHigh {
+        acc: Register,
+        rhs: Operand<W>,
+        dst_hi: Register,
+        cond: JumpCondition,
+    },
+    /// Add the value of the operand (rhs) to the register (acc), clamping the result at the minimum and maximum of the word.
+    /// The result is stored in acc. The overflow flag is set if the result was clamped. (ADDSAT)
+    AddSat {
+        acc: Register,
+        rhs: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Subtract the value of the operand (rhs) from the register (acc), clamping the result at the minimum and maximum of the word.
+    /// The result is stored in acc. The overflow flag is set if the result was clamped. (SUBSAT)
+    SubSat {
+        acc: Register,
+        rhs: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Store the smaller of the values of the register (acc) and the operand (rhs) in acc.
+    /// The values are compared as signed numbers, or as unsigned numbers if `unsigned` is set. (MIN\[U\])
+    Min {
+        acc: Register,
+        rhs: Operand<W>,
+        unsigned: bool,
+        cond: JumpCondition,
+    },
+    /// Store the larger of the values of the register (acc) and the operand (rhs) in acc.
+    /// The values are compared as signed numbers, or as unsigned numbers if `unsigned` is set. (MAX\[U\])
+    Max {
+        acc: Register,
+        rhs: Operand<W>,
+        unsigned: bool,
+        cond: JumpCondition,
+    },
+    /// Raise the register (reg) to the power of the operand (exp), wrapping around on overflow.
+    /// A negative exponent sets the overflow flag and leaves the register unchanged. (POW)
+    Pow {
+        reg: Register,
+        exp: Operand<W>,
+        cond: JumpCondition,
     },
+    /// Replace the value in the register with -1, 0 or 1 according to its sign.
+    /// The signed, zero and parity flags are set depending on the result. (SGN)
+    Sgn { reg: Register, cond: JumpCondition },
+    /// Replace the value in the register with its absolute value.
+    /// The absolute value of the minimum wraps around to the minimum, which sets the overflow flag. (ABS)
+    Abs { reg: Register, cond: JumpCondition },
+    /// Sign-extend the low byte of the register to the full word. Flags are not affected. (SXT)
+    Sxt { reg: Register, cond: JumpCondition },
+    /// Zero-extend the low byte of the register to the full word. Flags are not affected. (ZXT)
+    Zxt { reg: Register, cond: JumpCondition },
+    /// Reverse the order of the bits in the register.
+    /// The zero flag is set depending on the result. (BREV)
+    Brev { reg: Register, cond: JumpCondition },
+    /// Replace the value in the register with the number of its leading zero bits.
+    /// The count of zero is the number of bits of the word. Flags are not affected. (CLZ)
+    Clz { reg: Register, cond: JumpCondition },
+    /// Replace the value in the register with the number of its trailing zero bits.
+    /// The count of zero is the number of bits of the word. Flags are not affected. (CTZ)
+    Ctz { reg: Register, cond: JumpCondition },
+    /// Reverse the order of the bytes in the register, e.g. to convert between little and big endian.
+    /// Flags are not affected. (BSWAP)
+    Bswap { reg: Register, cond: JumpCondition },
     /// Divide the value of the register (acc) by the value of the operand (rhs).
     /// The result is stored in acc. (DIV\[S\])
     Div {
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
+        set_flags: bool,
+        cond: JumpCondition,
     },
     /// Increment the value in a register by one. (INC\[S\])
-    Inc { reg: Register, signed: bool },
+    Inc {
+        reg: Register,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
     /// Decrement the value in a register by one. (DEC\[S\])
-    Dec { reg: Register, signed: bool },
+    Dec {
+        reg: Register,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
     /// Set program counter to a value, effectively jumping to the instruction at this point in the program.
     /// The condition is checked before jumping and the jump is performed if the condition is met.
     /// See the assembly instruction at `JumpCondition`.
     Jump { to: W, condition: JumpCondition },
+    /// Add an offset to the program counter, which already points to the next instruction, if the condition is met.
+    /// Unlike [`Jump`](Instruction::Jump) this does not depend on the position of the program in memory.
+    /// See the assembly instruction at `JumpCondition`, e.g. `JZ` corresponds to `JRZ`. (JRcc)
+    JumpRel { offset: W, condition: JumpCondition },
+    /// Set the register to 1 if the condition is met and to 0 otherwise.
+    /// See the assembly instruction at `JumpCondition`, e.g. `JZ` corresponds to `SETZ`. (SETcc)
+    SetIf { reg: Register, condition: JumpCondition },
+    /// Copy a value from the operand to the register if the condition is met. The flags are not affected.
+    /// See the assembly instruction at `JumpCondition`, e.g. `JZ` corresponds to `CMOVZ`. (CMOVcc)
+    CmovIf {
+        to: Register,
+        from: Operand<W>,
+        condition: JumpCondition,
+    },
     /// Compare the values of two operands and set the flags accordingly. This is the same as `SUBS` but disregards the result of the subtraction. (CMP)
-    Cmp { lhs: Operand<W>, rhs: Operand<W> },
-    /// Perform an xor operation on the value in the register with the value of the operand. (XOR)
-    Xor { reg: Register, rhs: Operand<W> },
-    /// Perform an and operation on the value in the register with the value of the operand. (AND)
-    And { reg: Register, rhs: Operand<W> },
-    /// Perform an or operation on the value in the register with the value of the operand. (OR)
-    Or { reg: Register, rhs: Operand<W> },
-    /// Perform a not operation on the value in the register. (NOT)
-    Not { reg: Register },
-    /// Shift the value in the register left by the specified number of bits.
-    /// The assembler only accepts values between 1 and the number of bits of the Word size minus 1.
-    Shl { reg: Register, val: W },
-    /// Shift the value in the register right by the specified number of bits.
-    /// The assembler only accepts values between 1 and the number of bits of the Word size minus 1.
-    Shr { reg: Register, val: W },
-    /// Rotate the value in the register left by the specified number of bits.
-    /// The assembler only accepts values between 1 and the number of bits of the Word size minus 1.
-    Rol { reg: Register, val: u32 },
-    /// Rotate the value in the register right by the specified number of bits.
-    /// The assembler only accepts values between 1 and the number of bits of the Word size minus 1.
-    Ror { reg: Register, val: u32 },
+    Cmp {
+        lhs: Operand<W>,
+        rhs: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Perform an xor operation on the value in the register with the value of the operand. (XOR\[S\])
+    Xor {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform an and operation on the value in the register with the value of the operand. (AND\[S\])
+    And {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform an or operation on the value in the register with the value of the operand. (OR\[S\])
+    Or {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform a nand operation on the value in the register with the value of the operand. (NAND\[S\])
+    Nand {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform a nor operation on the value in the register with the value of the operand. (NOR\[S\])
+    Nor {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform a xnor operation on the value in the register with the value of the operand. (XNOR\[S\])
+    Xnor {
+        reg: Register,
+        rhs: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Perform a not operation on the value in the register. (NOT\[S\])
+    Not {
+        reg: Register,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Shift the value in the register left by the number of bits specified by the operand. (SHL\[S\])
+    /// The number of bits is masked to the number of bits of the Word size, e.g. a shift by 33 bits shifts a 32-bit word by 1 bit.
+    Shl {
+        reg: Register,
+        val: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Shift the value in the register right by the number of bits specified by the operand. (SHR\[S\])
+    /// The number of bits is masked to the number of bits of the Word size, e.g. a shift by 33 bits shifts a 32-bit word by 1 bit.
+    Shr {
+        reg: Register,
+        val: Operand<W>,
+        set_flags: bool,
+        cond: JumpCondition,
+    },
+    /// Rotate the value in the register left by the number of bits specified by the operand.
+    /// The number of bits is masked to the number of bits of the Word size, like for the shifts.
+    Rol {
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Rotate the value in the register right by the number of bits specified by the operand.
+    /// The number of bits is masked to the number of bits of the Word size, like for the shifts.
+    Ror {
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Rotate the value in the register left through the carry flag by the number of bits specified by the operand.
+    /// The carry flag is shifted into the least significant bit and the most significant bit is shifted into the carry flag.
+    /// The number of bits is masked to the number of bits of the Word size, like for the shifts.
+    Rcl {
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    },
+    /// Rotate the value in the register right through the carry flag by the number of bits specified by the operand.
+    /// The carry flag is shifted into the most significant bit and the least significant bit is shifted into the carry flag.
+    /// The number of bits is masked to the number of bits of the Word size, like for the shifts.
+    Rcr {
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    },
 }
 
 impl<W: Word> InstructionTrait<W> for Instruction<W> {
+    type Flags = FlagSet;
+
     /// Execute an instruction on a processor.
-    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn execute<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         instruction: Self,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
+        if let Some(cond) = instruction.cond()
+            && !cond.check(processor)
+        {
+            return;
+        }
+
         match instruction {
             Self::Nop => (),
-            Self::Mov { to, from } => Self::mov(to, from, processor),
-            Self::Push { from } => Self::push(from, processor),
-            Self::Pop { to } => Self::pop(to, processor),
-            Self::Call { addr } => Self::call(addr, processor),
-            Self::Ret => Self::ret(processor),
-            Self::Add { acc, rhs, signed } => Self::add(acc, rhs, signed, processor),
-            Self::Sub { acc, rhs, signed } => Self::sub(acc, rhs, signed, processor),
-            Self::Mul { acc, rhs, signed } => Self::mul(acc, rhs, signed, processor),
-            Self::Div { acc, rhs, signed } => Self::div(acc, rhs, signed, processor),
-            Self::Inc { reg, signed } => Self::inc(reg, signed, processor),
-            Self::Dec { reg, signed } => Self::dec(reg, signed, processor),
+            Self::Mov { to, from, .. } => Self::mov(to, from, processor),
+            Self::Movh { to, val, .. } => Self::movh(to, val, processor),
+            Self::Movl { to, val, .. } => Self::movl(to, val, processor),
+            Self::Push { from, .. } => Self::push(from, processor),
+            Self::Pop { to, .. } => Self::pop(to, processor),
+            Self::Swp { reg, addr, .. } => Self::swp(reg, addr, processor),
+            Self::Ldr { reg, addr, .. } => Self::ldr(reg, addr, processor),
+            Self::Str { reg, addr, .. } => Self::str(reg, addr, processor),
+            Self::In { to, port, .. } => Self::input(to, port, processor),
+            Self::Out { from, port, .. } => Self::output(from, port, processor),
+            Self::Call { addr, .. } => Self::call(addr, processor),
+            Self::Ret { .. } => Self::ret(processor),
+            Self::BinStack { op, .. } => Self::bin_stack(op, processor),
+            Self::Add {
+                acc, rhs, set_flags, ..
+            } => Self::add(acc, rhs, set_flags, processor),
+            Self::Sub {
+                acc, rhs, set_flags, ..
+            } => Self::sub(acc, rhs, set_flags, processor),
+            Self::Mul {
+                acc, rhs, set_flags, ..
+            } => Self::mul(acc, rhs, set_flags, processor),
+            Self::MulHigh { acc, rhs, dst_hi, .. } => Self::mul_high(acc, rhs, dst_hi, processor),
+            Self::AddSat { acc, rhs, .. } => Self::add_sat(acc, rhs, processor),
+            Self::Min { acc, rhs, unsigned, .. } => Self::min(acc, rhs, unsigned, processor),
+            Self::Max { acc, rhs, unsigned, .. } => Self::max(acc, rhs, unsigned, processor),
+            Self::Pow { reg, exp, .. } => Self::pow(reg, exp, processor),
+            Self::SubSat { acc, rhs, .. } => Self::sub_sat(acc, rhs, processor),
+            Self::Div {
+                acc, rhs, set_flags, ..
+            } => Self::div(acc, rhs, set_flags, processor),
+            Self::Inc { reg, set_flags, .. } => Self::inc(reg, set_flags, processor),
+            Self::Dec { reg, set_flags, .. } => Self::dec(reg, set_flags, processor),
+            Self::Sgn { reg, .. } => Self::sgn(reg, processor),
+            Self::Abs { reg, .. } => Self::abs(reg, processor),
+            Self::Sxt { reg, .. } => Self::sxt(reg, processor),
+            Self::Zxt { reg, .. } => Self::zxt(reg, processor),
+            Self::Brev { reg, .. } => Self::brev(reg, processor),
+            Self::Clz { reg, .. } => Self::clz(reg, processor),
+            Self::Ctz { reg, .. } => Self::ctz(reg, processor),
+            Self::Bswap { reg, .. } => Self::bswap(reg, processor),
             Self::Jump { to, condition } => Self::jmp(to, condition, processor),
-            Self::Cmp { lhs, rhs } => Self::cmp(lhs, rhs, processor),
-            Self::Xor { reg, rhs } => Self::xor(reg, rhs, processor),
-            Self::Or { reg, rhs } => Self::or(reg, rhs, processor),
-            Self::And { reg, rhs } => Self::and(reg, rhs, processor),
-            Self::Not { reg } => Self::not(reg, processor),
-            Self::Shl { reg, val } => Self::shl(reg, val, processor),
-            Self::Shr { reg, val } => Self::shr(reg, val, processor),
-            Self::Rol { reg, val } => Self::rol(reg, val, processor),
-            Self::Ror { reg, val } => Self::ror(reg, val, processor),
+            Self::JumpRel { offset, condition } => Self::jmp_rel(offset, condition, processor),
+            Self::SetIf { reg, condition } => Self::set_if(reg, condition, processor),
+            Self::CmovIf { to, from, condition } => Self::cmov_if(to, from, condition, processor),
+            Self::Cmp { lhs, rhs, .. } => Self::cmp(lhs, rhs, processor),
+            Self::Xor {
+                reg, rhs, set_flags, ..
+            } => Self::xor(reg, rhs, set_flags, processor),
+            Self::Or {
+                reg, rhs, set_flags, ..
+            } => Self::or(reg, rhs, set_flags, processor),
+            Self::And {
+                reg, rhs, set_flags, ..
+            } => Self::and(reg, rhs, set_flags, processor),
+            Self::Nand {
+                reg, rhs, set_flags, ..
+            } => Self::nand(reg, rhs, set_flags, processor),
+            Self::Nor {
+                reg, rhs, set_flags, ..
+            } => Self::nor(reg, rhs, set_flags, processor),
+            Self::Xnor {
+                reg, rhs, set_flags, ..
+            } => Self::xnor(reg, rhs, set_flags, processor),
+            Self::Not { reg, set_flags, .. } => Self::not(reg, set_flags, processor),
+            Self::Shl {
+                reg, val, set_flags, ..
+            } => Self::shl(reg, val, set_flags, processor),
+            Self::Shr {
+                reg, val, set_flags, ..
+            } => Self::shr(reg, val, set_flags, processor),
+            Self::Rol { reg, val, .. } => Self::rol(reg, val, processor),
+            Self::Ror { reg, val, .. } => Self::ror(reg, val, processor),
+            Self::Rcl { reg, val, .. } => Self::rcl(reg, val, processor),
+            Self::Rcr { reg, val, .. } => Self::rcr(reg, val, processor),
+        }
+    }
+
+    /// Returns a handler that only executes the opcode of this instruction.
+    // skips formatting the match
+    #[rustfmt::skip]
+    fn handler<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(&self) -> Handler<STACK_SIZE, Self, P, W, S> {
+        // Creates a handler for a single variant. The variant is already known when the handler is selected,
+        // so the handler only destructures the instruction.
+        // Predicated variants name their condition after `if` and are skipped if the condition is not met.
+        macro_rules! handler {
+            ($pattern: pat => |$processor: ident| $body: expr) => {
+                |instruction, $processor| {
+                    let $pattern = instruction else {
+                        unreachable!("The dispatch table does not match the program. This should never happen.")
+                    };
+                    $body
+                }
+            };
+            ($pattern: pat if $cond: ident => |$processor: ident| $body: expr) => {
+                handler!($pattern => |$processor| if $cond.check($processor) { $body })
+            };
+        }
+
+        match self {
+            Self::Nop => |_, _| (),
+            Self::Mov { .. } => handler!(Self::Mov { to, from, cond } if cond => |processor| Self::mov(to, from, processor)),
+            Self::Movh { .. } => handler!(Self::Movh { to, val, cond } if cond => |processor| Self::movh(to, val, processor)),
+            Self::Movl { .. } => handler!(Self::Movl { to, val, cond } if cond => |processor| Self::movl(to, val, processor)),
+            Self::Push { .. } => handler!(Self::Push { from, cond } if cond => |processor| Self::push(from, processor)),
+            Self::Pop { .. } => handler!(Self::Pop { to, cond } if cond => |processor| Self::pop(to, processor)),
+            Self::Swp { .. } => handler!(Self::Swp { reg, addr, cond } if cond => |processor| Self::swp(reg, addr, processor)),
+            Self::Ldr { .. } => handler!(Self::Ldr { reg, addr, cond } if cond => |processor| Self::ldr(reg, addr, processor)),
+            Self::Str { .. } => handler!(Self::Str { reg, addr, cond } if cond => |processor| Self::str(reg, addr, processor)),
+            Self::In { .. } => handler!(Self::In { to, port, cond } if cond => |processor| Self::input(to, port, processor)),
+            Self::Out { .. } => handler!(Self::Out { from, port, cond } if cond => |processor| Self::output(from, port, processor)),
+            Self::Call { .. } => handler!(Self::Call { addr, cond } if cond => |processor| Self::call(addr, processor)),
+            Self::Ret { .. } => handler!(Self::Ret { cond } if cond => |processor| Self::ret(processor)),
+            Self::BinStack { .. } => handler!(Self::BinStack { op, cond } if cond => |processor| Self::bin_stack(op, processor)),
+            Self::Add { .. } => handler!(Self::Add { acc, rhs, set_flags, cond } if cond => |processor| Self::add(acc, rhs, set_flags, processor)),
+            Self::Sub { .. } => handler!(Self::Sub { acc, rhs, set_flags, cond } if cond => |processor| Self::sub(acc, rhs, set_flags, processor)),
+            Self::Mul { .. } => handler!(Self::Mul { acc, rhs, set_flags, cond } if cond => |processor| Self::mul(acc, rhs, set_flags, processor)),
+            Self::MulHigh { .. } => handler!(Self::MulHigh { acc, rhs, dst_hi, cond } if cond => |processor| Self::mul_high(acc, rhs, dst_hi, processor)),
+            Self::AddSat { .. } => handler!(Self::AddSat { acc, rhs, cond } if cond => |processor| Self::add_sat(acc, rhs, processor)),
+            Self::Min { .. } => handler!(Self::Min { acc, rhs, unsigned, cond } if cond => |processor| Self::min(acc, rhs, unsigned, processor)),
+            Self::Max { .. } => handler!(Self::Max { acc, rhs, unsigned, cond } if cond => |processor| Self::max(acc, rhs, unsigned, processor)),
+            Self::Pow { .. } => handler!(Self::Pow { reg, exp, cond } if cond => |processor| Self::pow(reg, exp, processor)),
+            Self::SubSat { .. } => handler!(Self::SubSat { acc, rhs, cond } if cond => |processor| Self::sub_sat(acc, rhs, processor)),
+            Self::Div { .. } => handler!(Self::Div { acc, rhs, set_flags, cond } if cond => |processor| Self::div(acc, rhs, set_flags, processor)),
+            Self::Inc { .. } => handler!(Self::Inc { reg, set_flags, cond } if cond => |processor| Self::inc(reg, set_flags, processor)),
+            Self::Dec { .. } => handler!(Self::Dec { reg, set_flags, cond } if cond => |processor| Self::dec(reg, set_flags, processor)),
+            Self::Sgn { .. } => handler!(Self::Sgn { reg, cond } if cond => |processor| Self::sgn(reg, processor)),
+            Self::Abs { .. } => handler!(Self::Abs { reg, cond } if cond => |processor| Self::abs(reg, processor)),
+            Self::Sxt { .. } => handler!(Self::Sxt { reg, cond } if cond => |processor| Self::sxt(reg, processor)),
+            Self::Zxt { .. } => handler!(Self::Zxt { reg, cond } if cond => |processor| Self::zxt(reg, processor)),
+            Self::Brev { .. } => handler!(Self::Brev { reg, cond } if cond => |processor| Self::brev(reg, processor)),
+            Self::Clz { .. } => handler!(Self::Clz { reg, cond } if cond => |processor| Self::clz(reg, processor)),
+            Self::Ctz { .. } => handler!(Self::Ctz { reg, cond } if cond => |processor| Self::ctz(reg, processor)),
+            Self::Bswap { .. } => handler!(Self::Bswap { reg, cond } if cond => |processor| Self::bswap(reg, processor)),
+            Self::Jump { .. } => handler!(Self::Jump { to, condition } => |processor| Self::jmp(to, condition, processor)),
+            Self::JumpRel { .. } => handler!(Self::JumpRel { offset, condition } => |processor| Self::jmp_rel(offset, condition, processor)),
+            Self::SetIf { .. } => handler!(Self::SetIf { reg, condition } => |processor| Self::set_if(reg, condition, processor)),
+            Self::CmovIf { .. } => handler!(Self::CmovIf { to, from, condition } => |processor| Self::cmov_if(to, from, condition, processor)),
+            Self::Cmp { .. } => handler!(Self::Cmp { lhs, rhs, cond } if cond => |processor| Self::cmp(lhs, rhs, processor)),
+            Self::Xor { .. } => handler!(Self::Xor { reg, rhs, set_flags, cond } if cond => |processor| Self::xor(reg, rhs, set_flags, processor)),
+            Self::Or { .. } => handler!(Self::Or { reg, rhs, set_flags, cond } if cond => |processor| Self::or(reg, rhs, set_flags, processor)),
+            Self::And { .. } => handler!(Self::And { reg, rhs, set_flags, cond } if cond => |processor| Self::and(reg, rhs, set_flags, processor)),
+            Self::Nand { .. } => handler!(Self::Nand { reg, rhs, set_flags, cond } if cond => |processor| Self::nand(reg, rhs, set_flags, processor)),
+            Self::Nor { .. } => handler!(Self::Nor { reg, rhs, set_flags, cond } if cond => |processor| Self::nor(reg, rhs, set_flags, processor)),
+            Self::Xnor { .. } => handler!(Self::Xnor { reg, rhs, set_flags, cond } if cond => |processor| Self::xnor(reg, rhs, set_flags, processor)),
+            Self::Not { .. } => handler!(Self::Not { reg, set_flags, cond } if cond => |processor| Self::not(reg, set_flags, processor)),
+            Self::Shl { .. } => handler!(Self::Shl { reg, val, set_flags, cond } if cond => |processor| Self::shl(reg, val, set_flags, processor)),
+            Self::Shr { .. } => handler!(Self::Shr { reg, val, set_flags, cond } if cond => |processor| Self::shr(reg, val, set_flags, processor)),
+            Self::Rol { .. } => handler!(Self::Rol { reg, val, cond } if cond => |processor| Self::rol(reg, val, processor)),
+            Self::Ror { .. } => handler!(Self::Ror { reg, val, cond } if cond => |processor| Self::ror(reg, val, processor)),
+            Self::Rcl { .. } => handler!(Self::Rcl { reg, val, cond } if cond => |processor| Self::rcl(reg, val, processor)),
+            Self::Rcr { .. } => handler!(Self::Rcr { reg, val, cond } if cond => |processor| Self::rcr(reg, val, processor)),
+        }
+    }
+
+    /// Returns the mnemonic of the instruction, see [`Instruction::mnemonic`].
+    #[inline]
+    fn mnemonic(&self) -> &'static str {
+        Self::mnemonic(self)
+    }
+
+    /// Returns the clock cycles of the instruction.
+    ///
+    /// Most instructions take one cycle. Instructions that access the stack or change the control flow take two or three cycles,
+    /// multiplication takes three and division twelve cycles.
+    /// Predicated instructions take the same number of cycles, whether their condition is met or not.
+    #[inline]
+    fn cycles(&self) -> u32 {
+        match self {
+            Self::Div { .. } | Self::BinStack { op: StackOp::Div, .. } => 12,
+            Self::Mul { .. } | Self::MulHigh { .. } | Self::Pow { .. } | Self::BinStack { op: StackOp::Mul, .. } => 3,
+            Self::Swp { .. } | Self::Call { .. } | Self::Ret { .. } => 3,
+            Self::Push { .. } | Self::Pop { .. } | Self::Ldr { .. } | Self::Str { .. } => 2,
+            Self::In { .. } | Self::Out { .. } => 2,
+            Self::Jump { .. } | Self::JumpRel { .. } => 2,
+            Self::BinStack { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns the target of jumps and of calls to a literal address.
+    /// Calls to an address in a register or on the stack are only known at runtime.
+    /// Relative jumps are not checked, as their target depends on their own address.
+    #[inline]
+    fn jump_target(&self) -> Option<usize> {
+        match self {
+            Self::Jump { to, .. }
+            | Self::Call {
+                addr: Operand::Value(to),
+                ..
+            } => Some((*to).into()),
+            _ => None,
+        }
+    }
+}
+
+impl<W: Word> Display for Instruction<W> {
+    /// Formats the instruction in assembly syntax, e.g. `ADDS R0, #1` or `MOVNE R0, R1`.
+    ///
+    /// Jump targets are formatted as the address of the instruction, as labels are not part of the assembled program.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.mnemonic())?;
+
+        if let Some(cond) = self.cond() {
+            write!(f, "{}", cond.predicate_suffix())?;
+        }
+
+        match self {
+            Self::Nop | Self::Ret { .. } | Self::BinStack { .. } => Ok(()),
+            Self::Mov { to, from, .. } => write!(f, " {to}, {from}"),
+            Self::Movh { to, val, .. } | Self::Movl { to, val, .. } => write!(f, " {to}, #{val}"),
+            Self::Push { from, .. } => write!(f, " {from}"),
+            Self::Pop { to, .. } => write!(f, " {to}"),
+            Self::Swp { reg, addr, .. } | Self::Ldr { reg, addr, .. } | Self::Str { reg, addr, .. } => {
+                write!(f, " {reg}, {addr}")
+            }
+            Self::In { to, port, .. } => write!(f, " {to}, #{port}"),
+            Self::Out { from, port, .. } => write!(f, " #{port}, {from}"),
+            Self::Call { addr, .. } => write!(f, " {addr}"),
+            Self::MulHigh { acc, rhs, dst_hi, .. } => write!(f, " {acc}, {dst_hi}, {rhs}"),
+            Self::Add { acc, rhs, .. }
+            | Self::Sub { acc, rhs, .. }
+            | Self::Mul { acc, rhs, .. }
+            | Self::Div { acc, rhs, .. }
+            | Self::AddSat { acc, rhs, .. }
+            | Self::Min { acc, rhs, .. }
+            | Self::Max { acc, rhs, .. }
+            | Self::Pow { reg: acc, exp: rhs, .. }
+            | Self::SubSat { acc, rhs, .. } => write!(f, " {acc}, {rhs}"),
+            Self::Inc { reg, .. }
+            | Self::Dec { reg, .. }
+            | Self::Not { reg, .. }
+            | Self::Sgn { reg, .. }
+            | Self::Abs { reg, .. }
+            | Self::Sxt { reg, .. }
+            | Self::Zxt { reg, .. }
+            | Self::Brev { reg, .. }
+            | Self::Clz { reg, .. }
+            | Self::Ctz { reg, .. }
+            | Self::Bswap { reg, .. } => write!(f, " {reg}"),
+            Self::Jump { to, .. } => write!(f, " #{to}"),
+            Self::JumpRel { offset, .. } => write!(f, " #{offset}"),
+            Self::SetIf { reg, .. } => write!(f, " {reg}"),
+            Self::CmovIf { to, from, .. } => write!(f, " {to}, {from}"),
+            Self::Cmp { lhs, rhs, .. } => write!(f, " {lhs}, {rhs}"),
+            Self::Xor { reg, rhs, .. }
+            | Self::And { reg, rhs, .. }
+            | Self::Or { reg, rhs, .. }
+            | Self::Nand { reg, rhs, .. }
+            | Self::Nor { reg, rhs, .. }
+            | Self::Xnor { reg, rhs, .. } => write!(f, " {reg}, {rhs}"),
+            Self::Shl { reg, val, .. } | Self::Shr { reg, val, .. } => write!(f, " {reg}, {val}"),
+            Self::Rol { reg, val, .. }
+            | Self::Ror { reg, val, .. }
+            | Self::Rcl { reg, val, .. }
+            | Self::Rcr { reg, val, .. } => write!(f, " {reg}, {val}"),
         }
     }
 }
 
 impl<W: Word> Instruction<W> {
+    /// Returns the assembly mnemonic of the instruction, e.g. `ADDS` or `JNZ`.
+    ///
+    /// The condition of a predicated instruction is not part of the mnemonic, e.g. `ADDEQ` returns `ADD`.
+    /// Apart from that it is the same as the first word of the [`Display`] output, but does not require formatting.
+    #[must_use]
+    pub const fn mnemonic(&self) -> &'static str {
+        // Returns the mnemonic with the suffix of the instructions that set the flags, or with another suffix for a variant.
+        macro_rules! s {
+            ($set_flags: expr, $mnemonic: literal) => {
+                s!($set_flags, $mnemonic, "S")
+            };
+            ($flag: expr, $mnemonic: literal, $suffix: literal) => {
+                if *$flag {
+                    concat!($mnemonic, $suffix)
+                } else {
+                    $mnemonic
+                }
+            };
+        }
+
+        match self {
+            Self::Nop => "NOP",
+            Self::Mov { .. } => "MOV",
+            Self::Movh { .. } => "MOVH",
+            Self::Movl { .. } => "MOVL",
+            Self::Push { .. } => "PUSH",
+            Self::Pop { .. } => "POP",
+            Self::Swp { .. } => "SWP",
+            Self::Ldr { .. } => "LDR",
+            Self::Str { .. } => "STR",
+            Self::In { .. } => "IN",
+            Self::Out { .. } => "OUT",
+            Self::Call { .. } => "CALL",
+            Self::Ret { .. } => "RET",
+            Self::BinStack { op, .. } => op.mnemonic(),
+            Self::Add { set_flags, .. } => s!(set_flags, "ADD"),
+            Self::Sub { set_flags, .. } => s!(set_flags, "SUB"),
+            Self::Mul { set_flags, .. } => s!(set_flags, "MUL"),
+            Self::MulHigh { .. } => "MULH",
+            Self::AddSat { .. } => "ADDSAT",
+            Self::Min { unsigned, .. } => s!(unsigned, "MIN", "U"),
+            Self::Max { unsigned, .. } => s!(unsigned, "MAX", "U"),
+            Self::Pow { .. } => "POW",
+            Self::SubSat { .. } => "SUBSAT",
+            Self::Div { set_flags, .. } => s!(set_flags, "DIV"),
+            Self::Inc { set_flags, .. } => s!(set_flags, "INC"),
+            Self::Dec { set_flags, .. } => s!(set_flags, "DEC"),
+            Self::Sgn { .. } => "SGN",
+            Self::Abs { .. } => "ABS",
+            Self::Sxt { .. } => "SXT",
+            Self::Zxt { .. } => "ZXT",
+            Self::Brev { .. } => "BREV",
+            Self::Clz { .. } => "CLZ",
+            Self::Ctz { .. } => "CTZ",
+            Self::Bswap { .. } => "BSWAP",
+            Self::Jump { condition, .. } => condition.jump_mnemonic(),
+            Self::JumpRel { condition, .. } => condition.rel_jump_mnemonic(),
+            Self::SetIf { condition, .. } => condition.set_mnemonic(),
+            Self::CmovIf { condition, .. } => condition.cmov_mnemonic(),
+            Self::Cmp { .. } => "CMP",
+            Self::Xor { set_flags, .. } => s!(set_flags, "XOR"),
+            Self::And { set_flags, .. } => s!(set_flags, "AND"),
+            Self::Or { set_flags, .. } => s!(set_flags, "OR"),
+            Self::Nand { set_flags, .. } => s!(set_flags, "NAND"),
+            Self::Nor { set_flags, .. } => s!(set_flags, "NOR"),
+            Self::Xnor { set_flags, .. } => s!(set_flags, "XNOR"),
+            Self::Not { set_flags, .. } => s!(set_flags, "NOT"),
+            Self::Shl { set_flags, .. } => s!(set_flags, "SHL"),
+            Self::Shr { set_flags, .. } => s!(set_flags, "SHR"),
+            Self::Rol { .. } => "ROL",
+            Self::Ror { .. } => "ROR",
+            Self::Rcl { .. } => "RCL",
+            Self::Rcr { .. } => "RCR",
+        }
+    }
+
+    /// Returns the condition of a predicated instruction.
+    /// The instruction is only executed if the condition is met.
+    ///
+    /// Returns `None` for the instructions that are not predicated, i.e. `NOP`, jumps, `SETcc` and `CMOVcc`.
+    #[must_use]
+    pub const fn cond(&self) -> Option<JumpCondition> {
+        match *self {
+            Self::Nop | Self::Jump { .. } | Self::JumpRel { .. } | Self::SetIf { .. } | Self::CmovIf { .. } => None,
+            Self::Mov { cond, .. }
+            | Self::Movh { cond, .. }
+            | Self::Movl { cond, .. }
+            | Self::Push { cond, .. }
+            | Self::Pop { cond, .. }
+            | Self::Swp { cond, .. }
+            | Self::Ldr { cond, .. }
+            | Self::Str { cond, .. }
+            | Self::In { cond, .. }
+            | Self::Out { cond, .. }
+            | Self::Call { cond, .. }
+            | Self::Ret { cond }
+            | Self::BinStack { cond, .. }
+            | Self::Add { cond, .. }
+            | Self::Sub { cond, .. }
+            | Self::Mul { cond, .. }
+            | Self::MulHigh { cond, .. }
+            | Self::AddSat { cond, .. }
+            | Self::Min { cond, .. }
+            | Self::Max { cond, .. }
+            | Self::Pow { cond, .. }
+            | Self::SubSat { cond, .. }
+            | Self::Sgn { cond, .. }
+            | Self::Abs { cond, .. }
+            | Self::Sxt { cond, .. }
+            | Self::Zxt { cond, .. }
+            | Self::Brev { cond, .. }
+            | Self::Clz { cond, .. }
+            | Self::Ctz { cond, .. }
+            | Self::Bswap { cond, .. }
+            | Self::Div { cond, .. }
+            | Self::Inc { cond, .. }
+            | Self::Dec { cond, .. }
+            | Self::Cmp { cond, .. }
+            | Self::Xor { cond, .. }
+            | Self::And { cond, .. }
+            | Self::Or { cond, .. }
+            | Self::Nand { cond, .. }
+            | Self::Nor { cond, .. }
+            | Self::Xnor { cond, .. }
+            | Self::Not { cond, .. }
+            | Self::Shl { cond, .. }
+            | Self::Shr { cond, .. }
+            | Self::Rol { cond, .. }
+            | Self::Ror { cond, .. }
+            | Self::Rcl { cond, .. }
+            | Self::Rcr { cond, .. } => Some(cond),
+        }
+    }
+
     // skips forrmatting the match
     #[rustfmt::skip]
     pub(crate) const fn from_reg_operand_instruction(
         instr: ASMRegOperandInstruction,
         lhs: Register,
-        rhs: Operand<W>
+        rhs: Operand<W>,
+        cond: JumpCondition,
     ) -> Self {
-        use ASMRegOperandInstruction::{Mov, Add, AddS, Sub, SubS, Mul, MulS, Div, DivS, Or, And, Xor};
+        use ASMRegOperandInstruction::{Mov, Add, AddS, AddSat, Sub, SubS, SubSat, Mul, MulS, Min, MinU, Max, MaxU, Pow, Div, DivS, Or, OrS, And, AndS, Swp, Ldr, Str, Xor, XorS, Nand, NandS, Nor, NorS, Xnor, XnorS};
         match instr {
-            Mov => Self::Mov { to: lhs, from: rhs },
-            Add => Self::Add { acc: lhs, rhs, signed: false },
-            AddS => Self::Add { acc: lhs, rhs, signed: true },
-            Sub => Self::Sub { acc: lhs, rhs, signed: false },
-            SubS => Self::Sub { acc: lhs, rhs, signed: true },
-            Mul => Self::Mul { acc: lhs, rhs, signed: false },
-            MulS => Self::Mul { acc: lhs, rhs, signed: true },
-            Div => Self::Div { acc: lhs, rhs, signed: false },
-            DivS => Self::Div { acc: lhs, rhs, signed: true },
-            Or => Self::Or { reg: lhs, rhs },
-            And => Self::And { reg: lhs, rhs },
-            Xor => Self::Xor { reg: lhs, rhs },
-        }
-    }
-
-    pub(crate) const fn from_single_reg_instruction(instr: ASMSingleRegInstruction, reg: Register) -> Self {
-        use ASMSingleRegInstruction::{Dec, DecS, Inc, IncS, Not, Pop};
+            Mov => Self::Mov { to: lhs, from: rhs, cond },
+            Add => Self::Add { acc: lhs, rhs, set_flags: false, cond },
+            AddS => Self::Add { acc: lhs, rhs, set_flags: true, cond },
+            Sub => Self::Sub { acc: lhs, rhs, set_flags: false, cond },
+            SubS => Self::Sub { acc: lhs, rhs, set_flags: true, cond },
+            Mul => Self::Mul { acc: lhs, rhs, set_flags: false, cond },
+            MulS => Self::Mul { acc: lhs, rhs, set_flags: true, cond },
+            AddSat => Self::AddSat { acc: lhs, rhs, cond },
+            Min => Self::Min { acc: lhs, rhs, unsigned: false, cond },
+            MinU => Self::Min { acc: lhs, rhs, unsigned: true, cond },
+            Max => Self::Max { acc: lhs, rhs, unsigned: false, cond },
+            MaxU => Self::Max { acc: lhs, rhs, unsigned: true, cond },
+            Pow => Self::Pow { reg: lhs, exp: rhs, cond },
+            SubSat => Self::SubSat { acc: lhs, rhs, cond },
+            Div => Self::Div { acc: lhs, rhs, set_flags: false, cond },
+            DivS => Self::Div { acc: lhs, rhs, set_flags: true, cond },
+            Or => Self::Or { reg: lhs, rhs, set_flags: false, cond },
+            OrS => Self::Or { reg: lhs, rhs, set_flags: true, cond },
+            And => Self::And { reg: lhs, rhs, set_flags: false, cond },
+            AndS => Self::And { reg: lhs, rhs, set_flags: true, cond },
+            Xor => Self::Xor { reg: lhs, rhs, set_flags: false, cond },
+            XorS => Self::Xor { reg: lhs, rhs, set_flags: true, cond },
+            Nand => Self::Nand { reg: lhs, rhs, set_flags: false, cond },
+            NandS => Self::Nand { reg: lhs, rhs, set_flags: true, cond },
+            Nor => Self::Nor { reg: lhs, rhs, set_flags: false, cond },
+            NorS => Self::Nor { reg: lhs, rhs, set_flags: true, cond },
+            Xnor => Self::Xnor { reg: lhs, rhs, set_flags: false, cond },
+            XnorS => Self::Xnor { reg: lhs, rhs, set_flags: true, cond },
+            Swp => Self::Swp { reg: lhs, addr: rhs, cond },
+            Ldr => Self::Ldr { reg: lhs, addr: rhs, cond },
+            Str => Self::Str { reg: lhs, addr: rhs, cond },
+        }
+    }
+
+    // skips forrmatting the match
+    #[rustfmt::skip]
+    pub(crate) const fn from_single_reg_instruction(
+        instr: ASMSingleRegInstruction,
+        reg: Register,
+        cond: JumpCondition,
+    ) -> Self {
+        use ASMSingleRegInstruction::{Abs, Brev, Bswap, Clz, Ctz, Dec, DecS, Inc, IncS, Not, NotS, Pop, Sgn, Sxt, Zxt};
+        match instr {
+            Inc => Self::Inc { reg, set_flags: false, cond },
+            IncS => Self::Inc { reg, set_flags: true, cond },
+            Dec => Self::Dec { reg, set_flags: false, cond },
+            DecS => Self::Dec { reg, set_flags: true, cond },
+            Sgn => Self::Sgn { reg, cond },
+            Abs => Self::Abs { reg, cond },
+            Sxt => Self::Sxt { reg, cond },
+            Zxt => Self::Zxt { reg, cond },
+            Brev => Self::Brev { reg, cond },
+            Clz => Self::Clz { reg, cond },
+            Ctz => Self::Ctz { reg, cond },
+            Bswap => Self::Bswap { reg, cond },
+            Not => Self::Not { reg, set_flags: false, cond },
+            NotS => Self::Not { reg, set_flags: true, cond },
+            Pop => Self::Pop { to: reg, cond },
+        }
+    }
+
+    /// Expands a pseudo-instruction into the real instruction it stands for.
+    pub(crate) fn from_pseudo_instruction(instr: ASMPseudoInstruction, reg: Register, cond: JumpCondition) -> Self {
         match instr {
-            Inc => Self::Inc { reg, signed: false },
-            IncS => Self::Inc { reg, signed: true },
-            Dec => Self::Dec { reg, signed: false },
-            DecS => Self::Dec { reg, signed: true },
-            Not => Self::Not { reg },
-            Pop => Self::Pop { to: reg },
+            ASMPseudoInstruction::Clr => Self::Mov {
+                to: reg,
+                from: Operand::Value(0.into()),
+                cond,
+            },
+            ASMPseudoInstruction::Tst => Self::Cmp {
+                lhs: Operand::Register(reg),
+                rhs: Operand::Value(0.into()),
+                cond,
+            },
         }
     }
 
     pub(crate) const fn from_single_operand_instruction(
         instr: ASMSingleOperandInstruction,
         operand: Operand<W>,
+        cond: JumpCondition,
     ) -> Self {
         use ASMSingleOperandInstruction::{Call, Push};
 
         match instr {
-            Call => Self::Call { addr: operand },
-            Push => Self::Push { from: operand },
+            Call => Self::Call { addr: operand, cond },
+            Push => Self::Push { from: operand, cond },
         }
     }
 
@@ -184,137 +848,426 @@ impl<W: Word> Instruction<W> {
         instr: ASMTwoOperandInstruction,
         lhs: Operand<W>,
         rhs: Operand<W>,
+        cond: JumpCondition,
     ) -> Self {
         use ASMTwoOperandInstruction::Cmp;
 
         match instr {
-            Cmp => Self::Cmp { lhs, rhs },
+            Cmp => Self::Cmp { lhs, rhs, cond },
+        }
+    }
+
+    // skips forrmatting the match
+    #[rustfmt::skip]
+    pub(crate) const fn from_half_word_instruction(
+        instr: ASMHalfWordInstruction,
+        to: Register,
+        val: W,
+        cond: JumpCondition,
+    ) -> Self {
+        use ASMHalfWordInstruction::{Movh, Movl};
+
+        match instr {
+            Movh => Self::Movh { to, val, cond },
+            Movl => Self::Movl { to, val, cond },
         }
     }
 
-    pub(crate) const fn from_shift_instruction(instr: ASMShiftInstruction, reg: Register, val: W) -> Self {
-        use ASMShiftInstruction::{Shl, Shr};
+    pub(crate) const fn from_shift_instruction(
+        instr: ASMShiftInstruction,
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    ) -> Self {
+        use ASMShiftInstruction::{Shl, ShlS, Shr, ShrS};
 
         match instr {
-            Shl => Self::Shl { reg, val },
-            Shr => Self::Shr { reg, val },
+            Shl => Self::Shl {
+                reg,
+                val,
+                set_flags: false,
+                cond,
+            },
+            ShlS => Self::Shl {
+                reg,
+                val,
+                set_flags: true,
+                cond,
+            },
+            Shr => Self::Shr {
+                reg,
+                val,
+                set_flags: false,
+                cond,
+            },
+            ShrS => Self::Shr {
+                reg,
+                val,
+                set_flags: true,
+                cond,
+            },
         }
     }
 
-    pub(crate) const fn from_rotate_instruction(instr: ASMRotateInstruction, reg: Register, val: u32) -> Self {
-        use ASMRotateInstruction::{Rol, Ror};
+    pub(crate) const fn from_rotate_instruction(
+        instr: ASMRotateInstruction,
+        reg: Register,
+        val: Operand<W>,
+        cond: JumpCondition,
+    ) -> Self {
+        use ASMRotateInstruction::{Rcl, Rcr, Rol, Ror};
 
         match instr {
-            Ror => Self::Ror { reg, val },
-            Rol => Self::Rol { reg, val },
+            Ror => Self::Ror { reg, val, cond },
+            Rol => Self::Rol { reg, val, cond },
+            Rcl => Self::Rcl { reg, val, cond },
+            Rcr => Self::Rcr { reg, val, cond },
         }
     }
 
     pub(crate) const fn from_jump_instruction(instr: ASMJumpInstruction, dest: W) -> Self {
-        use ASMJumpInstruction::{Jc, Jg, Jge, Jl, Jle, Jmp, Jnc, Jns, Jnz, Js, Jz};
-        let condition = match instr {
-            Jmp => JumpCondition::Unconditional,
-            Jz => JumpCondition::Zero,
-            Jnz => JumpCondition::NotZero,
-            Jc => JumpCondition::Carry,
-            Jnc => JumpCondition::NotCarry,
-            Js => JumpCondition::Signed,
-            Jns => JumpCondition::NotSigned,
-            Jg => JumpCondition::Greater,
-            Jl => JumpCondition::Less,
-            Jge => JumpCondition::GreaterOrEq,
-            Jle => JumpCondition::LessOrEq,
-        };
-
-        Self::Jump { to: dest, condition }
+        Self::Jump {
+            to: dest,
+            condition: JumpCondition::from_jump_instruction(instr),
+        }
+    }
+
+    pub(crate) const fn from_rel_jump_instruction(instr: ASMJumpInstruction, offset: W) -> Self {
+        Self::JumpRel {
+            offset,
+            condition: JumpCondition::from_jump_instruction(instr),
+        }
     }
 
     /// Copy a value from an operand to a register.
     #[inline]
-    const fn mov<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn mov<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         to: Register,
         from: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         processor.registers.set_reg(to, from.resolve(processor));
     }
 
+    /// Returns the number of bits in half a word and a mask selecting the low half of a word.
+    #[inline]
+    fn half_word() -> (W, W) {
+        #[allow(clippy::cast_possible_wrap)]
+        let half = W::from((W::BITS / 2) as i32);
+        (half, !(W::from(-1) << half))
+    }
+
+    /// Set the high half of the register to the low half of the value, keeping the low half of the register.
+    #[inline]
+    fn movh<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        to: Register,
+        val: W,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let (half, low_mask) = Self::half_word();
+        let low = processor.registers.get_reg(to) & low_mask;
+
+        processor.registers.set_reg(to, (val << half) | low);
+    }
+
+    /// Set the low half of the register to the low half of the value and clear the high half of the register.
+    #[inline]
+    fn movl<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        to: Register,
+        val: W,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let (_, low_mask) = Self::half_word();
+        processor.registers.set_reg(to, val & low_mask);
+    }
+
     /// Push a value from the operand to the stack.
     #[inline]
-    fn push<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn push<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         from: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        processor.registers.inc(Register::SP);
+        Self::push_value(from, processor);
+    }
+
+    /// Pushes the value of the operand onto the stack.
+    /// Reports a stack overflow and returns `false` if the stack is full.
+    #[inline]
+    fn push_value<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        from: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) -> bool {
+        processor.stack_growth().push(&mut processor.registers);
         let sp = processor.registers.sp();
+        let val = from.resolve(processor);
+
+        if processor.stack.write_region(sp.into(), &[val]).is_err() {
+            // leave the stack pointer as it was
+            processor.stack_growth().pop(&mut processor.registers);
+            Self::stack_overflow(processor);
+            return false;
+        }
 
-        processor.stack.write(sp, from.resolve(processor));
+        true
     }
 
     /// Pop a value from the stack to the register.
     #[inline]
-    fn pop<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn pop<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         to: Register,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        let sp = processor.registers.sp();
-        let val = processor.stack.read(sp);
+        if let Some(val) = Self::pop_value(processor) {
+            processor.registers.set_reg(to, val);
+        }
+    }
+
+    /// Pops the value at the top of the stack.
+    /// Reports a stack underflow and returns `None` if the stack is empty.
+    #[inline]
+    fn pop_value<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) -> Option<W> {
+        if processor.stack_is_empty() {
+            Self::stack_underflow(processor);
+            return None;
+        }
+
+        let val = processor.stack.read(processor.registers.sp());
+        processor.stack_growth().pop(&mut processor.registers);
+        Some(val)
+    }
+
+    /// Returns the address of the instruction that is currently executed.
+    #[inline]
+    fn instruction_address<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        processor: &Processor<STACK_SIZE, Self, P, W, S>,
+    ) -> usize {
+        // the program counter already points to the next instruction
+        Into::<usize>::into(processor.registers.pc()).saturating_sub(1)
+    }
+
+    /// Reports a pop from an empty stack. The stack pointer is not changed.
+    #[inline]
+    fn stack_underflow<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let pc = Self::instruction_address(processor);
+        processor.fault(ProgramError::StackUnderflow { pc });
+    }
+
+    /// Reports a push onto a full stack. The stack pointer is not changed.
+    #[inline]
+    fn stack_overflow<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let pc = Self::instruction_address(processor);
+        processor.fault(ProgramError::StackOverflow { pc });
+    }
+
+    /// Swap the value of the register with the value on the stack at the address specified by the operand.
+    #[inline]
+    fn swp<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        addr: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let addr = addr.resolve_address(processor);
+        let tmp = processor.stack.read(addr);
+
+        processor.stack.write(addr, processor.registers.get_reg(reg));
+        processor.registers.set_reg(reg, tmp);
+    }
+
+    /// Load the value on the stack at the address specified by the operand into the register.
+    #[inline]
+    fn ldr<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        addr: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let addr = addr.resolve_address(processor);
+        processor.registers.set_reg(reg, processor.stack.read(addr));
+    }
+
+    /// Store the value of the register on the stack at the address specified by the operand.
+    #[inline]
+    fn str<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        addr: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let addr = addr.resolve_address(processor);
+        processor.stack.write(addr, processor.registers.get_reg(reg));
+    }
+
+    /// Read a value from the I/O port into the register.
+    /// Faults if no input handler is connected to the port. The register is not changed then.
+    #[inline]
+    fn input<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        to: Register,
+        port: W,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        match processor.read_port(port) {
+            Some(val) => processor.registers.set_reg(to, val),
+            None => Self::unconnected_port(port, processor),
+        }
+    }
+
+    /// Write the value of the operand to the I/O port.
+    /// Faults if no output handler is connected to the port.
+    #[inline]
+    fn output<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        from: Operand<W>,
+        port: W,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let val = from.resolve(processor);
+
+        if processor.write_port(port, val).is_none() {
+            Self::unconnected_port(port, processor);
+        }
+    }
 
-        processor.registers.dec(Register::SP);
-        processor.registers.set_reg(to, val);
+    /// Reports an access to a port without a connected handler.
+    #[inline]
+    fn unconnected_port<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        port: W,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let pc = Self::instruction_address(processor);
+        processor.fault(ProgramError::UnconnectedPort { port: port.into(), pc });
     }
 
     /// Call a subroutine at the program address specified by the operand.
     /// Pushes the current program counter onto the stack and sets the program counter to the address of the subroutine.
     #[inline]
-    fn call<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn call<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         addr: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        Self::push(Operand::Value(processor.registers.pc()), processor);
-        processor.registers.set_reg(Register::PC, addr.resolve(processor));
+        if !Self::push_value(Operand::Value(processor.registers.pc()), processor) {
+            return;
+        }
+        processor.enter_call();
+        processor.jump(addr.resolve(processor));
     }
 
     /// Return from a subroutine.
     /// Pops the return address from the stack and sets the program counter to the popped value.
     #[inline]
-    fn ret<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(processor: &mut Processor<STACK_SIZE, Self, P, W>) {
-        Self::pop(Register::PC, processor);
+    fn ret<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        if !processor.leave_call() {
+            let pc = Self::instruction_address(processor);
+            return processor.fault(ProgramError::ReturnWithoutCall { pc });
+        }
+
+        if let Some(addr) = Self::pop_value(processor) {
+            processor.jump(addr);
+        }
+    }
+
+    /// Pop `b` and then `a` from the stack and push the result of `a op b`.
+    /// This decrements the stack pointer by one.
+    #[inline]
+    fn bin_stack<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        op: StackOp,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        if processor.stack_is_empty() {
+            return Self::stack_underflow(processor);
+        }
+
+        let b = processor.stack.read(processor.registers.sp());
+        processor.stack_growth().pop(&mut processor.registers);
+
+        if processor.stack_is_empty() {
+            // leave the stack as it was
+            processor.stack_growth().push(&mut processor.registers);
+            return Self::stack_underflow(processor);
+        }
+
+        let sp = processor.registers.sp();
+        let a = processor.stack.read(sp);
+
+        processor.stack.write(sp, op.apply(a, b));
     }
 
     /// Set program pointer to value, effectively jumping to the instruction at this point in the program.
     /// The condition is checked before jumping and the jump is performed if the condition is met.
     #[inline]
-    const fn jmp<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn jmp<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         to: W,
         condition: JumpCondition,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        if condition.check(processor) {
+            processor.jump(to);
+        }
+    }
+
+    /// Add the offset to the program counter, which already points to the next instruction.
+    /// The condition is checked before jumping and the jump is performed if the condition is met.
+    #[inline]
+    fn jmp_rel<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        offset: W,
+        condition: JumpCondition,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         if condition.check(processor) {
-            processor.registers.set_reg(Register::PC, to);
+            let pc = processor.registers.get_reg(Register::PC);
+            processor.jump(pc + offset);
+        }
+    }
+
+    /// Set the register to 1 if the condition is met and to 0 otherwise.
+    #[inline]
+    fn set_if<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        condition: JumpCondition,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let val = i32::from(condition.check(processor));
+        processor.registers.set_reg(reg, val.into());
+    }
+
+    /// Copy a value from the operand to the register if the condition is met.
+    #[inline]
+    fn cmov_if<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        to: Register,
+        from: Operand<W>,
+        condition: JumpCondition,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        if condition.check(processor) {
+            let val = from.resolve(processor);
+            processor.registers.set_reg(to, val);
         }
     }
 
     /// Add the value of an operand (rhs) to a register (acc).
     #[inline]
-    fn add<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn add<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(acc);
         let b = rhs.resolve(processor);
 
-        if signed {
+        if set_flags {
             let (result, overflow) = a.overflowing_add(b);
             let carry = a.check_carry_add(b);
 
             processor.registers.set_reg(acc, result);
-            processor.registers.set_flag(Flag::V, overflow);
-            processor.registers.set_flag(Flag::C, carry);
+            processor.update_flag(Flag::V, overflow);
+            processor.update_flag(Flag::C, carry);
 
-            Self::set_signed_zero_flags(result, processor);
+            Self::set_result_flags(result, processor);
         } else {
             processor.registers.set_reg(acc, a + b);
         }
@@ -322,24 +1275,24 @@ impl<W: Word> Instruction<W> {
 
     /// Subtract the value of an operand (rhs) from a register (acc).
     #[inline]
-    fn sub<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn sub<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(acc);
         let b = rhs.resolve(processor);
 
-        if signed {
+        if set_flags {
             let (result, overflow) = a.overflowing_sub(b);
             let carry = a.check_carry_sub(b);
 
             processor.registers.set_reg(acc, result);
-            processor.registers.set_flag(Flag::V, overflow);
-            processor.registers.set_flag(Flag::C, carry);
+            processor.update_flag(Flag::V, overflow);
+            processor.update_flag(Flag::C, carry);
 
-            Self::set_signed_zero_flags(result, processor);
+            Self::set_result_flags(result, processor);
         } else {
             processor.registers.set_reg(acc, a - b);
         }
@@ -348,50 +1301,157 @@ impl<W: Word> Instruction<W> {
     /// Multiply the value of an operand (acc) with the value of a register (rhs).
     /// The result is stored in acc.
     #[inline]
-    fn mul<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn mul<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(acc);
         let b = rhs.resolve(processor);
 
-        if signed {
+        if set_flags {
             let (result, overflow) = a.overflowing_mul(b);
             let carry = a.check_carry_mul(b);
 
             processor.registers.set_reg(acc, result);
-            processor.registers.set_flag(Flag::V, overflow);
-            processor.registers.set_flag(Flag::C, carry);
+            processor.update_flag(Flag::V, overflow);
+            processor.update_flag(Flag::C, carry);
 
-            Self::set_signed_zero_flags(result, processor);
+            Self::set_result_flags(result, processor);
         } else {
             processor.registers.set_reg(acc, a * b);
         }
     }
 
+    /// Multiply the value of a register (acc) with the value of an operand (rhs).
+    /// The lower half of the double-width product is stored in acc and the upper half in dst_hi.
+    #[inline]
+    fn mul_high<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        acc: Register,
+        rhs: Operand<W>,
+        dst_hi: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(acc);
+        let b = rhs.resolve(processor);
+
+        let (low, high) = a.widening_mul(b);
+        processor.registers.set_reg(acc, low);
+        processor.registers.set_reg(dst_hi, high);
+    }
+
+    /// Add the value of an operand (rhs) to a register (acc), clamping the result at the minimum and maximum of the word.
+    /// Sets the overflow flag if the result was clamped.
+    #[inline]
+    fn add_sat<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        acc: Register,
+        rhs: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(acc);
+        let b = rhs.resolve(processor);
+
+        processor.registers.set_reg(acc, a.saturating_add(b));
+        processor.update_flag(Flag::V, a.overflowing_add(b).1);
+    }
+
+    /// Store the smaller of the values of a register (acc) and an operand (rhs) in acc.
+    /// The flags are not affected.
+    #[inline]
+    fn min<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        acc: Register,
+        rhs: Operand<W>,
+        unsigned: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(acc);
+        let b = rhs.resolve(processor);
+
+        if Self::less(b, a, unsigned) {
+            processor.registers.set_reg(acc, b);
+        }
+    }
+
+    /// Store the larger of the values of a register (acc) and an operand (rhs) in acc.
+    /// The flags are not affected.
+    #[inline]
+    fn max<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        acc: Register,
+        rhs: Operand<W>,
+        unsigned: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(acc);
+        let b = rhs.resolve(processor);
+
+        if Self::less(a, b, unsigned) {
+            processor.registers.set_reg(acc, b);
+        }
+    }
+
+    /// Raise a register (reg) to the power of an operand (exp), wrapping around on overflow.
+    /// Sets the overflow flag and leaves the register unchanged if the exponent is negative.
+    #[inline]
+    fn pow<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        exp: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let exp = exp.resolve(processor);
+        let negative = exp.is_negative();
+
+        processor.update_flag(Flag::V, negative);
+        if !negative {
+            let exp = u32::try_from(exp.into()).unwrap_or(u32::MAX);
+            let result = processor.registers.get_reg(reg).wrapping_pow(exp);
+            processor.registers.set_reg(reg, result);
+        }
+    }
+
+    /// Compares two words as signed or unsigned numbers.
+    #[inline]
+    fn less(a: W, b: W, unsigned: bool) -> bool {
+        // subtracting a larger unsigned number borrows
+        if unsigned { a.check_carry_sub(b) } else { a < b }
+    }
+
+    /// Subtract the value of an operand (rhs) from a register (acc), clamping the result at the minimum and maximum of the word.
+    /// Sets the overflow flag if the result was clamped.
+    #[inline]
+    fn sub_sat<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        acc: Register,
+        rhs: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(acc);
+        let b = rhs.resolve(processor);
+
+        processor.registers.set_reg(acc, a.saturating_sub(b));
+        processor.update_flag(Flag::V, a.overflowing_sub(b).1);
+    }
+
     /// Divide the value of an operand (acc) by the value of a register (rhs).
     /// The result is stored in acc.
     #[inline]
-    fn div<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn div<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         acc: Register,
         rhs: Operand<W>,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(acc);
         let b = rhs.resolve(processor);
 
-        if signed {
+        if set_flags {
             let (result, overflow) = a.overflowing_div(b);
             let carry = overflow; // this is the same as a.carry_div(b)
 
             processor.registers.set_reg(acc, result);
-            processor.registers.set_flag(Flag::V, overflow);
-            processor.registers.set_flag(Flag::C, carry);
+            processor.update_flag(Flag::V, overflow);
+            processor.update_flag(Flag::C, carry);
 
-            Self::set_signed_zero_flags(result, processor);
+            Self::set_result_flags(result, processor);
         } else {
             processor.registers.set_reg(acc, a / b);
         }
@@ -399,12 +1459,12 @@ impl<W: Word> Instruction<W> {
 
     /// Increment the value in a register by one.
     #[inline]
-    fn inc<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn inc<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        if signed {
+        if set_flags {
             Self::add(reg, Operand::Value(1.into()), true, processor);
         } else {
             processor.registers.inc(reg);
@@ -413,46 +1473,158 @@ impl<W: Word> Instruction<W> {
 
     /// Decrement the value in a register by one.
     #[inline]
-    fn dec<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn dec<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        signed: bool,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        if signed {
+        if set_flags {
             Self::sub(reg, Operand::Value(1.into()), true, processor);
         } else {
             processor.registers.dec(reg);
         }
     }
 
-    /// Sets the signed and zero flags.
+    /// Replace the value in the register with -1, 0 or 1 according to its sign and set the signed, zero and parity flags.
+    #[inline]
+    fn sgn<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).signum();
+
+        processor.registers.set_reg(reg, result);
+        Self::set_result_flags(result, processor);
+    }
+
+    /// Replace the value in the register with its absolute value.
+    /// Sets the overflow flag if the value is the minimum, whose absolute value cannot be represented.
+    #[inline]
+    fn abs<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).wrapping_abs();
+
+        processor.registers.set_reg(reg, result);
+        processor.update_flag(Flag::V, result.is_negative());
+    }
+
+    /// Sign-extend the low byte of the register to the full word.
+    #[inline]
+    fn sxt<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).sign_extend(8);
+        processor.registers.set_reg(reg, result);
+    }
+
+    /// Zero-extend the low byte of the register to the full word.
+    #[inline]
+    fn zxt<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).zero_extend(8);
+        processor.registers.set_reg(reg, result);
+    }
+
+    /// Reverse the order of the bits in the register and set the zero flag.
+    #[inline]
+    fn brev<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).reverse_bits();
+
+        processor.registers.set_reg(reg, result);
+        processor.update_flag(Flag::Z, result.is_zero());
+    }
+
+    /// Replace the value in the register with the number of its leading zero bits.
+    #[inline]
+    fn clz<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        #[allow(clippy::cast_possible_wrap)]
+        let count = W::from(processor.registers.get_reg(reg).leading_zeros() as i32);
+        processor.registers.set_reg(reg, count);
+    }
+
+    /// Replace the value in the register with the number of its trailing zero bits.
+    #[inline]
+    fn ctz<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        #[allow(clippy::cast_possible_wrap)]
+        let count = W::from(processor.registers.get_reg(reg).trailing_zeros() as i32);
+        processor.registers.set_reg(reg, count);
+    }
+
+    /// Reverse the order of the bytes in the register.
+    #[inline]
+    fn bswap<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let result = processor.registers.get_reg(reg).swap_bytes();
+        processor.registers.set_reg(reg, result);
+    }
+
+    /// Sets the signed, zero and parity flags depending on the result.
     #[inline]
-    fn set_signed_zero_flags<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn set_result_flags<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         val: W,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
-        match val.cmp(&(0.into())) {
-            Ordering::Less => {
-                processor.registers.set_flag(Flag::S, true);
-                processor.registers.set_flag(Flag::Z, false);
-            }
-            Ordering::Equal => {
-                processor.registers.set_flag(Flag::S, false);
-                processor.registers.set_flag(Flag::Z, true);
-            }
-            Ordering::Greater => {
-                processor.registers.set_flag(Flag::S, false);
-                processor.registers.set_flag(Flag::Z, false);
-            }
+        processor.update_flag(Flag::S, val.is_negative());
+        processor.update_flag(Flag::Z, val.is_zero());
+        processor.update_flag(Flag::P, has_even_parity(val));
+    }
+
+    /// Stores the result of a logical or bitwise operation in the register.
+    /// If set_flags is set, the signed, zero and parity flags are set depending on the result.
+    #[inline]
+    fn set_logical_result<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        result: W,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        processor.registers.set_reg(reg, result);
+
+        if set_flags {
+            Self::set_result_flags(result, processor);
+        }
+    }
+
+    /// Stores the result of a bitwise operation in the register.
+    /// If set_flags is set, the signed, zero and parity flags are set depending on the result
+    /// and the carry and overflow flags are cleared, like in x86.
+    #[inline]
+    fn set_bitwise_result<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        result: W,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        Self::set_logical_result(reg, result, set_flags, processor);
+
+        if set_flags {
+            processor.update_flag(Flag::C, false);
+            processor.update_flag(Flag::V, false);
         }
     }
 
     /// Compares two operands and sets the flags accordingly.
     #[inline]
-    fn cmp<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn cmp<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         lhs: Operand<W>,
         rhs: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = lhs.resolve(processor);
         let b = rhs.resolve(processor);
@@ -460,110 +1632,226 @@ impl<W: Word> Instruction<W> {
         let (result, overflow) = a.overflowing_sub(b);
         let carry = a.check_carry_sub(b);
 
-        processor.registers.set_flag(Flag::V, overflow);
-        processor.registers.set_flag(Flag::C, carry);
-        Self::set_signed_zero_flags(result, processor);
+        processor.update_flag(Flag::V, overflow);
+        processor.update_flag(Flag::C, carry);
+        Self::set_result_flags(result, processor);
     }
 
-    /// Perform an xor operation on the value in the register with the value of the operand. (XOR)
+    /// Perform an xor operation on the value in the register with the value of the operand. (XOR\[S\])
     #[inline]
-    fn xor<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn xor<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
         rhs: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
         let b = rhs.resolve(processor);
 
-        processor.registers.set_reg(reg, a ^ b);
+        Self::set_bitwise_result(reg, a ^ b, set_flags, processor);
     }
 
-    /// Perform an and operation on the value in the register with the value of the operand. (AND)
+    /// Perform an and operation on the value in the register with the value of the operand. (AND\[S\])
     #[inline]
-    fn and<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn and<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
         rhs: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
         let b = rhs.resolve(processor);
 
-        processor.registers.set_reg(reg, a & b);
+        Self::set_bitwise_result(reg, a & b, set_flags, processor);
     }
 
-    /// Perform an or operation on the value in the register with the value of the operand. (OR)
+    /// Perform an or operation on the value in the register with the value of the operand. (OR\[S\])
     #[inline]
-    fn or<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn or<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
         rhs: Operand<W>,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
         let b = rhs.resolve(processor);
 
-        processor.registers.set_reg(reg, a | b);
+        Self::set_bitwise_result(reg, a | b, set_flags, processor);
     }
 
-    /// Perform a not operation on the value in the register. (NOT)
+    /// Perform a nand operation on the value in the register with the value of the operand. (NAND\[S\])
     #[inline]
-    fn not<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn nand<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        rhs: Operand<W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
+        let b = rhs.resolve(processor);
 
-        processor.registers.set_reg(reg, !a);
+        Self::set_bitwise_result(reg, !(a & b), set_flags, processor);
     }
 
-    /// Shift the value in the register left by the specified number of bits.
+    /// Perform a nor operation on the value in the register with the value of the operand. (NOR\[S\])
     #[inline]
-    fn shl<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn nor<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        val: W,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        rhs: Operand<W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
-        processor.registers.set_reg(reg, a << val);
+        let b = rhs.resolve(processor);
+
+        Self::set_bitwise_result(reg, !(a | b), set_flags, processor);
     }
 
-    /// Shift the value in the register right by the specified number of bits.
+    /// Perform a xnor operation on the value in the register with the value of the operand. (XNOR\[S\])
     #[inline]
-    fn shr<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn xnor<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        val: W,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        rhs: Operand<W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(reg);
+        let b = rhs.resolve(processor);
+
+        Self::set_bitwise_result(reg, !(a ^ b), set_flags, processor);
+    }
+
+    /// Perform a not operation on the value in the register. (NOT\[S\])
+    #[inline]
+    fn not<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(reg);
+
+        Self::set_bitwise_result(reg, !a, set_flags, processor);
+    }
+
+    /// Shift the value in the register left by the number of bits specified by the operand.
+    #[inline]
+    fn shl<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        val: Operand<W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let a = processor.registers.get_reg(reg);
+        let val = Self::shift_amount(val.resolve(processor));
+        Self::set_logical_result(reg, a << val, set_flags, processor);
+    }
+
+    /// Shift the value in the register right by the number of bits specified by the operand.
+    #[inline]
+    fn shr<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        val: Operand<W>,
+        set_flags: bool,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
-        processor.registers.set_reg(reg, a >> val);
+        let val = Self::shift_amount(val.resolve(processor));
+        Self::set_logical_result(reg, a >> val, set_flags, processor);
+    }
+
+    /// Masks a shift amount to the number of bits of the word, so that shifting by a register never overflows.
+    /// Negative amounts are masked like their two's complement, e.g. -1 shifts a 32-bit word by 31 bits.
+    #[inline]
+    fn shift_amount(val: W) -> W {
+        let val: usize = val.into();
+        W::try_from(val % W::BITS as usize).unwrap_or_default()
+    }
+
+    /// Resolves the number of bits of a rotation and masks it like a [`shift_amount`](Instruction::shift_amount).
+    #[inline]
+    fn rotate_amount<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        val: Operand<W>,
+        processor: &Processor<STACK_SIZE, Self, P, W, S>,
+    ) -> u32 {
+        let val: usize = Self::shift_amount(val.resolve(processor)).into();
+        // the masked amount is less than the number of bits of the word
+        u32::try_from(val).unwrap_or_default()
     }
 
-    /// Rotate the value in the register left by the specified number of bits.
+    /// Rotate the value in the register left by the number of bits specified by the operand.
     #[inline]
-    fn rol<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn rol<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        val: u32,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        val: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
+        let val = Self::rotate_amount(val, processor);
         processor.registers.set_reg(reg, a.rotate_left(val));
     }
 
-    /// Rotate the value in the register right by the specified number of bits.
+    /// Rotate the value in the register right by the number of bits specified by the operand.
     #[inline]
-    fn ror<const STACK_SIZE: usize, P: Deref<Target = [Self]>>(
+    fn ror<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
         reg: Register,
-        val: u32,
-        processor: &mut Processor<STACK_SIZE, Self, P, W>,
+        val: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
     ) {
         let a = processor.registers.get_reg(reg);
+        let val = Self::rotate_amount(val, processor);
         processor.registers.set_reg(reg, a.rotate_right(val));
     }
+
+    /// Rotate the value in the register left through the carry flag by the number of bits specified by the operand.
+    #[inline]
+    fn rcl<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        val: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let mut a = processor.registers.get_reg(reg);
+        let val = Self::rotate_amount(val, processor);
+
+        for _ in 0..val {
+            let carry;
+            (a, carry) = a.rotate_left_through_carry(processor.registers.get_flag(Flag::C));
+            processor.update_flag(Flag::C, carry);
+        }
+
+        processor.registers.set_reg(reg, a);
+    }
+
+    /// Rotate the value in the register right through the carry flag by the number of bits specified by the operand.
+    #[inline]
+    fn rcr<const STACK_SIZE: usize, P: Deref<Target = [Self]>, S: StackMemory<W>>(
+        reg: Register,
+        val: Operand<W>,
+        processor: &mut Processor<STACK_SIZE, Self, P, W, S>,
+    ) {
+        let mut a = processor.registers.get_reg(reg);
+        let val = Self::rotate_amount(val, processor);
+
+        for _ in 0..val {
+            let carry;
+            (a, carry) = a.rotate_right_through_carry(processor.registers.get_flag(Flag::C));
+            processor.update_flag(Flag::C, carry);
+        }
+
+        processor.registers.set_reg(reg, a);
+    }
+}
+
+/// Returns true if the low byte of the value has an even number of set bits, like the parity flag of x86.
+#[inline]
+pub(crate) fn has_even_parity<W: Word>(val: W) -> bool {
+    (Into::<usize>::into(val) & 0xFF).count_ones().is_multiple_of(2)
 }
 
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::parser::ParserError;
     use procem::word::*;
 
     const STACK_SIZE: usize = 32;
@@ -571,6 +1859,170 @@ mod test {
     type P = Vec<IS>;
     type W = I8;
 
+    mod jump_condition {
+        use super::*;
+
+        fn check(condition: JumpCondition, zero: bool, signed: bool, carry: bool) -> bool {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_flag(Flag::Z, zero);
+            processor.registers.set_flag(Flag::S, signed);
+            processor.registers.set_flag(Flag::C, carry);
+            condition.check(&processor)
+        }
+
+        #[test]
+        fn test_check_all_flag_states() {
+            for zero in [false, true] {
+                for signed in [false, true] {
+                    for carry in [false, true] {
+                        let expected = [
+                            (JumpCondition::Unconditional, true),
+                            (JumpCondition::Zero, zero),
+                            (JumpCondition::NotZero, !zero),
+                            (JumpCondition::Carry, carry),
+                            (JumpCondition::NotCarry, !carry),
+                            (JumpCondition::Signed, signed),
+                            (JumpCondition::NotSigned, !signed),
+                            (JumpCondition::Greater, !zero && !signed),
+                            (JumpCondition::Less, !zero && signed),
+                            (JumpCondition::GreaterOrEq, zero || !signed),
+                            (JumpCondition::LessOrEq, zero || signed),
+                        ];
+                        for (condition, result) in expected {
+                            assert_eq!(
+                                check(condition, zero, signed, carry),
+                                result,
+                                "{condition} with Z={zero} S={signed} C={carry}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_check_value() {
+            for val in [-3, 0, 5] {
+                let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+                Instruction::set_result_flags(val.into(), &mut processor);
+
+                for condition in [
+                    JumpCondition::Unconditional,
+                    JumpCondition::Zero,
+                    JumpCondition::NotZero,
+                    JumpCondition::Signed,
+                    JumpCondition::NotSigned,
+                    JumpCondition::Greater,
+                    JumpCondition::Less,
+                    JumpCondition::GreaterOrEq,
+                    JumpCondition::LessOrEq,
+                    JumpCondition::ParityEven,
+                    JumpCondition::ParityOdd,
+                ] {
+                    assert_eq!(
+                        condition.check_value(W::from(val)),
+                        Some(condition.check(&processor)),
+                        "{condition} with {val}"
+                    );
+                }
+            }
+
+            assert_eq!(JumpCondition::Carry.check_value(W::from(1)), None);
+            assert_eq!(JumpCondition::NotCarry.check_value(W::from(1)), None);
+        }
+
+        #[test]
+        fn test_check_parity() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            // 0b0111 has an odd number of set bits
+            Instruction::set_result_flags(0b0111.into(), &mut processor);
+            assert!(!processor.registers.get_flag(Flag::P));
+            assert!(JumpCondition::ParityOdd.check(&processor));
+            assert!(!JumpCondition::ParityEven.check(&processor));
+
+            // zero has no set bits, which is an even number
+            Instruction::set_result_flags(0.into(), &mut processor);
+            assert!(processor.registers.get_flag(Flag::P));
+            assert!(JumpCondition::ParityEven.check(&processor));
+
+            // all bits of -1 are set
+            Instruction::set_result_flags((-1).into(), &mut processor);
+            assert!(processor.registers.get_flag(Flag::P));
+        }
+
+        #[test]
+        fn test_from_str_round_trip() {
+            for condition in JumpCondition::ALL {
+                assert_eq!(condition.to_string().parse(), Ok(condition));
+                assert_eq!(JumpCondition::try_from(condition.suffix()), Ok(condition));
+                assert_eq!(condition.suffix().to_lowercase().parse(), Ok(condition));
+            }
+        }
+
+        #[test]
+        fn test_from_str_unknown() {
+            assert_eq!(
+                "JX".parse::<JumpCondition>(),
+                Err(ParserError::UnknownJumpCondition { cond: "JX".to_string() })
+            );
+        }
+    }
+
+    mod operand {
+        use super::*;
+        use crate::AssemblerError;
+
+        #[test]
+        fn test_from_str_round_trip() {
+            for operand in [
+                Operand::Register(Register::R0),
+                Operand::Register(Register::SP),
+                Operand::Value(5.into()),
+                Operand::Value((-128).into()),
+                Operand::Indexed {
+                    base: Register::R1,
+                    offset: 4.into(),
+                },
+            ] {
+                assert_eq!(operand.to_string().parse::<Operand<W>>(), Ok(operand));
+            }
+        }
+
+        #[test]
+        fn test_from_str_literals() {
+            assert_eq!(Operand::<W>::try_from("#0x0A"), Ok(Operand::Value(10.into())));
+            assert_eq!(Operand::<W>::try_from("#'A'"), Ok(Operand::Value(65.into())));
+            assert_eq!(Operand::<W>::try_from("#true"), Ok(Operand::Value(1.into())));
+            assert_eq!(
+                Operand::<W>::try_from("[r2]"),
+                Ok(Operand::Indexed {
+                    base: Register::R2,
+                    offset: 0.into(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_from_str_invalid() {
+            assert_eq!(
+                "#300".parse::<Operand<W>>(),
+                Err(vec![AssemblerError::Parser(ParserError::LiteralOutOfRange {
+                    literal: "300".to_string(),
+                    word_bits: 8,
+                })])
+            );
+            assert!(matches!(
+                "R0, R1".parse::<Operand<W>>().as_ref().map_err(Vec::as_slice),
+                Err([AssemblerError::Parser(ParserError::InvalidToken { idx: 1, .. })])
+            ));
+            assert_eq!(
+                "".parse::<Operand<W>>(),
+                Err(vec![AssemblerError::Parser(ParserError::EmptyTokenList)])
+            );
+        }
+    }
+
     mod mov {
         use super::*;
 
@@ -578,10 +2030,11 @@ mod test {
         fn test_move_reg() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
             processor.registers.set_reg(Register::R0, 10.into());
-            let _ = IS::execute(
+            IS::execute(
                 Instruction::Mov {
                     from: Operand::Register(Register::R0),
                     to: Register::R1,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
@@ -594,10 +2047,11 @@ mod test {
         #[test]
         fn test_move_val() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            let _ = IS::execute(
+            IS::execute(
                 Instruction::Mov {
                     to: Register::R0,
                     from: Operand::Value(10.into()),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
@@ -605,612 +2059,2503 @@ mod test {
         }
     }
 
-    mod inc {
+    mod half_word {
         use super::*;
 
         #[test]
-        fn test_inc() {
-            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 10.into());
-            let _ = IS::execute(
-                Instruction::Inc {
-                    reg: Register::R0,
-                    signed: false,
+        fn test_movl_movh_i32() {
+            let mut processor = Processor::<STACK_SIZE, Instruction<I32>, Vec<_>, I32>::new();
+            processor.registers.set_reg(Register::R0, (-1).into());
+
+            Instruction::execute(
+                Instruction::Movl {
+                    to: Register::R0,
+                    val: 0x5678.into(),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 11.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 0x5678.into());
+
+            Instruction::execute(
+                Instruction::Movh {
+                    to: Register::R0,
+                    val: 0x1234.into(),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0x1234_5678.into());
         }
 
         #[test]
-        fn test_inc_overflow() {
+        fn test_movl_movh_i8() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MAX.into());
-            let _ = IS::execute(
-                Instruction::Inc {
-                    reg: Register::R0,
-                    signed: false,
+
+            // only the low half of the value is used
+            IS::execute(
+                Instruction::Movl {
+                    to: Register::R0,
+                    val: 0x3A.into(),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 0x0A.into());
+
+            IS::execute(
+                Instruction::Movh {
+                    to: Register::R0,
+                    val: 0x0F.into(),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-6).into());
         }
     }
 
-    mod dec {
+    mod min_max {
         use super::*;
 
-        #[test]
-        fn test_dec() {
+        fn run(instruction: IS, val: i8) -> W {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 10.into());
-            let _ = IS::execute(
-                Instruction::Dec {
-                    reg: Register::R0,
-                    signed: false,
-                },
-                &mut processor,
-            );
-            assert_eq!(processor.registers.get_reg(Register::R0), 9.into());
+            processor.registers.set_reg(Register::R0, val.into());
+            IS::execute(instruction, &mut processor);
+            processor.registers.get_reg(Register::R0)
+        }
+
+        fn min(rhs: i8, unsigned: bool) -> IS {
+            Instruction::Min {
+                acc: Register::R0,
+                rhs: Operand::Value(rhs.into()),
+                unsigned,
+                cond: JumpCondition::Unconditional,
+            }
+        }
+
+        fn max(rhs: i8, unsigned: bool) -> IS {
+            Instruction::Max {
+                acc: Register::R0,
+                rhs: Operand::Value(rhs.into()),
+                unsigned,
+                cond: JumpCondition::Unconditional,
+            }
         }
 
         #[test]
-        fn test_dec_underflow() {
+        fn test_min() {
+            assert_eq!(run(min(3, false), 5), 3.into());
+            assert_eq!(run(min(3, false), -2), (-2).into());
+        }
+
+        #[test]
+        fn test_max() {
+            assert_eq!(run(max(3, false), 5), 5.into());
+            assert_eq!(run(max(3, false), -2), 3.into());
+        }
+
+        #[test]
+        fn test_unsigned() {
+            // -2 is 0xFE as an unsigned number
+            assert_eq!(run(min(3, true), -2), 3.into());
+            assert_eq!(run(max(3, true), -2), (-2).into());
+        }
+    }
+
+    mod pow {
+        use super::*;
+
+        fn pow(val: i8, exp: i8) -> (W, bool) {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MIN.into());
-            let _ = IS::execute(
-                Instruction::Dec {
+            processor.registers.set_reg(Register::R0, val.into());
+            IS::execute(
+                Instruction::Pow {
                     reg: Register::R0,
-                    signed: false,
+                    exp: Operand::Value(exp.into()),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), i8::MAX.into());
+            (
+                processor.registers.get_reg(Register::R0),
+                processor.registers.get_flag(Flag::V),
+            )
+        }
+
+        #[test]
+        fn test_pow() {
+            assert_eq!(pow(3, 4), (81.into(), false));
+            assert_eq!(pow(-2, 3), ((-8).into(), false));
+            assert_eq!(pow(5, 0), (1.into(), false));
+        }
+
+        #[test]
+        fn test_overflow() {
+            // 3^5 = 243 wraps around to 243 - 256
+            assert_eq!(pow(3, 5), ((-13).into(), false));
+            assert_eq!(pow(2, 8), (0.into(), false));
+        }
+
+        #[test]
+        fn test_negative_exponent() {
+            assert_eq!(pow(3, -1), (3.into(), true));
         }
     }
 
-    mod add {
+    mod abs {
         use super::*;
 
-        #[test]
-        fn test_add_reg() {
+        fn abs(val: i8) -> (W, bool) {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 5.into());
-            processor.registers.set_reg(Register::R1, 10.into());
-            let _ = IS::execute(
-                Instruction::Add {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, val.into());
+            IS::execute(
+                Instruction::Abs {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 15.into());
+            (
+                processor.registers.get_reg(Register::R0),
+                processor.registers.get_flag(Flag::V),
+            )
         }
 
         #[test]
-        fn test_add_reg_overflow() {
-            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MAX.into());
-            processor.registers.set_reg(Register::R1, 1.into());
-            let _ = IS::execute(
-                Instruction::Add {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
-                },
-                &mut processor,
-            );
-            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+        fn test_abs() {
+            assert_eq!(abs(42), (42.into(), false));
+            assert_eq!(abs(-42), (42.into(), false));
+            assert_eq!(abs(0), (0.into(), false));
+            assert_eq!(abs(i8::MAX), (i8::MAX.into(), false));
         }
 
         #[test]
-        fn test_add_val() {
+        fn test_abs_min_overflows() {
+            assert_eq!(abs(i8::MIN), (i8::MIN.into(), true));
+        }
+    }
+
+    mod brev {
+        use super::*;
+
+        fn brev(val: i8) -> (W, bool) {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 5.into());
-            let _ = IS::execute(
-                Instruction::Add {
-                    acc: Register::R0,
-                    rhs: Operand::Value(10.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, val.into());
+            IS::execute(
+                Instruction::Brev {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 15.into());
+            (
+                processor.registers.get_reg(Register::R0),
+                processor.registers.get_flag(Flag::Z),
+            )
         }
 
         #[test]
-        fn test_add_val_overflow() {
+        fn test_brev_zero() {
+            assert_eq!(brev(0), (0.into(), true));
+        }
+
+        #[test]
+        fn test_brev_lowest_bit_becomes_sign_bit() {
+            assert_eq!(brev(0x01), (i8::MIN.into(), false));
+        }
+
+        #[test]
+        fn test_brev_palindrome() {
+            assert_eq!(brev(0b0101_1010), (0b0101_1010.into(), false));
+        }
+    }
+
+    mod count_zeros {
+        use super::*;
+
+        fn count_zeros(val: i8) -> (W, W) {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MAX.into());
-            let _ = IS::execute(
-                Instruction::Add {
-                    acc: Register::R0,
-                    rhs: Operand::Value(1.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, val.into());
+            processor.registers.set_reg(Register::R1, val.into());
+            IS::execute(
+                Instruction::Clz {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+            IS::execute(
+                Instruction::Ctz {
+                    reg: Register::R1,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            (
+                processor.registers.get_reg(Register::R0),
+                processor.registers.get_reg(Register::R1),
+            )
+        }
+
+        #[test]
+        fn test_count_zeros_of_zero_is_full_width() {
+            assert_eq!(count_zeros(0), (8.into(), 8.into()));
+        }
+
+        #[test]
+        fn test_count_zeros_of_one() {
+            assert_eq!(count_zeros(1), (7.into(), 0.into()));
+        }
+
+        #[test]
+        fn test_count_zeros_of_min() {
+            assert_eq!(count_zeros(i8::MIN), (0.into(), 7.into()));
         }
     }
 
-    mod sub {
+    mod extend {
         use super::*;
 
         #[test]
-        fn test_sub_reg() {
-            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 5.into());
-            processor.registers.set_reg(Register::R1, 10.into());
-            let _ = IS::execute(
-                Instruction::Sub {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+        fn test_sxt_zxt() {
+            let mut processor = Processor::<STACK_SIZE, Instruction<I32>, Vec<_>, I32>::new();
+
+            processor.registers.set_reg(Register::R0, 0xFF.into());
+            Instruction::execute(
+                Instruction::Sxt {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-5).into());
-        }
+            assert_eq!(processor.registers.get_reg(Register::R0), (-1).into());
 
-        #[test]
-        fn test_sub_reg_overflow() {
-            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MIN.into());
-            processor.registers.set_reg(Register::R1, 1.into());
-            let _ = IS::execute(
-                Instruction::Sub {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            Instruction::execute(
+                Instruction::Zxt {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), i8::MAX.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 255.into());
+
+            // bits above the low byte are ignored
+            processor.registers.set_reg(Register::R1, 0x1234_0042.into());
+            Instruction::execute(
+                Instruction::Sxt {
+                    reg: Register::R1,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R1), 0x42.into());
         }
 
         #[test]
-        fn test_sub_val() {
+        fn test_sxt_i8_is_noop() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 5.into());
-            let _ = IS::execute(
-                Instruction::Sub {
-                    acc: Register::R0,
-                    rhs: Operand::Value(10.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, (-5).into());
+
+            IS::execute(
+                Instruction::Zxt {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
             assert_eq!(processor.registers.get_reg(Register::R0), (-5).into());
         }
+    }
+
+    mod swp {
+        use super::*;
 
         #[test]
-        fn test_sub_val_overflow() {
+        fn test_swp_val() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, (-128).into());
-            let _ = IS::execute(
-                Instruction::Sub {
-                    acc: Register::R0,
-                    rhs: Operand::Value(1.into()),
-                    signed: false,
+            processor.stack.write(3.into(), 42.into());
+            processor.registers.set_reg(Register::R0, 7.into());
+            IS::execute(
+                Instruction::Swp {
+                    reg: Register::R0,
+                    addr: Operand::Value(3.into()),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 127.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 42.into());
+            assert_eq!(processor.stack.read(3.into()), 7.into());
         }
-    }
-
-    mod mul {
-        use super::*;
 
         #[test]
-        fn test_mul_reg() {
+        fn test_swp_reg() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.stack.write(5.into(), (-1).into());
             processor.registers.set_reg(Register::R0, 5.into());
-            processor.registers.set_reg(Register::R1, 10.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            IS::execute(
+                Instruction::Swp {
+                    reg: Register::R0,
+                    addr: Operand::Register(Register::R0),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 50.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), (-1).into());
+            assert_eq!(processor.stack.read(5.into()), 5.into());
+        }
+    }
 
-            processor.registers.set_reg(Register::R0, (-5).into());
-            processor.registers.set_reg(Register::R1, 10.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+    mod ldr_str {
+        use super::*;
+
+        #[test]
+        fn test_ldr_indexed() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.stack.write(6.into(), 42.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            IS::execute(
+                Instruction::Ldr {
+                    reg: Register::R0,
+                    addr: Operand::Indexed {
+                        base: Register::R1,
+                        offset: 4.into(),
+                    },
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-50).into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 42.into());
         }
 
         #[test]
-        fn test_mul_reg_overflow() {
+        fn test_str_indexed() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 80.into());
+            processor.registers.set_reg(Register::R0, 42.into());
             processor.registers.set_reg(Register::R1, 2.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            IS::execute(
+                Instruction::Str {
+                    reg: Register::R0,
+                    addr: Operand::Indexed {
+                        base: Register::R1,
+                        offset: 4.into(),
+                    },
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-96).into());
+            assert_eq!(processor.stack.read(6.into()), 42.into());
         }
 
         #[test]
-        fn test_mul_reg_underflow() {
+        fn test_indexed_operand_resolves_to_value() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, (-80).into());
-            processor.registers.set_reg(Register::R1, 2.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            processor.stack.write(3.into(), 9.into());
+            processor.registers.set_reg(Register::R1, 3.into());
+            IS::execute(
+                Instruction::Mov {
+                    to: Register::R0,
+                    from: Operand::Indexed {
+                        base: Register::R1,
+                        offset: 0.into(),
+                    },
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 96.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 9.into());
         }
+    }
+
+    mod ports {
+        use super::*;
 
         #[test]
-        fn test_mul_val() {
+        fn test_in() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 5.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Value(10.into()),
-                    signed: false,
+            processor.connect_input(3.into(), |_| 42.into());
+            IS::execute(
+                Instruction::In {
+                    to: Register::R0,
+                    port: 3.into(),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 50.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 42.into());
+        }
 
-            processor.registers.set_reg(Register::R0, (-5).into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Value(10.into()),
-                    signed: false,
+        #[test]
+        fn test_out() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            // the handler stores the written value in a register to observe it
+            processor.connect_output(3.into(), |processor, val| {
+                processor.registers.set_reg(Register::R1, val)
+            });
+            IS::execute(
+                Instruction::Out {
+                    from: Operand::Value(42.into()),
+                    port: 3.into(),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-50).into());
+            assert_eq!(processor.registers.get_reg(Register::R1), 42.into());
         }
+    }
+
+    mod inc {
+        use super::*;
 
         #[test]
-        fn test_mul_val_overflow() {
+        fn test_inc() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 80.into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Value(2.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, 10.into());
+            IS::execute(
+                Instruction::Inc {
+                    reg: Register::R0,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-96).into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 11.into());
         }
 
         #[test]
-        fn test_mul_val_underflow() {
+        fn test_inc_overflow() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, (-80).into());
-            let _ = IS::execute(
-                Instruction::Mul {
-                    acc: Register::R0,
-                    rhs: Operand::Value(2.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, i8::MAX.into());
+            IS::execute(
+                Instruction::Inc {
+                    reg: Register::R0,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 96.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
         }
     }
 
-    mod div {
+    mod bin_stack {
         use super::*;
 
         #[test]
-        fn test_div_reg() {
+        fn test_bin_stack_sub() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 10.into());
-            processor.registers.set_reg(Register::R1, 5.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            IS::execute(
+                Instruction::Push {
+                    from: Operand::Value(6.into()),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
-
-            processor.registers.set_reg(Register::R0, (-10).into());
-            processor.registers.set_reg(Register::R1, 5.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            IS::execute(
+                Instruction::Push {
+                    from: Operand::Value(3.into()),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-2).into());
+            let sp = processor.registers.sp();
+            IS::execute(
+                Instruction::BinStack {
+                    op: StackOp::Sub,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.sp(), sp - 1.into());
+            assert_eq!(processor.stack.read(processor.registers.sp()), 3.into());
         }
 
         #[test]
-        fn test_div_reg_truncate() {
+        fn test_bin_stack_div() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 3.into());
-            processor.registers.set_reg(Register::R1, 2.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            processor.stack.write(1.into(), 7.into());
+            processor.stack.write(2.into(), 2.into());
+            processor.registers.set_reg(Register::SP, 2.into());
+            IS::execute(
+                Instruction::BinStack {
+                    op: StackOp::Div,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+            assert_eq!(processor.registers.sp(), 1.into());
+            assert_eq!(processor.stack.read(1.into()), 3.into());
         }
+    }
 
-        #[test]
-        fn test_div_reg_overflow() {
+    mod sgn {
+        use super::*;
+
+        #[test]
+        fn test_sgn_positive() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, i8::MIN.into());
-            processor.registers.set_reg(Register::R1, (-1).into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Register(Register::R1),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, 42.into());
+            IS::execute(
+                Instruction::Sgn {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (i8::MIN).into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::Z));
         }
 
         #[test]
-        fn test_div_val() {
+        fn test_sgn_negative() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 10.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Value(5.into()),
-                    signed: false,
-                },
-                &mut processor,
-            );
-            assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
-
-            processor.registers.set_reg(Register::R0, (-10).into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Value(5.into()),
-                    signed: false,
+            processor.registers.set_reg(Register::R0, i8::MIN.into());
+            IS::execute(
+                Instruction::Sgn {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (-2).into());
+            assert_eq!(processor.registers.get_reg(Register::R0), (-1).into());
+            assert!(processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::Z));
         }
 
         #[test]
-        fn test_div_val_truncate() {
+        fn test_sgn_zero() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            processor.registers.set_reg(Register::R0, 3.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Value(4.into()),
-                    signed: false,
+            IS::execute(
+                Instruction::Sgn {
+                    reg: Register::R0,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
             assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(processor.registers.get_flag(Flag::Z));
+        }
+    }
 
-            processor.registers.set_reg(Register::R0, 3.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Value(2.into()),
-                    signed: false,
+    mod dec {
+        use super::*;
+
+        #[test]
+        fn test_dec() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 10.into());
+            IS::execute(
+                Instruction::Dec {
+                    reg: Register::R0,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 9.into());
         }
 
         #[test]
-        fn test_div_val_overflow() {
+        fn test_dec_underflow() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
             processor.registers.set_reg(Register::R0, i8::MIN.into());
-            let _ = IS::execute(
-                Instruction::Div {
-                    acc: Register::R0,
-                    rhs: Operand::Value((-1).into()),
-                    signed: false,
+            IS::execute(
+                Instruction::Dec {
+                    reg: Register::R0,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::R0), (i8::MIN).into());
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MAX.into());
         }
     }
 
-    mod jmp {
+    mod add {
         use super::*;
 
         #[test]
-        fn test_jmp() {
+        fn test_add_reg() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
-            let _ = IS::execute(
-                Instruction::Jump {
-                    to: 2.into(),
-                    condition: JumpCondition::Unconditional,
+            processor.registers.set_reg(Register::R0, 5.into());
+            processor.registers.set_reg(Register::R1, 10.into());
+            IS::execute(
+                Instruction::Add {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::PC), 2.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 15.into());
         }
 
         #[test]
-        fn test_jmp_overflow() {
+        fn test_add_reg_overflow() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
-            let _ = IS::execute(
-                Instruction::Jump {
-                    to: i8::MAX.into(),
-                    condition: JumpCondition::Unconditional,
+            processor.registers.set_reg(Register::R0, i8::MAX.into());
+            processor.registers.set_reg(Register::R1, 1.into());
+            IS::execute(
+                Instruction::Add {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::PC), i8::MAX.into());
-            let _ = IS::execute(
-                Instruction::Inc {
-                    reg: Register::PC,
-                    signed: false,
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+        }
+
+        #[test]
+        fn test_add_val() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 5.into());
+            IS::execute(
+                Instruction::Add {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::PC), i8::MIN.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), 15.into());
         }
 
         #[test]
-        fn test_jmp_underflow() {
+        fn test_add_val_overflow() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
-            let _ = IS::execute(
-                Instruction::Jump {
-                    to: i8::MIN.into(),
-                    condition: JumpCondition::Unconditional,
+            processor.registers.set_reg(Register::R0, i8::MAX.into());
+            IS::execute(
+                Instruction::Add {
+                    acc: Register::R0,
+                    rhs: Operand::Value(1.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::PC), i8::MIN.into());
-            let _ = IS::execute(
-                Instruction::Dec {
-                    reg: Register::PC,
-                    signed: false,
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+        }
+
+        #[test]
+        fn test_only_adds_sets_flags() {
+            let execute_add = |set_flags| {
+                let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+                processor.registers.set_reg(Register::R0, i8::MAX.into());
+                IS::execute(
+                    Instruction::Add {
+                        acc: Register::R0,
+                        rhs: Operand::Value(1.into()),
+                        set_flags,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    &mut processor,
+                );
+                processor
+            };
+
+            // both wrap around like two's complement words
+            let add = execute_add(false);
+            let adds = execute_add(true);
+            assert_eq!(add.registers.get_reg(Register::R0), i8::MIN.into());
+            assert_eq!(adds.registers.get_reg(Register::R0), i8::MIN.into());
+
+            assert_eq!(add.registers.flags(), FlagSet::default());
+            assert!(adds.registers.get_flag(Flag::V));
+            assert!(adds.registers.get_flag(Flag::S));
+            assert!(!adds.registers.get_flag(Flag::Z));
+        }
+    }
+
+    mod add_narrow {
+        use super::*;
+
+        #[test]
+        fn test_adds_i4_overflow() {
+            let mut processor = Processor::<STACK_SIZE, Instruction<I4>, Vec<Instruction<I4>>, I4>::new();
+            processor.registers.set_reg(Register::R0, I4::MAX);
+            Instruction::execute(
+                Instruction::Add {
+                    acc: Register::R0,
+                    rhs: Operand::Value(1.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_reg(Register::PC), i8::MAX.into());
+            assert_eq!(processor.registers.get_reg(Register::R0), I4::MIN);
+            assert!(processor.registers.get_flag(Flag::V));
+            assert!(processor.registers.get_flag(Flag::S));
         }
     }
 
-    mod cmp {
+    mod sub {
         use super::*;
 
         #[test]
-        fn test_cmp_eq_reg() {
+        fn test_sub_reg() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 5.into());
+            processor.registers.set_reg(Register::R1, 10.into());
+            IS::execute(
+                Instruction::Sub {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-5).into());
+        }
 
-            processor.registers.set_reg(Register::R0, 1.into());
+        #[test]
+        fn test_sub_reg_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, i8::MIN.into());
             processor.registers.set_reg(Register::R1, 1.into());
-
-            let _ = IS::execute(
-                Instruction::Cmp {
-                    lhs: Operand::Register(Register::R0),
+            IS::execute(
+                Instruction::Sub {
+                    acc: Register::R0,
                     rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_flag(Flag::C), false);
-            assert_eq!(processor.registers.get_flag(Flag::S), false);
-            assert_eq!(processor.registers.get_flag(Flag::V), false);
-            assert_eq!(processor.registers.get_flag(Flag::Z), true);
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MAX.into());
         }
 
         #[test]
-        fn test_cmp_eq_reg_val() {
+        fn test_sub_val() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 5.into());
+            IS::execute(
+                Instruction::Sub {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-5).into());
+        }
 
-            processor.registers.set_reg(Register::R0, 1.into());
-
-            let _ = IS::execute(
-                Instruction::Cmp {
-                    lhs: Operand::Register(Register::R0),
+        #[test]
+        fn test_sub_val_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-128).into());
+            IS::execute(
+                Instruction::Sub {
+                    acc: Register::R0,
                     rhs: Operand::Value(1.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_flag(Flag::C), false);
-            assert_eq!(processor.registers.get_flag(Flag::S), false);
-            assert_eq!(processor.registers.get_flag(Flag::V), false);
-            assert_eq!(processor.registers.get_flag(Flag::Z), true);
+            assert_eq!(processor.registers.get_reg(Register::R0), 127.into());
         }
+    }
+
+    mod mul {
+        use super::*;
 
         #[test]
-        fn test_cmp_eq_val() {
+        fn test_mul_reg() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 5.into());
+            processor.registers.set_reg(Register::R1, 10.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 50.into());
 
-            let _ = IS::execute(
-                Instruction::Cmp {
-                    lhs: Operand::Value(1.into()),
-                    rhs: Operand::Value(1.into()),
+            processor.registers.set_reg(Register::R0, (-5).into());
+            processor.registers.set_reg(Register::R1, 10.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_flag(Flag::C), false);
-            assert_eq!(processor.registers.get_flag(Flag::S), false);
-            assert_eq!(processor.registers.get_flag(Flag::V), false);
-            assert_eq!(processor.registers.get_flag(Flag::Z), true);
+            assert_eq!(processor.registers.get_reg(Register::R0), (-50).into());
         }
 
         #[test]
-        fn test_cmp_less() {
+        fn test_mul_reg_overflow() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
-
-            processor.registers.set_reg(Register::R0, 1.into());
+            processor.registers.set_reg(Register::R0, 80.into());
             processor.registers.set_reg(Register::R1, 2.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-96).into());
+        }
 
-            let _ = IS::execute(
-                Instruction::Cmp {
-                    lhs: Operand::Register(Register::R0),
+        #[test]
+        fn test_mul_reg_underflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-80).into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
                     rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_flag(Flag::C), true);
-            assert_eq!(processor.registers.get_flag(Flag::S), true);
-            assert_eq!(processor.registers.get_flag(Flag::V), false);
-            assert_eq!(processor.registers.get_flag(Flag::Z), false);
+            assert_eq!(processor.registers.get_reg(Register::R0), 96.into());
         }
 
         #[test]
-        fn test_cmp_greater() {
+        fn test_mul_val() {
             let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 5.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 50.into());
 
-            processor.registers.set_reg(Register::R0, 2.into());
-            processor.registers.set_reg(Register::R1, 1.into());
+            processor.registers.set_reg(Register::R0, (-5).into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-50).into());
+        }
 
-            let _ = IS::execute(
-                Instruction::Cmp {
-                    lhs: Operand::Register(Register::R0),
-                    rhs: Operand::Register(Register::R1),
+        #[test]
+        fn test_mul_val_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 80.into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Value(2.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-96).into());
+        }
+
+        #[test]
+        fn test_mul_val_underflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-80).into());
+            IS::execute(
+                Instruction::Mul {
+                    acc: Register::R0,
+                    rhs: Operand::Value(2.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 96.into());
+        }
+    }
+
+    mod saturating {
+        use super::*;
+
+        #[test]
+        fn test_add_sat_clamps() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, i8::MAX.into());
+            IS::execute(
+                Instruction::AddSat {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MAX.into());
+            assert!(processor.registers.get_flag(Flag::V));
+        }
+
+        #[test]
+        fn test_add_sat_in_range() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 100.into());
+            processor.registers.set_flag(Flag::V, true);
+            IS::execute(
+                Instruction::AddSat {
+                    acc: Register::R0,
+                    rhs: Operand::Value(10.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 110.into());
+            assert!(!processor.registers.get_flag(Flag::V));
+        }
+
+        #[test]
+        fn test_sub_sat_clamps() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-100).into());
+            IS::execute(
+                Instruction::SubSat {
+                    acc: Register::R0,
+                    rhs: Operand::Value(100.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+            assert!(processor.registers.get_flag(Flag::V));
+        }
+    }
+
+    mod mul_high {
+        use super::*;
+
+        #[test]
+        fn test_mul_high() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0x40.into());
+            IS::execute(
+                Instruction::MulHigh {
+                    acc: Register::R0,
+                    rhs: Operand::Value(0x04.into()),
+                    dst_hi: Register::R1,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            // 0x40 * 0x04 = 0x0100
+            assert_eq!(processor.registers.get_reg(Register::R0), 0x00.into());
+            assert_eq!(processor.registers.get_reg(Register::R1), 0x01.into());
+        }
+
+        #[test]
+        fn test_mul_high_negative() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-2).into());
+            processor.registers.set_reg(Register::R1, 3.into());
+            IS::execute(
+                Instruction::MulHigh {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    dst_hi: Register::R2,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-6).into());
+            assert_eq!(processor.registers.get_reg(Register::R2), (-1).into());
+        }
+
+        #[test]
+        fn test_mul_high_same_register() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0x40.into());
+            IS::execute(
+                Instruction::MulHigh {
+                    acc: Register::R0,
+                    rhs: Operand::Value(0x40.into()),
+                    dst_hi: Register::R0,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0x10.into());
+        }
+    }
+
+    mod div {
+        use super::*;
+
+        #[test]
+        fn test_div_reg() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 10.into());
+            processor.registers.set_reg(Register::R1, 5.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+
+            processor.registers.set_reg(Register::R0, (-10).into());
+            processor.registers.set_reg(Register::R1, 5.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-2).into());
+        }
+
+        #[test]
+        fn test_div_reg_truncate() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 3.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+        }
+
+        #[test]
+        fn test_div_reg_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, i8::MIN.into());
+            processor.registers.set_reg(Register::R1, (-1).into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Register(Register::R1),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (i8::MIN).into());
+        }
+
+        #[test]
+        fn test_div_val() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 10.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Value(5.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+
+            processor.registers.set_reg(Register::R0, (-10).into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Value(5.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-2).into());
+        }
+
+        #[test]
+        fn test_div_val_truncate() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 3.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Value(4.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+
+            processor.registers.set_reg(Register::R0, 3.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Value(2.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+        }
+
+        #[test]
+        fn test_div_val_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, i8::MIN.into());
+            IS::execute(
+                Instruction::Div {
+                    acc: Register::R0,
+                    rhs: Operand::Value((-1).into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (i8::MIN).into());
+        }
+    }
+
+    mod jmp {
+        use super::*;
+
+        #[test]
+        fn test_jmp() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
+            IS::execute(
+                Instruction::Jump {
+                    to: 2.into(),
+                    condition: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), 2.into());
+        }
+
+        #[test]
+        fn test_jmp_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
+            IS::execute(
+                Instruction::Jump {
+                    to: i8::MAX.into(),
+                    condition: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), i8::MAX.into());
+            IS::execute(
+                Instruction::Inc {
+                    reg: Register::PC,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), i8::MIN.into());
+        }
+
+        #[test]
+        fn test_jmp_underflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            assert_eq!(processor.registers.get_reg(Register::PC), 0.into());
+            IS::execute(
+                Instruction::Jump {
+                    to: i8::MIN.into(),
+                    condition: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), i8::MIN.into());
+            IS::execute(
+                Instruction::Dec {
+                    reg: Register::PC,
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), i8::MAX.into());
+        }
+
+        #[test]
+        fn test_jmp_rel() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::PC, 5.into());
+            IS::execute(
+                Instruction::JumpRel {
+                    offset: (-3).into(),
+                    condition: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), 2.into());
+
+            // the zero flag is not set, so the jump is not taken
+            IS::execute(
+                Instruction::JumpRel {
+                    offset: 4.into(),
+                    condition: JumpCondition::Zero,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::PC), 2.into());
+        }
+
+        #[test]
+        fn test_validate_jump_target_out_of_bounds() {
+            use procem::program::{Program, ProgramValidationError};
+
+            let program = Program::<IS, P, W>::new(vec![
+                Instruction::Nop,
+                Instruction::Jump {
+                    to: 100.into(),
+                    condition: JumpCondition::Unconditional,
+                },
+                Instruction::Call {
+                    addr: Operand::Value(3.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+            ]);
+
+            assert_eq!(
+                program.validate(),
+                Err(vec![ProgramValidationError::JumpTargetOutOfBounds {
+                    idx: 1,
+                    target: 100,
+                    program_len: 3,
+                }])
+            );
+        }
+    }
+
+    mod cmp {
+        use super::*;
+
+        #[test]
+        fn test_cmp_eq_reg() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            processor.registers.set_reg(Register::R0, 1.into());
+            processor.registers.set_reg(Register::R1, 1.into());
+
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert!(!processor.registers.get_flag(Flag::C));
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_cmp_eq_reg_val() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            processor.registers.set_reg(Register::R0, 1.into());
+
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Value(1.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert!(!processor.registers.get_flag(Flag::C));
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_cmp_eq_val() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Value(1.into()),
+                    rhs: Operand::Value(1.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert!(!processor.registers.get_flag(Flag::C));
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_cmp_less() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            processor.registers.set_reg(Register::R0, 1.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert!(processor.registers.get_flag(Flag::C));
+            assert!(processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(!processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_cmp_greater() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            processor.registers.set_reg(Register::R0, 2.into());
+            processor.registers.set_reg(Register::R1, 1.into());
+
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert!(!processor.registers.get_flag(Flag::C));
+            assert!(!processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(!processor.registers.get_flag(Flag::Z));
+        }
+    }
+
+    mod display {
+        use super::*;
+
+        #[test]
+        fn test_display() {
+            let instruction: IS = Instruction::Mov {
+                to: Register::R0,
+                from: Operand::Value((-10).into()),
+                cond: JumpCondition::Unconditional,
+            };
+            assert_eq!(instruction.to_string(), "MOV R0, #-10");
+
+            let instruction: IS = Instruction::Add {
+                acc: Register::R1,
+                rhs: Operand::Register(Register::SP),
+                set_flags: true,
+                cond: JumpCondition::Unconditional,
+            };
+            assert_eq!(instruction.to_string(), "ADDS R1, SP");
+
+            let instruction: IS = Instruction::Jump {
+                to: 3.into(),
+                condition: JumpCondition::NotZero,
+            };
+            assert_eq!(instruction.to_string(), "JNZ #3");
+
+            assert_eq!(
+                IS::Ret {
+                    cond: JumpCondition::Unconditional
+                }
+                .to_string(),
+                "RET"
+            );
+        }
+    }
+
+    mod mnemonic {
+        use super::*;
+
+        /// One instruction of every mnemonic with its expected mnemonic.
+        pub(super) fn cases() -> [(IS, &'static str); 65] {
+            let reg = Register::R0;
+            let rhs = Operand::Value(1.into());
+            let val = 1.into();
+
+            [
+                (Instruction::Nop, "NOP"),
+                (
+                    Instruction::Movh {
+                        to: reg,
+                        val: 1.into(),
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MOVH",
+                ),
+                (
+                    Instruction::Movl {
+                        to: reg,
+                        val: 1.into(),
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MOVL",
+                ),
+                (
+                    Instruction::Mov {
+                        to: reg,
+                        from: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MOV",
+                ),
+                (
+                    Instruction::Push {
+                        from: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "PUSH",
+                ),
+                (
+                    Instruction::Pop {
+                        to: reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "POP",
+                ),
+                (
+                    Instruction::Swp {
+                        reg,
+                        addr: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SWP",
+                ),
+                (
+                    Instruction::Ldr {
+                        reg,
+                        addr: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "LDR",
+                ),
+                (
+                    Instruction::Str {
+                        reg,
+                        addr: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "STR",
+                ),
+                (
+                    Instruction::In {
+                        to: reg,
+                        port: val,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "IN",
+                ),
+                (
+                    Instruction::Out {
+                        from: rhs,
+                        port: val,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "OUT",
+                ),
+                (
+                    Instruction::Call {
+                        addr: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "CALL",
+                ),
+                (
+                    Instruction::Ret {
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "RET",
+                ),
+                (
+                    Instruction::BinStack {
+                        op: StackOp::Add,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SADD",
+                ),
+                (
+                    Instruction::BinStack {
+                        op: StackOp::Sub,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SSUB",
+                ),
+                (
+                    Instruction::BinStack {
+                        op: StackOp::Mul,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SMUL",
+                ),
+                (
+                    Instruction::BinStack {
+                        op: StackOp::Div,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SDIV",
+                ),
+                (
+                    Instruction::Add {
+                        acc: reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ADD",
+                ),
+                (
+                    Instruction::Add {
+                        acc: reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ADDS",
+                ),
+                (
+                    Instruction::Sub {
+                        acc: reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SUB",
+                ),
+                (
+                    Instruction::Sub {
+                        acc: reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SUBS",
+                ),
+                (
+                    Instruction::Mul {
+                        acc: reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MUL",
+                ),
+                (
+                    Instruction::Mul {
+                        acc: reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MULS",
+                ),
+                (
+                    Instruction::MulHigh {
+                        acc: reg,
+                        rhs,
+                        dst_hi: reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MULH",
+                ),
+                (
+                    Instruction::AddSat {
+                        acc: reg,
+                        rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ADDSAT",
+                ),
+                (
+                    Instruction::SubSat {
+                        acc: reg,
+                        rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SUBSAT",
+                ),
+                (
+                    Instruction::Div {
+                        acc: reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "DIV",
+                ),
+                (
+                    Instruction::Div {
+                        acc: reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "DIVS",
+                ),
+                (
+                    Instruction::Inc {
+                        reg,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "INC",
+                ),
+                (
+                    Instruction::Inc {
+                        reg,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "INCS",
+                ),
+                (
+                    Instruction::Dec {
+                        reg,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "DEC",
+                ),
+                (
+                    Instruction::Dec {
+                        reg,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "DECS",
+                ),
+                (
+                    Instruction::Sgn {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SGN",
+                ),
+                (
+                    Instruction::Min {
+                        acc: reg,
+                        rhs,
+                        unsigned: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MIN",
+                ),
+                (
+                    Instruction::Max {
+                        acc: reg,
+                        rhs,
+                        unsigned: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "MAXU",
+                ),
+                (
+                    Instruction::Pow {
+                        reg,
+                        exp: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "POW",
+                ),
+                (
+                    Instruction::Abs {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ABS",
+                ),
+                (
+                    Instruction::Sxt {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SXT",
+                ),
+                (
+                    Instruction::Zxt {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ZXT",
+                ),
+                (
+                    Instruction::Brev {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "BREV",
+                ),
+                (
+                    Instruction::Clz {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "CLZ",
+                ),
+                (
+                    Instruction::Ctz {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "CTZ",
+                ),
+                (
+                    Instruction::Bswap {
+                        reg,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "BSWAP",
+                ),
+                (
+                    Instruction::Jump {
+                        to: val,
+                        condition: JumpCondition::Unconditional,
+                    },
+                    "JMP",
+                ),
+                (
+                    Instruction::Jump {
+                        to: val,
+                        condition: JumpCondition::GreaterOrEq,
+                    },
+                    "JGE",
+                ),
+                (
+                    Instruction::JumpRel {
+                        offset: val,
+                        condition: JumpCondition::Unconditional,
+                    },
+                    "JR",
+                ),
+                (
+                    Instruction::JumpRel {
+                        offset: val,
+                        condition: JumpCondition::NotZero,
+                    },
+                    "JRNZ",
+                ),
+                (
+                    Instruction::SetIf {
+                        reg,
+                        condition: JumpCondition::NotZero,
+                    },
+                    "SETNZ",
+                ),
+                (
+                    Instruction::CmovIf {
+                        to: reg,
+                        from: rhs,
+                        condition: JumpCondition::Less,
+                    },
+                    "CMOVL",
+                ),
+                (
+                    Instruction::Cmp {
+                        lhs: Operand::Register(reg),
+                        rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "CMP",
+                ),
+                (
+                    Instruction::Xor {
+                        reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "XOR",
+                ),
+                (
+                    Instruction::And {
+                        reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ANDS",
+                ),
+                (
+                    Instruction::Nand {
+                        reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "NANDS",
+                ),
+                (
+                    Instruction::Nor {
+                        reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "NOR",
+                ),
+                (
+                    Instruction::Xnor {
+                        reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "XNOR",
+                ),
+                (
+                    Instruction::Or {
+                        reg,
+                        rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "OR",
+                ),
+                (
+                    Instruction::Not {
+                        reg,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "NOTS",
+                ),
+                (
+                    Instruction::Shl {
+                        reg,
+                        val: rhs,
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SHL",
+                ),
+                (
+                    Instruction::Shr {
+                        reg,
+                        val: rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "SHRS",
+                ),
+                (
+                    Instruction::Rol {
+                        reg,
+                        val: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ROL",
+                ),
+                (
+                    Instruction::Ror {
+                        reg,
+                        val: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ROR",
+                ),
+                (
+                    Instruction::Rcl {
+                        reg,
+                        val: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "RCL",
+                ),
+                (
+                    Instruction::Rcr {
+                        reg,
+                        val: rhs,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "RCR",
+                ),
+                (
+                    Instruction::Xor {
+                        reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "XORS",
+                ),
+                (
+                    Instruction::Or {
+                        reg,
+                        rhs,
+                        set_flags: true,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    "ORS",
+                ),
+            ]
+        }
+
+        #[test]
+        fn test_mnemonic() {
+            for (instruction, mnemonic) in cases() {
+                assert_eq!(instruction.mnemonic(), mnemonic);
+                assert!(instruction.to_string().starts_with(mnemonic));
+            }
+        }
+    }
+
+    mod logical {
+        use super::*;
+
+        #[test]
+        fn test_ands_sets_zero() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 10.into());
+            IS::execute(
+                Instruction::And {
+                    reg: Register::R0,
+                    rhs: Operand::Value(0.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+            assert!(processor.registers.get_flag(Flag::Z));
+            assert!(!processor.registers.get_flag(Flag::S));
+        }
+
+        #[test]
+        fn test_ands_clears_carry_and_overflow() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_flag(Flag::C, true);
+            processor.registers.set_flag(Flag::V, true);
+            processor.registers.set_reg(Register::R0, 0b0110.into());
+            IS::execute(
+                Instruction::And {
+                    reg: Register::R0,
+                    rhs: Operand::Value(0b0011.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0b0010.into());
+            assert!(!processor.registers.get_flag(Flag::C));
+            assert!(!processor.registers.get_flag(Flag::V));
+            assert!(!processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_and_preserves_flags() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 10.into());
+            IS::execute(
+                Instruction::And {
+                    reg: Register::R0,
+                    rhs: Operand::Value(0.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+            assert!(!processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_negated_truth_tables() {
+            // every combination of bits: a = 0b0011, b = 0b0101
+            let a = 0b0011;
+            let b = 0b0101;
+            let cases = [
+                (
+                    Instruction::Nand {
+                        reg: Register::R0,
+                        rhs: Operand::Value(b.into()),
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    !(a & b),
+                ),
+                (
+                    Instruction::Nor {
+                        reg: Register::R0,
+                        rhs: Operand::Value(b.into()),
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    !(a | b),
+                ),
+                (
+                    Instruction::Xnor {
+                        reg: Register::R0,
+                        rhs: Operand::Value(b.into()),
+                        set_flags: false,
+                        cond: JumpCondition::Unconditional,
+                    },
+                    !(a ^ b),
+                ),
+            ];
+
+            for (instruction, expected) in cases {
+                let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+                processor.registers.set_reg(Register::R0, a.into());
+                IS::execute(instruction, &mut processor);
+                assert_eq!(
+                    processor.registers.get_reg(Register::R0),
+                    expected.into(),
+                    "{instruction}"
+                );
+            }
+        }
+
+        #[test]
+        fn test_negated_flags() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (-1).into());
+            IS::execute(
+                Instruction::Nand {
+                    reg: Register::R0,
+                    rhs: Operand::Value((-1).into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+            assert!(processor.registers.get_flag(Flag::Z));
+            assert!(!processor.registers.get_flag(Flag::S));
+
+            IS::execute(
+                Instruction::Nor {
+                    reg: Register::R0,
+                    rhs: Operand::Value(1.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-2).into());
+            assert!(!processor.registers.get_flag(Flag::Z));
+            assert!(processor.registers.get_flag(Flag::S));
+
+            // without the S suffix the flags are not affected
+            IS::execute(
+                Instruction::Xnor {
+                    reg: Register::R0,
+                    rhs: Operand::Value(3.into()),
+                    set_flags: false,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+            assert!(processor.registers.get_flag(Flag::S));
+        }
+
+        #[test]
+        fn test_nots_sets_signed() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            IS::execute(
+                Instruction::Not {
+                    reg: Register::R0,
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (-1).into());
+            assert!(processor.registers.get_flag(Flag::S));
+            assert!(!processor.registers.get_flag(Flag::Z));
+        }
+
+        #[test]
+        fn test_shls_sets_flags() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0b0100_0000.into());
+            IS::execute(
+                Instruction::Shl {
+                    reg: Register::R0,
+                    val: Operand::Value(1.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), i8::MIN.into());
+            assert!(processor.registers.get_flag(Flag::S));
+
+            IS::execute(
+                Instruction::Shl {
+                    reg: Register::R0,
+                    val: Operand::Value(1.into()),
+                    set_flags: true,
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+            assert!(processor.registers.get_flag(Flag::Z));
+            assert!(!processor.registers.get_flag(Flag::S));
+        }
+
+        #[test]
+        fn test_shift_by_register() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            let shift = |processor: &mut Processor<STACK_SIZE, IS, P, W>, left: bool, amount: i32| {
+                processor.registers.set_reg(Register::R1, amount.into());
+                let (reg, val, set_flags, cond) = (
+                    Register::R0,
+                    Operand::Register(Register::R1),
+                    false,
+                    JumpCondition::Unconditional,
+                );
+                let instruction = if left {
+                    Instruction::Shl {
+                        reg,
+                        val,
+                        set_flags,
+                        cond,
+                    }
+                } else {
+                    Instruction::Shr {
+                        reg,
+                        val,
+                        set_flags,
+                        cond,
+                    }
+                };
+                IS::execute(instruction, processor);
+                processor.registers.get_reg(Register::R0)
+            };
+
+            processor.registers.set_reg(Register::R0, 1.into());
+            assert_eq!(shift(&mut processor, true, 3), 8.into());
+            // the amount is masked to the 8 bits of the word
+            assert_eq!(shift(&mut processor, false, 9), 4.into());
+            assert_eq!(shift(&mut processor, true, 8), 4.into());
+            processor.registers.set_reg(Register::R0, 1.into());
+            assert_eq!(shift(&mut processor, true, -1), i8::MIN.into());
+        }
+    }
+
+    mod predicated {
+        use super::*;
+
+        fn add_if_zero() -> IS {
+            Instruction::Add {
+                acc: Register::R0,
+                rhs: Operand::Value(1.into()),
+                set_flags: false,
+                cond: JumpCondition::Zero,
+            }
+        }
+
+        #[test]
+        fn test_add_if_zero() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+
+            IS::execute(add_if_zero(), &mut processor);
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+
+            processor.registers.set_flag(Flag::Z, true);
+            IS::execute(add_if_zero(), &mut processor);
+            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+        }
+
+        #[test]
+        fn test_add_if_zero_handler() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            let handler = add_if_zero().handler::<STACK_SIZE, P, _>();
+
+            handler(add_if_zero(), &mut processor);
+            assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+
+            processor.registers.set_flag(Flag::Z, true);
+            handler(add_if_zero(), &mut processor);
+            assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+        }
+
+        #[test]
+        fn test_display() {
+            assert_eq!(add_if_zero().to_string(), "ADDEQ R0, #1");
+            assert_eq!(add_if_zero().mnemonic(), "ADD");
+            assert_eq!(add_if_zero().cond(), Some(JumpCondition::Zero));
+
+            let instruction: IS = Instruction::Sub {
+                acc: Register::R0,
+                rhs: Operand::Value(1.into()),
+                set_flags: true,
+                cond: JumpCondition::NotSigned,
+            };
+            assert_eq!(instruction.to_string(), "SUBSPL R0, #1");
+
+            assert_eq!(IS::Nop.cond(), None);
+        }
+
+        #[test]
+        fn test_round_trip_every_condition() {
+            for (instruction, mnemonic) in super::mnemonic::cases() {
+                if instruction.cond().is_none() {
+                    continue;
+                }
+
+                let operands = &instruction.to_string()[mnemonic.len()..];
+                for cond in JumpCondition::ALL.into_iter().skip(1) {
+                    let source = format!("{mnemonic}{}{operands}", cond.predicate_suffix());
+                    let parsed = crate::assemble::<W>(&source).unwrap();
+
+                    assert_eq!(parsed.len(), 1, "{source}");
+                    assert_eq!(parsed[0].mnemonic(), mnemonic, "{source}");
+                    assert_eq!(parsed[0].cond(), Some(cond), "{source}");
+                    assert_eq!(parsed[0].to_string(), source);
+                }
+            }
+        }
+    }
+
+    mod set_if {
+        use super::*;
+
+        #[test]
+        fn test_set_if_less() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 1.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            processor.registers.set_reg(Register::R2, 5.into());
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            IS::execute(
+                Instruction::SetIf {
+                    reg: Register::R2,
+                    condition: JumpCondition::Less,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R2), 1.into());
+
+            IS::execute(
+                Instruction::SetIf {
+                    reg: Register::R2,
+                    condition: JumpCondition::GreaterOrEq,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R2), 0.into());
+        }
+    }
+
+    mod cmov_if {
+        use super::*;
+
+        #[test]
+        fn test_cmov_if_greater() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 3.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            IS::execute(
+                Instruction::CmovIf {
+                    to: Register::R2,
+                    from: Operand::Value(7.into()),
+                    condition: JumpCondition::Greater,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R2), 7.into());
+            assert!(!processor.registers.get_flag(Flag::Z));
+            assert!(!processor.registers.get_flag(Flag::S));
+        }
+
+        #[test]
+        fn test_cmov_if_not_greater() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 2.into());
+            processor.registers.set_reg(Register::R1, 2.into());
+            processor.registers.set_reg(Register::R2, 5.into());
+            IS::execute(
+                Instruction::Cmp {
+                    lhs: Operand::Register(Register::R0),
+                    rhs: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            IS::execute(
+                Instruction::CmovIf {
+                    to: Register::R2,
+                    from: Operand::Value(7.into()),
+                    condition: JumpCondition::Greater,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R2), 5.into());
+            assert!(processor.registers.get_flag(Flag::Z));
+        }
+    }
+
+    mod rcl {
+        use super::*;
+
+        #[test]
+        fn test_rcl_with_carry() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0b0100_0001.into());
+            processor.registers.set_flag(Flag::C, true);
+            IS::execute(
+                Instruction::Rcl {
+                    reg: Register::R0,
+                    val: Operand::Value(1.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (0b1000_0011_u8 as i8).into());
+            assert!(!processor.registers.get_flag(Flag::C));
+        }
+
+        #[test]
+        fn test_rcl_multiple_bits() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (0b1100_0000_u8 as i8).into());
+            processor.registers.set_flag(Flag::C, true);
+            IS::execute(
+                Instruction::Rcl {
+                    reg: Register::R0,
+                    val: Operand::Value(2.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), 0b0000_0011.into());
+            assert!(processor.registers.get_flag(Flag::C));
+        }
+    }
+
+    mod rcr {
+        use super::*;
+
+        #[test]
+        fn test_rcr_with_carry() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0b0000_0011.into());
+            processor.registers.set_flag(Flag::C, true);
+            IS::execute(
+                Instruction::Rcr {
+                    reg: Register::R0,
+                    val: Operand::Value(1.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (0b1000_0001_u8 as i8).into());
+            assert!(processor.registers.get_flag(Flag::C));
+        }
+
+        #[test]
+        fn test_rcr_multiple_bits() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, (0b1000_0010_u8 as i8).into());
+            processor.registers.set_flag(Flag::C, true);
+            IS::execute(
+                Instruction::Rcr {
+                    reg: Register::R0,
+                    val: Operand::Value(2.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                &mut processor,
+            );
+            assert_eq!(processor.registers.get_reg(Register::R0), (0b0110_0000_u8 as i8).into());
+            assert!(processor.registers.get_flag(Flag::C));
+        }
+    }
+
+    mod rotate {
+        use super::*;
+
+        #[test]
+        fn test_rotate_by_register() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            let rotate = |processor: &mut Processor<STACK_SIZE, IS, P, W>, left: bool, amount: i32| {
+                processor.registers.set_reg(Register::R1, amount.into());
+                let (reg, val, cond) = (
+                    Register::R0,
+                    Operand::Register(Register::R1),
+                    JumpCondition::Unconditional,
+                );
+                let instruction = if left {
+                    Instruction::Rol { reg, val, cond }
+                } else {
+                    Instruction::Ror { reg, val, cond }
+                };
+                IS::execute(instruction, processor);
+                processor.registers.get_reg(Register::R0)
+            };
+
+            processor.registers.set_reg(Register::R0, 1.into());
+            assert_eq!(rotate(&mut processor, true, 3), 8.into());
+            // the amount is masked to the 8 bits of the word
+            assert_eq!(rotate(&mut processor, false, 11), 1.into());
+            assert_eq!(rotate(&mut processor, true, 8), 1.into());
+            assert_eq!(rotate(&mut processor, true, -1), i8::MIN.into());
+        }
+
+        #[test]
+        fn test_rotate_through_carry_by_register() {
+            let mut processor = Processor::<STACK_SIZE, IS, P, W>::new();
+            processor.registers.set_reg(Register::R0, 0b0100_0001.into());
+            processor.registers.set_reg(Register::R1, 9.into());
+            processor.registers.set_flag(Flag::C, true);
+            IS::execute(
+                Instruction::Rcl {
+                    reg: Register::R0,
+                    val: Operand::Register(Register::R1),
+                    cond: JumpCondition::Unconditional,
                 },
                 &mut processor,
             );
-            assert_eq!(processor.registers.get_flag(Flag::C), false);
-            assert_eq!(processor.registers.get_flag(Flag::S), false);
-            assert_eq!(processor.registers.get_flag(Flag::V), false);
-            assert_eq!(processor.registers.get_flag(Flag::Z), false);
+            assert_eq!(processor.registers.get_reg(Register::R0), (0b1000_0011_u8 as i8).into());
+            assert!(!processor.registers.get_flag(Flag::C));
         }
     }
 }
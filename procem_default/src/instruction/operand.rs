@@ -1,29 +1,95 @@
+use core::fmt::{Display, Formatter};
 use core::ops::Deref;
+use core::str::FromStr;
 
-use procem::{processor::Processor, register::Register, word::Word};
+use procem::{processor::Processor, register::Register, stack::StackMemory, word::Word};
 
+use crate::AssemblerError;
 use crate::instruction::Instruction;
+use crate::parser::Parser;
+use crate::tokenizer::Tokenizer;
 
 /// Operand for the instruction set.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Operand<W> {
     Register(Register),
     Value(W),
+    /// The value on the stack at the address in the base register plus the offset (base+offset addressing).
+    Indexed {
+        base: Register,
+        offset: W,
+    },
 }
 
 impl<W: Word> Operand<W> {
     /// Resolve the operand to a value.
+    ///
+    /// # Panics
+    /// Panics if an indexed operand points outside of the stack.
     #[inline]
-    pub(crate) const fn resolve<const STACK_SIZE: usize, P>(
+    pub(crate) fn resolve<const STACK_SIZE: usize, P, S>(
         self,
-        processor: &Processor<STACK_SIZE, Instruction<W>, P, W>,
+        processor: &Processor<STACK_SIZE, Instruction<W>, P, W, S>,
     ) -> W
     where
         P: Deref<Target = [Instruction<W>]>,
+        S: StackMemory<W>,
     {
         match self {
             Self::Register(reg) => processor.registers.get_reg(reg),
             Self::Value(val) => val,
+            Self::Indexed { .. } => processor.stack.read(self.resolve_address(processor)),
         }
     }
+
+    /// Resolve the operand to a stack address.
+    ///
+    /// The value of a register or literal operand is used as the address directly.
+    /// An indexed operand is resolved to the address it points to instead of the value stored there.
+    #[inline]
+    pub(crate) fn resolve_address<const STACK_SIZE: usize, P, S>(
+        self,
+        processor: &Processor<STACK_SIZE, Instruction<W>, P, W, S>,
+    ) -> W
+    where
+        P: Deref<Target = [Instruction<W>]>,
+        S: StackMemory<W>,
+    {
+        match self {
+            Self::Register(reg) => processor.registers.get_reg(reg),
+            Self::Value(val) => val,
+            Self::Indexed { base, offset } => processor.registers.get_reg(base).overflowing_add(offset).0,
+        }
+    }
+}
+
+impl<W: Word> Display for Operand<W> {
+    /// Formats the operand in assembly syntax, e.g. `R0`, `#10` or `[R1 + #4]`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::Register(reg) => write!(f, "{reg}"),
+            Self::Value(val) => write!(f, "#{val}"),
+            Self::Indexed { base, offset } => write!(f, "[{base} + #{offset}]"),
+        }
+    }
+}
+
+impl<W: Word> FromStr for Operand<W> {
+    type Err = Vec<AssemblerError>;
+
+    /// Parses an operand in assembly syntax, e.g. `R0`, `#10` or `[R1 + #4]`.
+    /// Literals follow the same rules as in the assembler, so `#0x0A`, `#'A'` and `#true` are valid as well.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens =
+            Tokenizer::tokenize(s).map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
+        Parser::parse_operand(&tokens).map_err(|err| vec![err.into()])
+    }
+}
+
+impl<W: Word> TryFrom<&str> for Operand<W> {
+    type Error = Vec<AssemblerError>;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
 }
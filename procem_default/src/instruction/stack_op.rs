@@ -0,0 +1,46 @@
+use core::fmt::{Display, Formatter};
+
+use procem::word::Word;
+
+/// Arithmetic operation that combines the two topmost values of the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StackOp {
+    /// Addition. \[SADD\]
+    Add,
+    /// Subtraction. \[SSUB\]
+    Sub,
+    /// Multiplication. \[SMUL\]
+    Mul,
+    /// Division. \[SDIV\]
+    Div,
+}
+
+impl StackOp {
+    /// Applies the operation to the values `a` and `b`, i.e. `a op b`.
+    #[inline]
+    pub(crate) fn apply<W: Word>(self, a: W, b: W) -> W {
+        match self {
+            Self::Add => a + b,
+            Self::Sub => a - b,
+            Self::Mul => a * b,
+            Self::Div => a / b,
+        }
+    }
+
+    /// The mnemonic of the corresponding stack instruction, e.g. `SADD`.
+    pub(crate) const fn mnemonic(self) -> &'static str {
+        match self {
+            Self::Add => "SADD",
+            Self::Sub => "SSUB",
+            Self::Mul => "SMUL",
+            Self::Div => "SDIV",
+        }
+    }
+}
+
+impl Display for StackOp {
+    /// Formats the operation as the mnemonic of the corresponding stack instruction.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.mnemonic())
+    }
+}
@@ -6,7 +6,11 @@
 //!
 //! All instructions can be written in mixed case.
 //! All operations that can be suffixed with an 'S', set the flag registers depending on the operation.
+//! The suffix does not change the result: the arithmetic is always performed on two's complement words, e.g. 'ADD' and 'ADDS' store the same result.
+//! The bitwise operations (AND, OR, XOR, NOT, NAND, NOR, XNOR) only affect the flags with the suffix: like in x86 they then set the signed (S), zero (Z) and parity (P) flags depending on the result and clear the carry (C) and overflow (V) flags.
 //! - *Labels* (**\<LABEL>**) are used to mark specific locations in the program. They are denoted using a dot ('.') followed by a string (e.g., '.label').
+//!   Multiple labels can mark the same location. Each label name can only be used once.
+//!   Labels can be used before they are defined. A label after the last instruction marks the end of the program.
 //! - *Registers* (**\<REG>**) must be a valid register name (e.g., 'R0', 'r1', 'R2', 'PC', 'sp').
 //! - *Literals* (**\<LIT>**) are decimal, binary, hexadecimal, octal, boolean or char constants.
 //!   They are denoted using a '#' followed by a valid literal value.
@@ -16,24 +20,84 @@
 //!   - Octal values start with '0o', followed by a sequence of '0's through '7's.
 //!   - Boolean values are either 'true' or 'false'.
 //!   - Character values are enclosed in single quotes, e.g., 'a', 'B', '5'.
-//! - *Operands* (**\<OP>**) can be a register name or a literal.
+//!     The escape sequences `\n`, `\t`, `\r`, `\0`, `\\`, `\'`, `\"` and `\xNN` (two hexadecimal digits) can be used in characters and strings.
+//! - *Operands* (**\<OP>**) can be a register name, a literal or an indexed address.
+//!   - Indexed addresses (e.g., '[R1 + #4]' or '[R1]') refer to the value on the stack at the address in the register plus the literal offset. Only 'LDR' and 'STR' accept them.
 //!
 //! 'END' marks the end of the program. It is only used as a guide for the assembler and not part of the assembled program.
 //!
+//! A line break ends an instruction, so a missing or surplus operand is reported for its line and never taken from the next line.
+//!
+//! ### Macros
+//!
+//! Repeated instruction sequences can be defined once as a macro between '.macro NAME' and '.endm'.
+//! Every instruction named 'NAME' is then replaced by the instructions of the macro.
+//! The comma separated operands of the invocation replace the positional arguments '%1', '%2', ... in the macro, e.g.
+//! '.macro swap push %1 mov %1, %2 pop %2 .endm' and 'swap R0, R1'.
+//! Macros can be used before they are defined and can invoke other macros up to a nesting depth of 64.
+//! Macro names consist of letters only. A label in a macro is defined again on every invocation, so macros should not contain labels.
+//!
+//! ### Data
+//!
+//! Constant data like lookup tables or strings is defined in a data section, which starts with '.data' and ends with '.text'.
+//! In a data section '.word' adds comma separated literals to the data, a string adds one word per character.
+//! A label in a data section marks the address of the following word, e.g. '.data .table .word #1, #2, #3 .text'.
+//! In the instructions a data label can be used as a literal operand with its address, optionally with an offset, e.g. 'LDR R0, .table + #1'.
+//!
+//! The data is returned by [`assemble_with_metadata()`] and is meant to be written to the stack at address 0, e.g. with
+//! [`write_memory()`](procem::processor::Processor::write_memory()), before the program is run.
+//!
+//! ### Entry point
+//!
+//! A program starts at its first instruction unless '.entry' names the label of another instruction, e.g. '.entry .main'.
+//! This allows helper routines to be defined before the code that calls them. A program has at most one '.entry'.
+//! The entry point is returned by [`assemble_with_metadata()`] and set with
+//! [`with_program_counter()`](procem::processor::ProcessorBuilder::with_program_counter()) before the program is run.
+//!
+//! ### Conditional execution
+//!
+//! Like in ARM, all operations except NOP, the jumps, SETcc and CMOVcc can be executed conditionally.
+//! The condition is appended to the mnemonic as a two letter code, e.g. 'ADDEQ R0, #1' or 'RETNE'.
+//! The codes are EQ (Z), NE (NZ), HS (C), LO (NC), MI (S), PL (NS), GT (G), LT (L), GE, LE, PE and PO, with the condition of the matching jump instruction in parentheses.
+//! The operation is skipped if the condition is not met.
+//! The flag setting suffix comes before the condition, e.g. 'SUBSNE R0, #1'.
+//!
 //! ### Operations
 //!
 //! - **NOP**: No operation.
 //! - **MOV \<REG>, \<OP>**: Copy a value from the operand to the register.
-//! - **PUSH \<OP>**: Push a value from the operand to the stack.
-//! - **POP \<REG>**: Pop a value from the stack to the register.
-//! - **CALL \<OP>**: Call a subroutine at the program address specified by the operand. Pushes the current program counter onto the stack and sets the program counter to the address of the subroutine.
-//! - **RET**: Return from a subroutine. Pops the return address from the stack and sets the program counter to the popped value.
+//! - **MOVH \<REG>, \<LIT>**: Set the high half of the register to the low half of the literal. The low half of the register is kept.
+//! - **MOVL \<REG>, \<LIT>**: Set the low half of the register to the low half of the literal and clear the high half. Together with `MOVH` this builds large constants, e.g. 'MOVL R0, #0x5678' and 'MOVH R0, #0x1234' load `0x12345678`.
+//! - **PUSH \<OP>**: Push a value from the operand to the stack. Stops the program with an error if the stack is full.
+//! - **POP \<REG>**: Pop a value from the stack to the register. Stops the program with an error if the stack is empty.
+//! - **SWP \<REG>, \<OP>**: Atomically swap the value of the register with the value on the stack at the address specified by the operand.
+//! - **LDR \<REG>, \<OP>**: Load the value on the stack at the address specified by the operand (e.g. '[R1 + #4]') into the register.
+//! - **STR \<REG>, \<OP>**: Store the value of the register on the stack at the address specified by the operand (e.g. '[R1 + #4]').
+//! - **IN \<REG>, \<LIT>**: Read a value from the I/O port given by the literal into the register. Stops the program with an error if no input handler is connected to the port.
+//! - **OUT \<LIT>, \<OP>**: Write the value of the operand to the I/O port given by the literal. Stops the program with an error if no output handler is connected to the port.
+//! - **CALL \<OP>**: Call a subroutine at the program address specified by the operand. Pushes the current program counter onto the stack and sets the program counter to the address of the subroutine. Stops the program with an error if the stack is full.
+//! - **RET**: Return from a subroutine. Pops the return address from the stack and sets the program counter to the popped value. Stops the program with an error if no subroutine was called.
+//! - **SADD, SSUB, SMUL, SDIV**: Pop the value `b` and then the value `a` from the stack and push the result of `a + b`, `a - b`, `a * b` or `a / b`. Stops the program with an error if there are less than two values on the stack.
 //! - **ADD\[S] \<REG>, \<OP>**: Add the value of the operand to the register. The result is stored in the register.
 //! - **SUB\[S] \<REG>, \<OP>**: Subtract the value of the operand from the register. The result is stored in the register.
+//! - **ADDSAT \<REG>, \<OP>**: Add the value of the operand to the register, clamping the result at the minimum and maximum value of the word. Sets the overflow flag (V) if the result was clamped.
+//! - **SUBSAT \<REG>, \<OP>**: Subtract the value of the operand from the register, clamping the result at the minimum and maximum value of the word. Sets the overflow flag (V) if the result was clamped.
+//! - **MIN\[U] \<REG>, \<OP>**: Store the smaller of the values of the register and the operand in the register. The values are compared as signed numbers, or as unsigned numbers with the `U` suffix.
+//! - **MAX\[U] \<REG>, \<OP>**: Store the larger of the values of the register and the operand in the register. The values are compared as signed numbers, or as unsigned numbers with the `U` suffix.
 //! - **MUL\[S] \<REG>, \<OP>**: Multiply the value of the operand with the value of the register. The result is stored in the register.
+//! - **MULH \<REG>, \<REG>, \<OP>**: Multiply the value of the operand with the value of the first register. The lower half of the double-width product is stored in the first register and the upper half in the second register.
+//! - **POW \<REG>, \<OP>**: Raise the value of the register to the power of the operand, wrapping around on overflow. The result is stored in the register. A negative exponent sets the overflow flag (V) and leaves the register unchanged.
 //! - **DIV\[S] \<REG>, \<OP>**: Divide the value of the register by the value of the operand. The result is stored in the register.
 //! - **INC\[S] \<REG>**: Increment the value in a register by one.
 //! - **DEC\[S] \<REG>**: Decrement the value in a register by one.
+//! - **SGN \<REG>**: Replace the value in a register with -1, 0 or 1 according to its sign. Sets the signed (S), zero (Z) and parity (P) flags.
+//! - **ABS \<REG>**: Replace the value in a register with its absolute value. The absolute value of the minimum wraps around to the minimum and sets the overflow flag (V).
+//! - **SXT \<REG>**: Sign-extend the low byte of a register to the full word.
+//! - **ZXT \<REG>**: Zero-extend the low byte of a register to the full word.
+//! - **BREV \<REG>**: Reverse the order of the bits in a register. Sets the zero flag (Z).
+//! - **CLZ \<REG>**: Replace the value in a register with the number of its leading zero bits. Zero has as many leading zeros as the word has bits.
+//! - **CTZ \<REG>**: Replace the value in a register with the number of its trailing zero bits. Zero has as many trailing zeros as the word has bits.
+//! - **BSWAP \<REG>**: Reverse the order of the bytes in a register, e.g. to convert between little and big endian.
 //! - **JMP \<LABEL>**: Set program counter to the address of the label (first instruction after the label), effectively jumping to the instruction at this point in the program.
 //! - **JZ \<LABEL>**: Jump to the label if the zero flag (Z) is set.
 //! - **JNZ \<LABEL>**: Jump to the label if the zero flag (Z) is not set.
@@ -45,18 +109,40 @@
 //! - **JGE \<LABEL>**: Jump to the label if the zero flag (Z) is set or signed flag (S) is not set.
 //! - **JL \<LABEL>**: Jump to the label if the zero flag (Z) is not set and the signed flag (S) is set.
 //! - **JLE \<LABEL>**: Jump to the label if the zero flag (Z) or signed flag (S) is set.
+//! - **JPE \<LABEL>**: Jump to the label if the parity flag (P) is set, i.e. the low byte of the last result has an even number of set bits.
+//! - **JPO \<LABEL>**: Jump to the label if the parity flag (P) is not set.
+//! - **JR, JRZ, JRNZ, JRC, JRNC, JRS, JRNS, JRG, JRGE, JRL, JRLE, JRPE, JRPO \<LABEL>**: Jump to the label if the condition of the corresponding jump instruction (e.g. `JZ` for `JRZ`) is met. The label is encoded as an offset from the next instruction, so the jump does not depend on the position of the program.
+//! - **SETZ, SETNZ, SETC, SETNC, SETS, SETNS, SETG, SETGE, SETL, SETLE, SETPE, SETPO \<REG>**: Set the register to 1 if the condition of the corresponding jump instruction (e.g. `JZ` for `SETZ`) is met and to 0 otherwise.
+//! - **CMOVZ, CMOVNZ, CMOVC, CMOVNC, CMOVS, CMOVNS, CMOVG, CMOVGE, CMOVL, CMOVLE, CMOVPE, CMOVPO \<REG>, \<OP>**: Copy a value from the operand to the register if the condition of the corresponding jump instruction (e.g. `JZ` for `CMOVZ`) is met. The flags are not affected.
 //! - **CMP \<OP>, \<OP>**: Compare the values of two operands and set the flags accordingly. This is the same as `SUBS` but disregards the result of the subtraction.
-//! - **XOR \<REG>, \<OP>**: Perform a bitwise xor operation on the value in the register with the value of the operand.
-//! - **AND \<REG>, \<OP>**: Perform a bitwise and operation on the value in the register with the value of the operand.
-//! - **OR \<REG>, \<OP>**: Perform a bitwise or operation on the value in the register with the value of the operand.
-//! - **NOT \<REG>**: Perform a bitwise not operation on the value in the register.
-//! - **SHL \<REG>, \<LIT>**: Shift the value in the register left by the specified number of bits. Only use values between 1 and the number of bits of the Word size minus 1.
-//! - **SHR \<REG>, \<LIT>**: Shift the value in the register right by the specified number of bits. Only use values between 1 and the number of bits of the Word size minus 1.
-//! - **ROL \<REG>, \<LIT>**: Rotate the value in the register left by the specified number of bits. Only use values between 1 and the number of bits of the Word size minus 1.
-//! - **ROR \<REG>, \<LIT>**: Rotate the value in the register right by the specified number of bits. Only use values between 1 and the number of bits of the Word size minus 1.
+//! - **XOR\[S] \<REG>, \<OP>**: Perform a bitwise xor operation on the value in the register with the value of the operand.
+//! - **AND\[S] \<REG>, \<OP>**: Perform a bitwise and operation on the value in the register with the value of the operand.
+//! - **OR\[S] \<REG>, \<OP>**: Perform a bitwise or operation on the value in the register with the value of the operand.
+//! - **NAND\[S] \<REG>, \<OP>**: Perform a bitwise nand operation on the value in the register with the value of the operand.
+//! - **NOR\[S] \<REG>, \<OP>**: Perform a bitwise nor operation on the value in the register with the value of the operand.
+//! - **XNOR\[S] \<REG>, \<OP>**: Perform a bitwise xnor operation on the value in the register with the value of the operand.
+//! - **NOT\[S] \<REG>**: Perform a bitwise not operation on the value in the register.
+//! - **SHL\[S] \<REG>, \<OP>**: Shift the value in the register left by the specified number of bits, masked to the number of bits of the Word size.
+//! - **SHR\[S] \<REG>, \<OP>**: Shift the value in the register right by the specified number of bits, masked to the number of bits of the Word size.
+//! - **ROL \<REG>, \<OP>**: Rotate the value in the register left by the specified number of bits, masked to the number of bits of the Word size.
+//! - **ROR \<REG>, \<OP>**: Rotate the value in the register right by the specified number of bits, masked to the number of bits of the Word size.
+//! - **RCL \<REG>, \<OP>**: Rotate the value in the register left through the carry flag (C) by the specified number of bits, masked to the number of bits of the Word size.
+//! - **RCR \<REG>, \<OP>**: Rotate the value in the register right through the carry flag (C) by the specified number of bits, masked to the number of bits of the Word size.
+//!
+//! ### Pseudo-instructions
+//!
+//! The assembler expands these shorthands into a real instruction, so they do not appear in the assembled program.
+//! Like the instruction they stand for, they can be executed conditionally, e.g. 'CLRNE R0'.
+//!
+//! - **CLR \<REG>**: Clear a register. Assembled as 'MOV \<REG>, #0'.
+//! - **TST \<REG>**: Set the flags depending on the value of a register. Assembled as 'CMP \<REG>, #0'.
 //!
 //! # Usage
-//! To assemble a [`Program`](../procem/program/struct.Program.html) from assembly code use the [`assemble`] function.
+//! To assemble a [`Program`](../procem/program/struct.Program.html) from assembly code use the [`assemble`] function
+//! or [`AssembledProgram::try_from_source`](TryFromSource::try_from_source).
+//!
+//! An assembled program can be stored in a compact binary format and loaded again without assembling it,
+//! see [`BinaryProgram`](encoding::BinaryProgram).
 //!
 //! # Example
 //! ```
@@ -88,21 +174,79 @@
 //! assert_eq!(processor.registers.get_reg(Register::R0), 6.into());
 //! ```
 //!
+use core::ops::Range;
+
 use crate::instruction::Instruction;
 use crate::parser::{Parser, ParserError};
-use crate::tokenizer::{Tokenizer, TokenizerError};
-use procem::program::Program;
+use crate::tokenizer::{PublicToken, TokenKind, Tokenizer, TokenizerError};
+use procem::processor::Processor;
+use procem::program::{Program, ProgramError};
+use procem::stack::StackError;
 use procem::word::Word;
 use thiserror::Error;
 
+pub mod encoding;
 pub mod instruction;
+mod macros;
+pub mod optimize;
 pub mod parser;
 pub mod tokenizer;
 
 pub type AssembledProgram<W> = Program<Instruction<W>, Vec<Instruction<W>>, W>;
 
+/// An assembled program together with the words of its data sections and its entry point, see [`assemble_with_metadata()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Assembled<W> {
+    /// The instructions of the program.
+    pub program: AssembledProgram<W>,
+    /// The words of all data sections, meant to be written to the stack at address 0.
+    pub data: Vec<W>,
+    /// The index of the instruction the program starts at.
+    pub entry: usize,
+}
+
+/// A processor that owns an [`AssembledProgram`], see [`run_source()`].
+pub type AssembledProcessor<const STACK_SIZE: usize, W> =
+    Processor<'static, STACK_SIZE, Instruction<W>, Vec<Instruction<W>>, W>;
+
+/// Options to relax the checks of the assembler, see [`assemble_with_options()`].
+///
+/// By default all checks are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AssemblerOptions {
+    /// Allows `CMP` with two literals, e.g. `cmp #5, #5`. Otherwise it is reported as [`ParserError::ComparisonOfConstants`],
+    /// as such a comparison is almost always a forgotten register.
+    pub allow_constant_comparisons: bool,
+}
+
+/// The location of an instruction in the assembly source, see [`assemble_with_spans()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    /// The line of the start of the instruction, starting at 1.
+    pub line: usize,
+    /// The byte column of the start of the instruction in its line, starting at 1.
+    pub column: usize,
+    /// The byte range of the instruction and its operands in the source.
+    pub range: Range<usize>,
+}
+
+impl SourceSpan {
+    /// Computes the line and column of the range from the byte offsets at which the lines of the source start.
+    fn new(line_starts: &[usize], range: Range<usize>) -> Self {
+        let line = line_starts.partition_point(|&start| start <= range.start);
+
+        Self {
+            line,
+            column: range.start - line_starts[line - 1] + 1,
+            range,
+        }
+    }
+}
+
 /// Assembles Program from assembly code.
 ///
+/// The words of data sections and the entry point are not part of the program, use [`assemble_with_metadata()`] to get them.
+///
 /// # Errors
 /// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
 ///
@@ -128,12 +272,14 @@ pub type AssembledProgram<W> = Program<Instruction<W>, Vec<Instruction<W>>, W>;
 ///     Program::<Instruction<I32>, Vec<Instruction<I32>>, I32>::new(vec![
 ///         Instruction::Mov {
 ///             to: Register::R0,
-///             from: Operand::Value(2.into())
+///             from: Operand::Value(2.into()),
+///             cond: JumpCondition::Unconditional
 ///         },
 ///         Instruction::Add {
 ///             acc: Register::R1,
 ///             rhs: Operand::Register(Register::R0),
-///             signed: false
+///             set_flags: false,
+///             cond: JumpCondition::Unconditional
 ///         },
 ///         Instruction::Jump {
 ///             to: 0.into(),
@@ -143,15 +289,299 @@ pub type AssembledProgram<W> = Program<Instruction<W>, Vec<Instruction<W>>, W>;
 /// );
 /// ```
 pub fn assemble<W: Word>(input: impl AsRef<str>) -> Result<AssembledProgram<W>, Vec<AssemblerError>> {
+    assemble_with_options(input, AssemblerOptions::default())
+}
+
+/// Assembles a program like [`assemble()`], but with some checks relaxed by the options.
+///
+/// # Errors
+/// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
+///
+/// # Example
+/// ```
+/// use procem::word::I32;
+/// use procem_default::{AssemblerOptions, assemble, assemble_with_options};
+///
+/// assert!(assemble::<I32>("cmp #1, #1").is_err());
+///
+/// let options = AssemblerOptions { allow_constant_comparisons: true };
+/// assert!(assemble_with_options::<I32>("cmp #1, #1", options).is_ok());
+/// ```
+pub fn assemble_with_options<W: Word>(
+    input: impl AsRef<str>,
+    options: AssemblerOptions,
+) -> Result<AssembledProgram<W>, Vec<AssemblerError>> {
     let tokens = Tokenizer::tokenize(input.as_ref())
         .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
 
-    let instructions = Parser::parse(tokens.as_ref())
+    let instructions = Parser::parse(tokens.as_ref(), options)
         .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
 
     Ok(Program::new(instructions))
 }
 
+/// Assembles a program like [`assemble()`] and also returns the words of its data sections and its entry point.
+///
+/// The addresses of the data labels start at 0, so the data is meant to be written to the stack at address 0
+/// before the program is run. The stack should then start behind the data, so that pushed values do not overwrite it.
+///
+/// The entry point is the instruction of the label after '.entry' or 0 if the program has no '.entry'.
+///
+/// # Errors
+/// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
+///
+/// # Example
+/// ```
+/// use procem::{processor::Processor, register::Register, word::I32};
+/// use procem_default::assemble_with_metadata;
+///
+/// let assembled = assemble_with_metadata::<I32>(
+///     "
+///     .entry .main
+///     .data
+///     .table
+///     .word #1, #2, #3
+///     .text
+///     .double
+///     add R0, R0
+///     ret
+///     .main
+///     ldr R0, .table + #1
+///     call #0
+///     ",
+/// )
+/// .unwrap();
+///
+/// let mut processor = Processor::<16, _, _, _>::builder()
+///     .with_program(&assembled.program)
+///     .with_stack_pointer(3.into())
+///     .with_program_counter(assembled.entry.try_into().unwrap())
+///     .build();
+/// processor.write_memory(0, &assembled.data).unwrap();
+/// processor.run_program().unwrap();
+///
+/// assert_eq!(assembled.entry, 2);
+/// assert_eq!(processor.registers.get_reg(Register::R0), 4.into());
+/// ```
+pub fn assemble_with_metadata<W: Word>(input: impl AsRef<str>) -> Result<Assembled<W>, Vec<AssemblerError>> {
+    let tokens = Tokenizer::tokenize(input.as_ref())
+        .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
+
+    let parsed = Parser::parse_all(tokens.as_ref(), AssemblerOptions::default())
+        .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
+
+    Ok(Assembled {
+        program: Program::new(parsed.instructions),
+        data: parsed.data,
+        entry: parsed.entry,
+    })
+}
+
+/// Splits assembly code into tokens, e.g. for syntax highlighting in an editor.
+///
+/// Every token has its [`kind`](TokenKind), its text and its byte range in the input.
+/// Macros are not expanded.
+///
+/// # Errors
+/// Returns a vector of all errors that happened during the tokenizing.
+///
+/// # Example
+/// ```
+/// use procem_default::{tokenize, tokenizer::TokenKind};
+///
+/// let tokens = tokenize("mov R0, #2").unwrap();
+///
+/// assert_eq!(tokens[0].kind, TokenKind::Instruction);
+/// assert_eq!(tokens[3].text, "#2");
+/// assert_eq!(tokens[3].span, 8..10);
+/// ```
+pub fn tokenize(input: &str) -> Result<Vec<PublicToken>, Vec<TokenizerError>> {
+    let (tokens, spans) = Tokenizer::tokenize_with_spans(input)?;
+
+    Ok(tokens
+        .iter()
+        .zip(spans)
+        .map(|(token, span)| PublicToken {
+            kind: TokenKind::from(token),
+            text: input[span.clone()].to_string(),
+            span,
+        })
+        .collect())
+}
+
+/// Parses tokens returned by [`tokenize()`] into instructions, e.g. after a tool has inspected or filtered them.
+///
+/// This is the second stage of [`assemble()`], including the expansion of macros.
+/// Only the kind and text of the tokens are used, so their spans do not need to be correct.
+///
+/// # Errors
+/// Returns a vector of all errors that happened during the parsing.
+/// A token whose text is not a single token of its kind is reported as [`ParserError::InvalidToken`].
+pub fn parse<W: Word>(tokens: &[PublicToken]) -> Result<Vec<Instruction<W>>, Vec<ParserError>> {
+    let mut errors = Vec::new();
+    let tokens: Vec<_> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, token)| token.to_token(idx).map_err(|err| errors.push(err)).ok())
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Parser::parse(&tokens, AssemblerOptions::default())
+}
+
+/// Assembles a program like [`assemble()`] and also returns the location in the source of every instruction.
+///
+/// The spans are indexed like the instructions, so the program counter of a processor can be mapped back to a line.
+/// Instructions from the body of a macro are located at the invocation of the macro.
+///
+/// # Errors
+/// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
+///
+/// # Example
+/// ```
+/// use procem::word::I32;
+/// use procem_default::assemble_with_spans;
+///
+/// let (program, spans) = assemble_with_spans::<I32>("mov R0, #2\n  add R0, R0").unwrap();
+///
+/// assert_eq!((spans[1].line, spans[1].column), (2, 3));
+/// assert_eq!(spans[1].range, 13..23);
+/// ```
+pub fn assemble_with_spans<W: Word>(
+    input: impl AsRef<str>,
+) -> Result<(AssembledProgram<W>, Vec<SourceSpan>), Vec<AssemblerError>> {
+    let input = input.as_ref();
+
+    let (tokens, token_spans) = Tokenizer::tokenize_with_spans(input)
+        .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
+
+    let parsed = Parser::parse_all(tokens.as_ref(), AssemblerOptions::default())
+        .map_err(|err| err.into_iter().map(Into::into).collect::<Vec<AssemblerError>>())?;
+
+    let line_starts: Vec<_> = core::iter::once(0)
+        .chain(input.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let spans = parsed
+        .token_ranges
+        .into_iter()
+        .map(|range| {
+            let range = token_spans[*range.start()].start..token_spans[*range.end()].end;
+            SourceSpan::new(&line_starts, range)
+        })
+        .collect();
+
+    Ok((Program::new(parsed.instructions), spans))
+}
+
+/// Assembles a program like [`assemble()`] and also returns the 1-based source line of every instruction.
+///
+/// This is a shorthand for the lines of [`assemble_with_spans()`], e.g. to show the source next to the instructions.
+///
+/// # Errors
+/// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
+///
+/// # Example
+/// ```
+/// use procem::word::I32;
+/// use procem_default::assemble_with_source_map;
+///
+/// let (program, lines) = assemble_with_source_map::<I32>("mov R0, #2\n\n.loop\nadd R0, R0").unwrap();
+///
+/// assert_eq!(lines, [1, 4]);
+/// ```
+pub fn assemble_with_source_map<W: Word>(
+    input: impl AsRef<str>,
+) -> Result<(AssembledProgram<W>, Vec<usize>), Vec<AssemblerError>> {
+    let (program, spans) = assemble_with_spans(input)?;
+    Ok((program, spans.into_iter().map(|span| span.line).collect()))
+}
+
+/// Extension trait to construct an [`AssembledProgram`] directly from assembly source.
+///
+/// [`Program`] lives in the [`procem`](../procem/index.html) crate, which is `no_std` and has no knowledge of the assembler.
+/// This trait is therefore provided by **`procem_default`** (which requires `std`) and is a thin wrapper over [`assemble`].
+pub trait TryFromSource: Sized {
+    /// Assembles a program from assembly code.
+    ///
+    /// # Errors
+    /// Returns a vector of all errors that a happened during either the tokenizing or the parsing.
+    ///
+    /// # Example
+    /// ```
+    /// use procem::{processor::Processor, register::Register, word::I32};
+    /// use procem_default::{AssembledProgram, TryFromSource};
+    ///
+    /// let program = AssembledProgram::<I32>::try_from_source(
+    ///     "
+    ///     mov R0, #4
+    ///     mul R0, #5
+    ///     ",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    /// let _ = processor.run_program();
+    ///
+    /// assert_eq!(processor.registers.get_reg(Register::R0), 20.into());
+    /// ```
+    fn try_from_source(src: &str) -> Result<Self, Vec<AssemblerError>>;
+}
+
+impl<W: Word> TryFromSource for AssembledProgram<W> {
+    #[inline]
+    fn try_from_source(src: &str) -> Result<Self, Vec<AssemblerError>> {
+        assemble(src)
+    }
+}
+
+/// Assembles a program, runs it from its entry point to completion on a new processor with a stack of `STACK_SIZE` words
+/// and returns the processor for inspection.
+///
+/// The words of the data sections are written to the stack at address 0 and the stack pointer starts behind them,
+/// see [`assemble_with_metadata()`].
+/// The processor owns the program, so it can be returned without keeping the program around.
+/// Use the [`ProcessorBuilder`](procem::processor::ProcessorBuilder) to configure the processor before running it instead.
+///
+/// # Errors
+/// Returns [`RunError::Assembler`] with all errors of the assembler, [`RunError::Data`] if the data does not fit on the stack
+/// or [`RunError::Program`] if the program fails.
+///
+/// # Example
+/// ```
+/// use procem::{register::Register, word::I32};
+/// use procem_default::run_source;
+///
+/// let processor = run_source::<1024, I32>("mov R0, #4\nmul R0, #5").unwrap();
+///
+/// assert_eq!(processor.registers.get_reg(Register::R0), 20.into());
+/// ```
+pub fn run_source<const STACK_SIZE: usize, W: Word>(
+    src: impl AsRef<str>,
+) -> Result<AssembledProcessor<STACK_SIZE, W>, RunError> {
+    let Assembled { program, data, entry } = assemble_with_metadata(src).map_err(RunError::Assembler)?;
+    let pc = W::try_from(entry).map_err(|_| ProgramError::PCOutOfBounds {
+        pc: entry,
+        program_len: program.len(),
+    })?;
+    let sp = W::try_from(data.len()).map_err(|_| StackError::OutOfBounds {
+        addr: 0,
+        len: data.len(),
+        stack_size: STACK_SIZE,
+    })?;
+    let mut processor = Processor::builder()
+        .with_owned_program(program)
+        .with_program_counter(pc)
+        .with_stack_pointer(sp)
+        .build();
+    processor.write_memory(0, &data)?;
+    processor.run_program()?;
+
+    Ok(processor)
+}
+
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum AssemblerError {
     #[error("Error during parsing: ")]
@@ -159,3 +589,14 @@ pub enum AssemblerError {
     #[error("Error during tokenization: ")]
     Tokenizer(#[from] TokenizerError),
 }
+
+/// The error of [`run_source()`].
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RunError {
+    #[error("The program could not be assembled")]
+    Assembler(Vec<AssemblerError>),
+    #[error("The data does not fit on the stack: {0}")]
+    Data(#[from] StackError),
+    #[error("Error while running the program: {0}")]
+    Program(#[from] ProgramError),
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use crate::parser::ParserError;
+use crate::tokenizer::Token;
+
+/// The expanded tokens with the index of the token in the input each of them originates from.
+pub(crate) type ExpandedTokens<'a> = (Vec<Token<'a>>, Vec<usize>);
+
+/// Maximum nesting depth of macro invocations. This stops macros that invoke themselves.
+pub(crate) const MACRO_RECURSION_LIMIT: usize = 64;
+
+/// Expands the macros defined with `.macro NAME ... .endm` in the tokens.
+///
+/// The definitions are removed and every instruction with the name of a macro is replaced by the body of the macro.
+/// The comma separated operands of the invocation replace the positional arguments `%1`, `%2`, ... in the body.
+/// Macros can be used before they are defined and can invoke other macros.
+///
+/// Every expanded token originates from a token of the input. Tokens from the body of a macro originate from its invocation.
+pub(crate) struct MacroExpander<'t, 'a> {
+    // bodies of the macros by their uppercase name
+    macros: HashMap<String, &'t [Token<'a>]>,
+    errors: Option<Vec<ParserError>>,
+    expanded: Vec<Token<'a>>,
+    origins: Vec<usize>,
+}
+
+impl<'t, 'a> MacroExpander<'t, 'a> {
+    pub(crate) fn expand(tokens: &'t [Token<'a>]) -> Result<ExpandedTokens<'a>, Vec<ParserError>> {
+        let mut expander = MacroExpander {
+            macros: HashMap::new(),
+            errors: None,
+            expanded: Vec::with_capacity(tokens.len()),
+            origins: Vec::with_capacity(tokens.len()),
+        };
+
+        let (tokens, origins) = expander.collect_definitions(tokens);
+        expander.expand_all(&tokens, &origins);
+
+        match expander.errors {
+            None => Ok((expander.expanded, expander.origins)),
+            Some(errors) => Err(errors),
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, token: Token<'a>, origin: usize) {
+        self.expanded.push(token);
+        self.origins.push(origin);
+    }
+
+    #[inline]
+    fn add_error(&mut self, err: ParserError) {
+        self.errors.get_or_insert_default().push(err);
+    }
+
+    /// Stores all macro definitions and returns the remaining tokens with their indices in the input.
+    fn collect_definitions(&mut self, tokens: &'t [Token<'a>]) -> ExpandedTokens<'a> {
+        let mut rest = Vec::with_capacity(tokens.len());
+        let mut origins = Vec::with_capacity(tokens.len());
+        let mut idx = 0;
+
+        while idx < tokens.len() {
+            match &tokens[idx] {
+                Token::Label(label) if label.eq_ignore_ascii_case(".macro") => {
+                    idx = self.collect_definition(tokens, idx);
+                }
+                Token::Label(label) if label.eq_ignore_ascii_case(".endm") => {
+                    self.add_error(ParserError::InvalidToken {
+                        idx,
+                        expected: "Label or Instruction",
+                        got: (*label).to_string(),
+                    });
+                    idx += 1;
+                }
+                token => {
+                    rest.push(token.clone());
+                    origins.push(idx);
+                    idx += 1;
+                }
+            }
+        }
+
+        (rest, origins)
+    }
+
+    /// Stores the definition starting with the `.macro` token at `start` and returns the index after its `.endm`.
+    fn collect_definition(&mut self, tokens: &'t [Token<'a>], start: usize) -> usize {
+        let Some(Token::Instruction(name)) = tokens.get(start + 1) else {
+            self.add_error(ParserError::InvalidToken {
+                idx: start + 1,
+                expected: "Macro name",
+                got: tokens
+                    .get(start + 1)
+                    .map_or_else(|| "End".to_string(), |token| format!("{token:?}")),
+            });
+            return start + 1;
+        };
+
+        let body_start = start + 2;
+        let Some(len) = tokens[body_start..]
+            .iter()
+            .position(|token| matches!(token, Token::Label(label) if label.eq_ignore_ascii_case(".endm")))
+        else {
+            self.add_error(ParserError::UnterminatedMacro { name: name.to_string() });
+            return tokens.len();
+        };
+
+        if self
+            .macros
+            .insert(name.to_ascii_uppercase(), &tokens[body_start..body_start + len])
+            .is_some()
+        {
+            self.add_error(ParserError::DuplicateMacro { name: name.to_string() });
+        }
+
+        body_start + len + 1
+    }
+
+    /// Expands all macro invocations in the tokens after the definitions were removed.
+    fn expand_all(&mut self, tokens: &[Token<'a>], origins: &[usize]) {
+        let mut idx = 0;
+
+        while idx < tokens.len() {
+            match &tokens[idx] {
+                // the assembler ignores everything after the end
+                Token::End => {
+                    self.expanded.extend_from_slice(&tokens[idx..]);
+                    self.origins.extend_from_slice(&origins[idx..]);
+                    return;
+                }
+                Token::Instruction(name) if self.macros.contains_key(&name.to_ascii_uppercase()) => {
+                    let end = Self::invocation_end(tokens, idx);
+                    if let Err(err) = self.expand_invocation(name, &tokens[idx + 1..end], origins[idx], 0) {
+                        self.add_error(err);
+                    }
+                    idx = end;
+                }
+                token => {
+                    self.push(token.clone(), origins[idx]);
+                    idx += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the index after the operands of the instruction at `idx`.
+    fn invocation_end(tokens: &[Token<'a>], idx: usize) -> usize {
+        tokens[idx + 1..]
+            .iter()
+            .position(|token| {
+                matches!(
+                    token,
+                    Token::Instruction(_) | Token::Label(_) | Token::Newline | Token::End
+                )
+            })
+            .map_or(tokens.len(), |len| idx + 1 + len)
+    }
+
+    /// Replaces the invocation of a macro with its body, expanding nested invocations.
+    /// All tokens of the body originate from the invocation at `origin`.
+    fn expand_invocation(
+        &mut self,
+        name: &str,
+        operands: &[Token<'a>],
+        origin: usize,
+        depth: usize,
+    ) -> Result<(), ParserError> {
+        if depth == MACRO_RECURSION_LIMIT {
+            return Err(ParserError::MacroRecursionLimit {
+                name: name.to_string(),
+                limit: MACRO_RECURSION_LIMIT,
+            });
+        }
+
+        let body = self.macros[&name.to_ascii_uppercase()];
+        let args: Vec<_> = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands.split(|token| *token == Token::Comma).collect()
+        };
+
+        let mut substituted = Vec::with_capacity(body.len());
+        for token in body {
+            match token {
+                Token::MacroArgument(arg) => {
+                    let value = arg.checked_sub(1).and_then(|idx| args.get(idx)).ok_or_else(|| {
+                        ParserError::MacroArgumentMissing {
+                            name: name.to_string(),
+                            arg: *arg,
+                        }
+                    })?;
+                    substituted.extend_from_slice(value);
+                }
+                token => substituted.push(token.clone()),
+            }
+        }
+
+        let mut idx = 0;
+        while idx < substituted.len() {
+            match &substituted[idx] {
+                Token::Instruction(inner) if self.macros.contains_key(&inner.to_ascii_uppercase()) => {
+                    let end = Self::invocation_end(&substituted, idx);
+                    self.expand_invocation(inner, &substituted[idx + 1..end], origin, depth + 1)?;
+                    idx = end;
+                }
+                token => {
+                    self.push(token.clone(), origin);
+                    idx += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokenizer::{Literal, Tokenizer};
+
+    fn expand(input: &str) -> Result<Vec<Token<'_>>, Vec<ParserError>> {
+        let tokens = Tokenizer::tokenize(input).unwrap();
+        MacroExpander::expand(&tokens).map(|(tokens, _)| tokens)
+    }
+
+    #[test]
+    fn test_expand_arguments() {
+        assert_eq!(
+            expand(".macro addtwo add %1, #2\n.endm\naddtwo R3"),
+            Ok(vec![
+                Token::Newline,
+                Token::Instruction("add"),
+                Token::Register("R3"),
+                Token::Comma,
+                Token::Literal(Literal::Decimal("2")),
+                Token::Newline,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_origins() {
+        let tokens = Tokenizer::tokenize(".macro inc\nadd %1, #1\n.endm\nnop\ninc R0\nnop").unwrap();
+        let (expanded, origins) = MacroExpander::expand(&tokens).unwrap();
+
+        assert_eq!(expanded.len(), origins.len());
+        // the body of the macro originates from the invocation of `inc`
+        assert_eq!(origins, [9, 10, 11, 12, 12, 12, 12, 12, 12, 14, 15]);
+    }
+
+    #[test]
+    fn test_missing_argument() {
+        assert_eq!(
+            expand(".macro m\nmov %1, %2\n.endm\nm R0"),
+            Err(vec![ParserError::MacroArgumentMissing {
+                name: "m".to_string(),
+                arg: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        assert_eq!(
+            expand(".macro loop\nnop\nloop\n.endm\nloop"),
+            Err(vec![ParserError::MacroRecursionLimit {
+                name: "loop".to_string(),
+                limit: MACRO_RECURSION_LIMIT,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_unterminated() {
+        assert_eq!(
+            expand(".macro m\nnop"),
+            Err(vec![ParserError::UnterminatedMacro { name: "m".to_string() }])
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use procem::{program::Program, register::Register, word::Word};
+
+use crate::instruction::{Instruction, jump_condition::JumpCondition, operand::Operand};
+use crate::{Assembled, AssembledProgram};
+
+/// A peephole optimization pass over an assembled program.
+///
+/// The pass
+/// - removes all `NOP`s,
+/// - folds a `MOV` of a literal that is directly followed by an `ADD` or `SUB` of a literal to the same register
+///   into a single `MOV`, e.g. `MOV R0, #0` and `ADD R0, #5` into `MOV R0, #5`.
+///
+/// Only unconditional instructions are folded and the flag setting variants (`ADDS`, `SUBS`) are kept,
+/// as are instructions that are jumped to and the entry point.
+/// The targets of jumps and calls to literal addresses, absolute and relative, and the entry point of an [`Assembled`]
+/// program are adjusted to the removed instructions.
+///
+/// Addresses that are computed while running the program cannot be adjusted.
+/// A program with a `CALL` to a register or an indexed address is therefore returned unchanged.
+/// Other uses of addresses as values, e.g. a label loaded into a register or writing to `PC`, are not detected.
+pub trait Optimize: Sized {
+    /// Returns an optimized program that behaves the same as this one.
+    ///
+    /// # Example
+    /// ```
+    /// use procem::word::I32;
+    /// use procem_default::{AssembledProgram, TryFromSource, optimize::Optimize};
+    ///
+    /// let program = AssembledProgram::<I32>::try_from_source("mov R0, #0\nadd R0, #5\nnop").unwrap();
+    ///
+    /// assert_eq!(
+    ///     program.optimize(),
+    ///     AssembledProgram::<I32>::try_from_source("mov R0, #5").unwrap()
+    /// );
+    /// ```
+    #[must_use]
+    fn optimize(&self) -> Self;
+}
+
+impl<W: Word> Optimize for AssembledProgram<W> {
+    fn optimize(&self) -> Self {
+        optimize_program(self, 0).0
+    }
+}
+
+impl<W: Word> Optimize for Assembled<W> {
+    fn optimize(&self) -> Self {
+        let (program, entry) = optimize_program(&self.program, self.entry);
+
+        Self {
+            program,
+            data: self.data.clone(),
+            entry,
+        }
+    }
+}
+
+/// Returns the optimized program and the index of the entry point in it.
+/// The program is returned unchanged if it cannot be optimized, see [`Optimize`].
+fn optimize_program<W: Word>(program: &AssembledProgram<W>, entry: usize) -> (AssembledProgram<W>, usize) {
+    if program.iter().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::Call {
+                addr: Operand::Register(_) | Operand::Indexed { .. },
+                ..
+            }
+        )
+    }) {
+        return (program.clone(), entry);
+    }
+
+    optimize(program, entry).map_or_else(
+        || (program.clone(), entry),
+        |(instructions, entry)| (Program::new(instructions), entry),
+    )
+}
+
+/// Returns the optimized instructions and the index of the entry point in them,
+/// or `None` if an address cannot be represented by the word.
+fn optimize<W: Word>(instructions: &[Instruction<W>], entry: usize) -> Option<(Vec<Instruction<W>>, usize)> {
+    let mut targets = vec![false; instructions.len() + 1];
+    // the program starts at the entry point like a jump to it
+    if let Some(is_target) = targets.get_mut(entry) {
+        *is_target = true;
+    }
+    for (idx, instruction) in instructions.iter().enumerate() {
+        if let Some(target) = jump_target(idx, instruction)?
+            && let Some(is_target) = targets.get_mut(target)
+        {
+            *is_target = true;
+        }
+    }
+    // jumping to a removed NOP jumps to the instruction after it
+    for idx in 0..instructions.len() {
+        if targets[idx] && instructions[idx] == Instruction::Nop {
+            targets[idx + 1] = true;
+        }
+    }
+
+    let mut optimized: Vec<Instruction<W>> = Vec::with_capacity(instructions.len());
+    // index of every instruction in the optimized program. Removed instructions are mapped to the next kept one.
+    let mut new_idx = Vec::with_capacity(instructions.len() + 1);
+
+    for (idx, instruction) in instructions.iter().enumerate() {
+        new_idx.push(optimized.len());
+
+        if *instruction == Instruction::Nop {
+            continue;
+        }
+
+        if !targets[idx]
+            && let Some(last) = optimized.last_mut()
+            && let Some(folded) = fold(last, instruction)
+        {
+            *last = folded;
+            continue;
+        }
+
+        optimized.push(*instruction);
+    }
+    new_idx.push(optimized.len());
+
+    let removed = |idx: usize| idx - new_idx[idx];
+    let mut kept = instructions
+        .iter()
+        .enumerate()
+        .filter(|&(idx, _)| new_idx[idx] < new_idx[idx + 1]);
+
+    for instruction in &mut optimized {
+        let (idx, original) = kept.next()?;
+
+        match instruction {
+            Instruction::Jump { to, .. }
+            | Instruction::Call {
+                addr: Operand::Value(to),
+                ..
+            } => {
+                let target: usize = (*to).into();
+                if target < new_idx.len() {
+                    *to = W::try_from(new_idx[target]).ok()?;
+                }
+            }
+            Instruction::JumpRel { offset, .. } => {
+                if let Some(target) = jump_target(idx, original)?
+                    && target < new_idx.len()
+                {
+                    *offset = *offset - W::try_from(removed(target)).ok()? + W::try_from(removed(idx + 1)).ok()?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some((optimized, new_idx.get(entry).copied().unwrap_or(entry)))
+}
+
+/// Returns the address the instruction at `idx` jumps to, if it is known before running the program.
+/// Returns `None` if the address of the next instruction cannot be represented by the word.
+fn jump_target<W: Word>(idx: usize, instruction: &Instruction<W>) -> Option<Option<usize>> {
+    Some(match instruction {
+        Instruction::JumpRel { offset, .. } => Some((W::try_from(idx + 1).ok()? + *offset).into()),
+        _ => procem::instruction::Instruction::jump_target(instruction),
+    })
+}
+
+/// Folds an instruction into the previous one, see [`Optimize`].
+fn fold<W: Word>(prev: &Instruction<W>, next: &Instruction<W>) -> Option<Instruction<W>> {
+    let Instruction::Mov {
+        to,
+        from: Operand::Value(val),
+        cond: JumpCondition::Unconditional,
+    } = *prev
+    else {
+        return None;
+    };
+
+    let val = match *next {
+        Instruction::Add {
+            acc,
+            rhs: Operand::Value(rhs),
+            set_flags: false,
+            cond: JumpCondition::Unconditional,
+        } if acc == to => val + rhs,
+        Instruction::Sub {
+            acc,
+            rhs: Operand::Value(rhs),
+            set_flags: false,
+            cond: JumpCondition::Unconditional,
+        } if acc == to => val - rhs,
+        _ => return None,
+    };
+
+    // moving to the program counter is a jump, which has to stay at its address
+    (to != Register::PC).then_some(Instruction::Mov {
+        to,
+        from: Operand::Value(val),
+        cond: JumpCondition::Unconditional,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{assemble, assemble_with_metadata};
+    use procem::word::I32;
+
+    fn optimize(input: &str) -> AssembledProgram<I32> {
+        assemble::<I32>(input).unwrap().optimize()
+    }
+
+    #[test]
+    fn test_remove_nops() {
+        assert_eq!(
+            optimize("nop\nmov R0, #1\nnop\nnop\npush R0\nnop"),
+            assemble("mov R0, #1\npush R0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fold_mov_and_add() {
+        assert_eq!(
+            optimize("mov R0, #0\nadd R0, #5\nsub R0, #2"),
+            assemble("mov R0, #3").unwrap()
+        );
+        // the flags of ADDS, other registers and conditional instructions are kept
+        for input in [
+            "mov R0, #0\nadds R0, #5",
+            "mov R0, #0\nadd R1, #5",
+            "mov R0, #0\naddeq R0, #5",
+            "moveq R0, #0\nadd R0, #5",
+            "mov R0, #0\nadd R0, R1",
+        ] {
+            assert_eq!(optimize(input), assemble(input).unwrap(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_keep_jump_target() {
+        let input = "
+            mov R0, #0
+            .loop
+            add R0, #1
+            jmp .loop
+            ";
+        assert_eq!(optimize(input), assemble(input).unwrap());
+        assert_eq!(
+            optimize("mov R0, #0\n.loop\nnop\nadd R0, #1\njmp .loop"),
+            assemble("mov R0, #0\n.loop\nadd R0, #1\njmp .loop").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_adjust_jump_targets() {
+        assert_eq!(
+            optimize(
+                "
+                nop
+                .start
+                jr .done
+                nop
+                nop
+                call #5
+                nop
+                jr .start
+                .done
+                jmp .start
+                "
+            ),
+            assemble(
+                "
+                .start
+                jr .done
+                call #2
+                jr .start
+                .done
+                jmp .start
+                "
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_adjust_entry() {
+        let assembled = assemble_with_metadata::<I32>("nop\nmov R0, #0\n.main\nadd R0, #1\nnop\n.entry .main").unwrap();
+        let optimized = assembled.optimize();
+
+        assert_eq!(optimized.program, assemble("mov R0, #0\nadd R0, #1").unwrap());
+        assert_eq!(optimized.entry, 1);
+
+        let assembled = assemble_with_metadata::<I32>(".entry .main\nmov R0, #0\n.main\nnop\nnop\nadd R0, #1").unwrap();
+        let optimized = assembled.optimize();
+        assert_eq!(optimized.program, assemble("mov R0, #0\nadd R0, #1").unwrap());
+        assert_eq!(optimized.entry, 1);
+    }
+
+    #[test]
+    fn test_indirect_call_is_unchanged() {
+        let input = "nop\nmov R0, #3\ncall R0\nnop";
+        assert_eq!(optimize(input), assemble(input).unwrap());
+    }
+}
@@ -1,5 +1,10 @@
-use core::num::ParseIntError;
-use std::{collections::HashMap, num::TryFromIntError};
+use core::num::{IntErrorKind, ParseIntError};
+use core::ops::RangeInclusive;
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    num::TryFromIntError,
+};
 
 use procem::{
     register::{Register, RegisterError},
@@ -7,12 +12,17 @@ use procem::{
 };
 use thiserror::Error;
 
+use crate::AssemblerOptions;
 use crate::instruction::asm_instruction::{
-    ASMInstruction, ASMJumpInstruction, ASMRegOperandInstruction, ASMRotateInstruction, ASMShiftInstruction,
-    ASMSingleOperandInstruction, ASMSingleRegInstruction, ASMTwoOperandInstruction,
+    ASMHalfWordInstruction, ASMInstruction, ASMJumpInstruction, ASMPortInstruction, ASMPseudoInstruction,
+    ASMRegOperandInstruction, ASMRotateInstruction, ASMShiftInstruction, ASMSingleOperandInstruction,
+    ASMSingleRegInstruction, ASMTwoOperandInstruction,
 };
+use crate::instruction::jump_condition::JumpCondition;
 use crate::instruction::operand::Operand;
+use crate::instruction::stack_op::StackOp;
 use crate::instruction::{Instruction, asm_instruction::ASMNoArgInstruction};
+use crate::macros::MacroExpander;
 use crate::tokenizer::{Literal, Token};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -21,7 +31,91 @@ pub(crate) struct Parser<'a, W> {
     instructions: Vec<Instruction<W>>,
     errors: Option<Vec<ParserError>>,
     idx: usize,
-    labels: HashMap<&'a str, usize>,
+    labels: HashMap<LabelKey<'a>, usize>,
+    jumps: Vec<PendingJump<'a>>,
+    instruction_count: usize,
+    // first and last token of each parsed instruction
+    instruction_tokens: Vec<RangeInclusive<usize>>,
+    // mnemonic of the instruction that is currently parsed
+    instruction: &'a str,
+    // words of the data sections and the addresses of their labels
+    data: Vec<W>,
+    data_labels: HashMap<LabelKey<'a>, usize>,
+    // label of the `.entry` directive and the index of its token
+    entry: Option<(&'a str, usize)>,
+    options: AssemblerOptions,
+}
+
+/// The output of the [`Parser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Parsed<W> {
+    pub(crate) instructions: Vec<Instruction<W>>,
+    /// The range of input tokens each instruction originates from.
+    pub(crate) token_ranges: Vec<RangeInclusive<usize>>,
+    /// The words of all data sections, in the order they are defined.
+    pub(crate) data: Vec<W>,
+    /// The index of the instruction the program starts at, set with `.entry`.
+    pub(crate) entry: usize,
+}
+
+/// A jump whose destination is resolved after all labels are known, so that labels can be used before they are defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingJump<'a> {
+    instr: ASMJumpInstruction,
+    // relative jumps store the offset to the next instruction instead of the destination
+    relative: bool,
+    label: &'a str,
+    // index of the jump in the parsed instructions
+    instruction_idx: usize,
+    // index of the label token
+    idx: usize,
+}
+
+impl PendingJump<'_> {
+    /// Converts the index of the label to the destination of the jump.
+    ///
+    /// The offset of a relative jump is counted from the next instruction, as the program counter is
+    /// already incremented when the jump is executed. Backward jumps wrap around to a negative offset.
+    fn destination<W: Word>(&self, target: usize) -> Option<W> {
+        let target: W = target.try_into().ok()?;
+
+        if self.relative {
+            let next: W = (self.instruction_idx + 1).try_into().ok()?;
+            Some(target - next)
+        } else {
+            Some(target)
+        }
+    }
+
+    const fn instruction<W: Word>(&self, dest: W) -> Instruction<W> {
+        if self.relative {
+            Instruction::from_rel_jump_instruction(self.instr, dest)
+        } else {
+            Instruction::from_jump_instruction(self.instr, dest)
+        }
+    }
+}
+
+/// Label name that hashes and compares case-insensitively, so that labels can borrow from the source.
+#[derive(Debug, Clone, Copy)]
+struct LabelKey<'a>(&'a str);
+
+impl PartialEq for LabelKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(other.0)
+    }
+}
+
+impl Eq for LabelKey<'_> {}
+
+impl Hash for LabelKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for byte in self.0.bytes() {
+            state.write_u8(byte.to_ascii_uppercase());
+        }
+        // terminates the key like `str` does, so that consecutive keys cannot collide
+        state.write_u8(0xff);
+    }
 }
 
 impl<'a, W: Word> Parser<'a, W> {
@@ -32,46 +126,204 @@ impl<'a, W: Word> Parser<'a, W> {
             instructions: Vec::default(),
             idx: 0,
             labels: HashMap::default(),
+            jumps: Vec::default(),
+            instruction_count: 0,
+            instruction_tokens: Vec::default(),
+            instruction: "",
+            data: Vec::default(),
+            data_labels: HashMap::default(),
+            entry: None,
+            options: AssemblerOptions::default(),
         }
     }
 
-    pub(crate) fn parse(tokens: &'a [Token<'a>]) -> Result<Vec<Instruction<W>>, Vec<ParserError>> {
-        let mut parser = Parser::new(tokens);
+    /// Parses the tokens after expanding all macros.
+    ///
+    /// The token indices in the errors refer to the tokens after the expansion.
+    pub(crate) fn parse(
+        tokens: &'a [Token<'a>],
+        options: AssemblerOptions,
+    ) -> Result<Vec<Instruction<W>>, Vec<ParserError>> {
+        Self::parse_all(tokens, options).map(|parsed| parsed.instructions)
+    }
+
+    /// Parses the tokens like [`parse()`](Parser::parse()) and also returns the range of input tokens of each instruction
+    /// and the words of the data sections.
+    ///
+    /// An instruction from the body of a macro originates from the invocation of the macro.
+    pub(crate) fn parse_all(tokens: &'a [Token<'a>], options: AssemblerOptions) -> Result<Parsed<W>, Vec<ParserError>> {
+        let (tokens, origins) = MacroExpander::expand(tokens)?;
+        let mut parser = Parser::new(&tokens);
+        parser.options = options;
+        parser.collect_data();
         parser.run();
 
+        let entry = parser.resolve_entry();
+
         match parser.errors {
-            None => Ok(parser.instructions),
+            None => {
+                let token_ranges = parser
+                    .instruction_tokens
+                    .iter()
+                    .map(|range| origins[*range.start()]..=origins[*range.end()])
+                    .collect();
+                Ok(Parsed {
+                    instructions: parser.instructions,
+                    token_ranges,
+                    data: parser.data,
+                    entry,
+                })
+            }
             Some(err) => Err(err),
         }
     }
 
+    /// Collects the words and labels of all data sections before the instructions are parsed,
+    /// so that data labels can be used before they are defined.
+    ///
+    /// A data section starts with `.data` and ends with `.text` or the end of the program.
+    fn collect_data(&mut self) {
+        let tokens = self.tokens;
+
+        while self.idx < tokens.len() {
+            match &tokens[self.idx] {
+                Token::End => break,
+                token if is_directive(token, ".data") => self.collect_data_section(),
+                _ => (),
+            }
+            self.idx += 1;
+        }
+
+        self.idx = 0;
+    }
+
+    /// Collects the data section after the `.data` at the current index, stopping before the token that ends it.
+    fn collect_data_section(&mut self) {
+        let tokens = self.tokens;
+
+        while let Some(token) = tokens.get(self.idx + 1) {
+            match token {
+                Token::End => return,
+                token if is_directive(token, ".text") => return,
+                token if is_directive(token, ".data") => (),
+                Token::Newline => (),
+                token if is_directive(token, ".word") => {
+                    self.idx += 1;
+                    self.collect_words();
+                    continue;
+                }
+                Token::Label(label) => {
+                    if let Some(old_addr) = self.data_labels.insert(LabelKey(label), self.data.len()) {
+                        self.add_error(ParserError::DuplicateLabel {
+                            idx: self.data.len(),
+                            old_idx: old_addr,
+                        });
+                    }
+                }
+                token => self.add_error(ParserError::InvalidToken {
+                    idx: self.idx + 1,
+                    expected: "Label or .word",
+                    got: format!("{token:?}"),
+                }),
+            }
+            self.idx += 1;
+        }
+    }
+
+    /// Collects the comma separated literals after the `.word` at the current index. Strings add a word per character.
+    fn collect_words(&mut self) {
+        let tokens = self.tokens;
+        self.instruction = ".word";
+
+        loop {
+            self.idx += 1;
+            match tokens.get(self.idx) {
+                Some(Token::Literal(Literal::String(string))) => {
+                    self.data.extend(string.chars().map(|c| W::from(c as i32)));
+                }
+                Some(Token::Literal(lit)) => match Self::convert_lit_to_val(lit) {
+                    Ok(val) => self.data.push(val),
+                    Err(err) => self.add_error(err),
+                },
+                _ => return self.add_error(self.unexpected_token("Literal")),
+            }
+
+            if tokens.get(self.idx + 1) != Some(&Token::Comma) {
+                return;
+            }
+            self.idx += 1;
+        }
+    }
+
+    /// Skips the data section after the `.data` at the current index, as it was already collected.
+    fn skip_data_section(&mut self) {
+        let next = self.tokens[self.idx + 1..]
+            .iter()
+            .position(|token| matches!(token, Token::End) || is_directive(token, ".text"))
+            .map_or(self.tokens.len(), |len| self.idx + 1 + len);
+
+        // `run` advances to the next token
+        self.idx = next - 1;
+    }
+
     fn run(&mut self) {
-        let mut instruction_count = 0;
+        let tokens = self.tokens;
 
-        while self.idx < self.tokens.len() {
-            match &self.tokens[self.idx] {
+        while self.idx < tokens.len() {
+            match &tokens[self.idx] {
+                token if is_directive(token, ".data") => self.skip_data_section(),
+                token if is_directive(token, ".text") => (),
+                Token::Newline => (),
+                token if is_directive(token, ".word") => {
+                    self.add_error(ParserError::WordOutsideOfDataSection { idx: self.idx });
+                    self.recover(self.idx);
+                }
+                token if is_directive(token, ".entry") => self.expect_entry(),
                 Token::Label(label) => {
-                    if let Some(old_instruction_idx) = self.labels.insert(label, instruction_count) {
+                    if let Some(old_instruction_idx) = self.labels.insert(LabelKey(label), self.instruction_count) {
                         self.add_error(ParserError::DuplicateLabel {
-                            idx: instruction_count,
+                            idx: self.instruction_count,
                             old_idx: old_instruction_idx,
                         });
                     }
                 }
                 Token::Instruction(inst) => {
+                    let start = self.idx;
+                    let error_count = self.error_count();
+                    self.instruction = inst;
                     self.parse_instruction(inst);
-                    instruction_count += 1;
+                    self.instruction_count += 1;
+
+                    if self.error_count() == error_count && !self.is_statement_end(self.idx + 1) {
+                        self.add_error(ParserError::TooManyOperands {
+                            idx: self.idx + 1,
+                            inst: (*inst).to_string(),
+                        });
+                    }
+
+                    if self.error_count() > error_count {
+                        self.recover(start);
+                    } else {
+                        // an instruction with missing operands ends with the last token
+                        let end = self.idx.min(self.tokens.len() - 1);
+                        self.instruction_tokens.resize(self.instructions.len(), start..=end);
+                    }
                 }
                 Token::End => break,
-                token => self.add_error(ParserError::InvalidToken {
-                    idx: self.idx,
-                    expected: "Label or Instruction",
-                    got: format!("{token:?}"),
-                }),
+                token => {
+                    self.add_error(ParserError::InvalidToken {
+                        idx: self.idx,
+                        expected: "Label or Instruction",
+                        got: format!("{token:?}"),
+                    });
+                    self.recover(self.idx);
+                }
             }
 
             self.idx += 1;
         }
+
+        self.resolve_jumps();
     }
 
     #[inline]
@@ -79,20 +331,48 @@ impl<'a, W: Word> Parser<'a, W> {
         self.errors.get_or_insert_default().push(err);
     }
 
+    #[inline]
+    fn error_count(&self) -> usize {
+        self.errors.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Skips the rest of an erroneous statement that started at `start`, so that its remaining tokens do not cause
+    /// further errors. Parsing resumes at the end of the line or the next label or instruction,
+    /// even if the statement consumed it as an operand.
+    fn recover(&mut self, start: usize) {
+        let next = (start + 1..self.tokens.len())
+            .find(|&idx| self.is_statement_end(idx))
+            .unwrap_or(self.tokens.len());
+
+        // `run` advances to the next token
+        self.idx = next - 1;
+    }
+
     fn parse_instruction(&mut self, instruction: &str) {
-        match instruction.try_into() {
-            Ok(inst) => match inst {
+        match ASMInstruction::parse_predicated(instruction) {
+            Ok((inst, cond)) => match inst {
                 ASMInstruction::NoArg(inst) => self.instructions.push(match inst {
                     ASMNoArgInstruction::Nop => Instruction::Nop,
-                    ASMNoArgInstruction::Ret => Instruction::Ret,
+                    ASMNoArgInstruction::Ret => Instruction::Ret { cond },
+                    ASMNoArgInstruction::SAdd => Instruction::BinStack { op: StackOp::Add, cond },
+                    ASMNoArgInstruction::SSub => Instruction::BinStack { op: StackOp::Sub, cond },
+                    ASMNoArgInstruction::SMul => Instruction::BinStack { op: StackOp::Mul, cond },
+                    ASMNoArgInstruction::SDiv => Instruction::BinStack { op: StackOp::Div, cond },
                 }),
-                ASMInstruction::RegOperand(inst) => self.expect_reg_operand_instruction(inst),
-                ASMInstruction::Jump(inst) => self.expect_destination(inst),
-                ASMInstruction::TwoOperand(inst) => self.expect_two_operand_instruction(inst),
-                ASMInstruction::SingleOperand(inst) => self.expect_single_operand_instruction(inst),
-                ASMInstruction::SingleReg(inst) => self.expect_single_reg_instruction(inst),
-                ASMInstruction::Rotate(inst) => self.expect_rotate_instruction(inst),
-                ASMInstruction::Shift(inst) => self.expect_shift_instruction(inst),
+                ASMInstruction::RegOperand(inst) => self.expect_reg_operand_instruction(inst, cond),
+                ASMInstruction::MulHigh => self.expect_mul_high_instruction(cond),
+                ASMInstruction::Jump(inst) => self.expect_destination(inst, false),
+                ASMInstruction::JumpRel(inst) => self.expect_destination(inst, true),
+                ASMInstruction::TwoOperand(inst) => self.expect_two_operand_instruction(inst, cond),
+                ASMInstruction::SingleOperand(inst) => self.expect_single_operand_instruction(inst, cond),
+                ASMInstruction::SingleReg(inst) => self.expect_single_reg_instruction(inst, cond),
+                ASMInstruction::Rotate(inst) => self.expect_rotate_instruction(inst, cond),
+                ASMInstruction::Set(inst) => self.expect_set_instruction(inst),
+                ASMInstruction::Cmov(inst) => self.expect_cmov_instruction(inst),
+                ASMInstruction::Shift(inst) => self.expect_shift_instruction(inst, cond),
+                ASMInstruction::HalfWord(inst) => self.expect_half_word_instruction(inst, cond),
+                ASMInstruction::Port(inst) => self.expect_port_instruction(inst, cond),
+                ASMInstruction::Pseudo(inst) => self.expect_pseudo_instruction(inst, cond),
             },
             Err(()) => self.add_error(ParserError::UnknownInstruction {
                 idx: self.idx,
@@ -101,78 +381,210 @@ impl<'a, W: Word> Parser<'a, W> {
         }
     }
 
-    fn expect_destination(&mut self, instr: ASMJumpInstruction) {
+    fn expect_destination(&mut self, instr: ASMJumpInstruction, relative: bool) {
         self.idx += 1;
 
         if let Some(Token::Label(label)) = self.tokens.get(self.idx) {
-            match self.labels.get(label.as_str()) {
-                Some(&idx) => match idx.try_into() {
-                    Ok(idx) => {
-                        self.instructions.push(Instruction::from_jump_instruction(instr, idx));
-                    }
-                    Err(_) => {
-                        self.add_error(ParserError::LabelIndexToWordConversionFailed {
-                            idx: self.idx,
-                            label: label.clone(),
-                        });
-                    }
-                },
-                None => self.add_error(ParserError::LabelNotFound {
-                    idx: self.idx,
-                    label: label.clone(),
+            let jump = PendingJump {
+                instr,
+                relative,
+                label,
+                instruction_idx: self.instructions.len(),
+                idx: self.idx,
+            };
+            self.jumps.push(jump);
+            // the destination is set once all labels are known
+            self.instructions.push(jump.instruction(W::default()));
+        } else {
+            self.add_error(self.unexpected_token("Label"));
+        }
+    }
+
+    /// Stores the label after the `.entry` at the current index, which is resolved once all labels are known.
+    fn expect_entry(&mut self) {
+        let start = self.idx;
+        self.instruction = ".entry";
+        self.idx += 1;
+
+        let Some(Token::Label(label)) = self.tokens.get(self.idx) else {
+            self.add_error(self.unexpected_token("Label"));
+            return self.recover(start);
+        };
+
+        if self.entry.replace((label, self.idx)).is_some() {
+            self.add_error(ParserError::DuplicateEntry { idx: start });
+        }
+    }
+
+    /// Returns the index of the instruction the label of `.entry` points to, or 0 if there is no `.entry`.
+    ///
+    /// Like a jump, the entry can be the label after the last instruction, which ends the program immediately.
+    fn resolve_entry(&mut self) -> usize {
+        let Some((label, idx)) = self.entry else {
+            return 0;
+        };
+        let program_len = self.instruction_count;
+
+        match self.labels.get(&LabelKey(label)) {
+            Some(&target) if target <= program_len => target,
+            Some(&target) => {
+                self.add_error(ParserError::JumpTargetOutOfRange {
+                    label: label.to_string(),
+                    target,
+                    program_len,
+                });
+                0
+            }
+            None => {
+                self.add_error(ParserError::LabelNotFound {
+                    idx,
+                    label: label.to_string(),
+                });
+                0
+            }
+        }
+    }
+
+    /// Sets the destinations of all jumps to the instruction their label points to.
+    ///
+    /// A label after the last instruction is a valid destination, jumping to it ends the program.
+    fn resolve_jumps(&mut self) {
+        let program_len = self.instruction_count;
+
+        for jump in std::mem::take(&mut self.jumps) {
+            let Some(&target) = self.labels.get(&LabelKey(jump.label)) else {
+                self.add_error(ParserError::LabelNotFound {
+                    idx: jump.idx,
+                    label: jump.label.to_string(),
+                });
+                continue;
+            };
+
+            if target > program_len {
+                self.add_error(ParserError::JumpTargetOutOfRange {
+                    label: jump.label.to_string(),
+                    target,
+                    program_len,
+                });
+                continue;
+            }
+
+            match jump.destination(target) {
+                Some(dest) => self.instructions[jump.instruction_idx] = jump.instruction(dest),
+                None => self.add_error(ParserError::LabelIndexToWordConversionFailed {
+                    idx: jump.idx,
+                    label: jump.label.to_string(),
                 }),
             }
-        } else {
-            self.add_error(ParserError::InvalidToken {
-                idx: self.idx,
-                expected: "Label",
-                got: self.current_token_string(),
-            });
+        }
+    }
+
+    /// Parses tokens that consist of a single operand, e.g. `R0`, `#5` or `[R1 + #4]`.
+    pub(crate) fn parse_operand(tokens: &'a [Token<'a>]) -> Result<Operand<W>, ParserError> {
+        let mut parser = Parser::new(tokens);
+
+        let operand = match tokens.first() {
+            Some(Token::Register(reg)) => Operand::Register(reg.parse()?),
+            Some(Token::Literal(lit)) => Operand::Value(Self::convert_lit_to_val(lit)?),
+            Some(Token::OpenBracket) => parser.expect_indexed_operand()?,
+            Some(token) => {
+                return Err(ParserError::InvalidToken {
+                    idx: 0,
+                    expected: "Register, Literal or Indexed address",
+                    got: format!("{token:?}"),
+                });
+            }
+            None => return Err(ParserError::EmptyTokenList),
+        };
+
+        match parser.get_next() {
+            None => Ok(operand),
+            Some(_) => Err(ParserError::InvalidToken {
+                idx: parser.idx,
+                expected: "End of the operand",
+                got: parser.current_token_string(),
+            }),
         }
     }
 
     fn expect_register(&mut self) -> Result<Register, ParserError> {
         match self.get_next() {
             Some(Token::Register(reg)) => reg.parse::<Register>().map_err(ParserError::RegisterParsing),
-            _ => Err(ParserError::InvalidToken {
-                idx: self.idx,
-                expected: "Register",
-                got: self.current_token_string(),
-            }),
+            _ => Err(self.unexpected_token("Register")),
         }
     }
 
     fn expect_comma(&mut self) -> Result<(), ParserError> {
         match self.get_next() {
             Some(Token::Comma) => Ok(()),
-            _ => Err(ParserError::InvalidToken {
-                idx: self.idx,
-                expected: "Comma",
-                got: self.current_token_string(),
-            }),
+            _ => Err(self.unexpected_token("Comma")),
         }
     }
 
     fn expect_operand(&mut self) -> Result<Operand<W>, ParserError> {
-        match self.get_next() {
+        let tokens = self.tokens;
+
+        self.idx += 1;
+        match tokens.get(self.idx) {
             Some(Token::Register(reg)) => Ok(Operand::Register(reg.parse().map_err(ParserError::RegisterParsing)?)),
             Some(Token::Literal(lit)) => Ok(Operand::Value(Self::convert_lit_to_val(lit)?)),
-            _ => Err(ParserError::InvalidToken {
+            Some(Token::Label(label)) if self.data_labels.contains_key(&LabelKey(label)) => {
+                self.expect_data_address(label)
+            }
+            _ => Err(self.unexpected_token("Register or Literal")),
+        }
+    }
+
+    /// Parses the address operand of `LDR` and `STR`, which can also be an indexed address, e.g. `[R1 + #4]`.
+    fn expect_address_operand(&mut self) -> Result<Operand<W>, ParserError> {
+        if self.tokens.get(self.idx + 1) != Some(&Token::OpenBracket) {
+            return self.expect_operand();
+        }
+
+        self.idx += 1;
+        self.expect_indexed_operand()
+    }
+
+    /// Parses the rest of an indexed operand after the opening bracket, e.g. `R1 + #4]` or `R1]`.
+    fn expect_indexed_operand(&mut self) -> Result<Operand<W>, ParserError> {
+        let base = self.expect_register()?;
+
+        let offset = match self.get_next() {
+            Some(Token::CloseBracket) => return Ok(Operand::Indexed { base, offset: 0.into() }),
+            Some(Token::Plus) => self.expect_word()?,
+            _ => {
+                return Err(self.unexpected_token("Plus or CloseBracket"));
+            }
+        };
+
+        match self.get_next() {
+            Some(Token::CloseBracket) => Ok(Operand::Indexed { base, offset }),
+            _ => Err(self.unexpected_token("CloseBracket")),
+        }
+    }
+
+    /// Parses the address of the data label at the current index, optionally followed by an offset, e.g. `.table + #1`.
+    fn expect_data_address(&mut self, label: &str) -> Result<Operand<W>, ParserError> {
+        let addr: W = self.data_labels[&LabelKey(label)].try_into().map_err(|_| {
+            ParserError::LabelIndexToWordConversionFailed {
                 idx: self.idx,
-                expected: "Register or Literal",
-                got: self.current_token_string(),
-            }),
+                label: label.to_string(),
+            }
+        })?;
+
+        if self.tokens.get(self.idx + 1) != Some(&Token::Plus) {
+            return Ok(Operand::Value(addr));
         }
+
+        self.idx += 1;
+        let (addr, _) = addr.overflowing_add(self.expect_word()?);
+        Ok(Operand::Value(addr))
     }
 
     fn expect_word(&mut self) -> Result<W, ParserError> {
         match self.get_next() {
             Some(Token::Literal(lit)) => Ok(Self::convert_lit_to_val(lit)?),
-            _ => Err(ParserError::InvalidToken {
-                idx: self.idx,
-                expected: "Literal",
-                got: self.current_token_string(),
-            }),
+            _ => Err(self.unexpected_token("Literal")),
         }
     }
 
@@ -182,6 +594,34 @@ impl<'a, W: Word> Parser<'a, W> {
         self.tokens.get(self.idx)
     }
 
+    /// Returns true if the token at `idx` ends the current line, starts the next statement or there are no more tokens.
+    fn is_statement_end(&self, idx: usize) -> bool {
+        self.tokens.get(idx).is_none_or(|token| {
+            matches!(
+                token,
+                Token::Newline | Token::Label(_) | Token::Instruction(_) | Token::End
+            )
+        })
+    }
+
+    /// Returns the error for an unexpected token at the current index.
+    /// If the token starts the next statement, the current instruction is missing an operand.
+    fn unexpected_token(&self, expected: &'static str) -> ParserError {
+        if self.is_statement_end(self.idx) {
+            ParserError::MissingOperand {
+                idx: self.idx,
+                inst: self.instruction.to_string(),
+                expected,
+            }
+        } else {
+            ParserError::InvalidToken {
+                idx: self.idx,
+                expected,
+                got: self.current_token_string(),
+            }
+        }
+    }
+
     #[inline]
     fn current_token_string(&self) -> String {
         self.tokens
@@ -192,16 +632,27 @@ impl<'a, W: Word> Parser<'a, W> {
     fn convert_lit_to_val(lit: &Literal<'_>) -> Result<W, ParserError> {
         match lit {
             Literal::Char(s) => Ok((*s as i32).into()),
-            Literal::Binary(s) => W::from_str_radix(s, 2).map_err(ParserError::LiteralParsing),
+            Literal::Binary(s) => Self::parse_numeric_literal(s, 2),
             Literal::Boolean(s) => Ok(i32::from(*s).into()),
-            Literal::Decimal(s) => W::from_str_radix(s, 10).map_err(ParserError::LiteralParsing),
-            Literal::Hexadecimal(s) => W::from_str_radix(s, 16).map_err(ParserError::LiteralParsing),
-            Literal::Octal(s) => W::from_str_radix(s, 8).map_err(ParserError::LiteralParsing),
+            Literal::Decimal(s) => Self::parse_numeric_literal(s, 10),
+            Literal::Hexadecimal(s) => Self::parse_numeric_literal(s, 16),
+            Literal::Octal(s) => Self::parse_numeric_literal(s, 8),
             Literal::String(_) => Err(ParserError::CannotConvertStrToVal),
         }
     }
 
-    fn expect_reg_operand_instruction(&mut self, instr: ASMRegOperandInstruction) {
+    /// Parses a numeric literal, reporting literals that are too large for the word type as `LiteralOutOfRange`.
+    fn parse_numeric_literal(lit: &str, radix: u32) -> Result<W, ParserError> {
+        W::from_str_radix(lit, radix).map_err(|err| match err.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ParserError::LiteralOutOfRange {
+                literal: lit.to_string(),
+                word_bits: W::BITS,
+            },
+            _ => ParserError::LiteralParsing(err),
+        })
+    }
+
+    fn expect_reg_operand_instruction(&mut self, instr: ASMRegOperandInstruction, cond: JumpCondition) {
         let acc = match self.expect_register() {
             Ok(reg) => reg,
             Err(err) => return self.add_error(err),
@@ -211,36 +662,114 @@ impl<'a, W: Word> Parser<'a, W> {
             return self.add_error(err);
         }
 
-        let operand = match self.expect_operand() {
+        let operand = match instr {
+            ASMRegOperandInstruction::Ldr | ASMRegOperandInstruction::Str => self.expect_address_operand(),
+            _ => self.expect_operand(),
+        };
+        let operand = match operand {
+            Ok(op) => op,
+            Err(err) => return self.add_error(err),
+        };
+
+        self.instructions
+            .push(Instruction::from_reg_operand_instruction(instr, acc, operand, cond));
+    }
+
+    fn expect_mul_high_instruction(&mut self, cond: JumpCondition) {
+        let acc = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
+        if let Err(err) = self.expect_comma() {
+            return self.add_error(err);
+        }
+
+        let dst_hi = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
+        if let Err(err) = self.expect_comma() {
+            return self.add_error(err);
+        }
+
+        let rhs = match self.expect_operand() {
             Ok(op) => op,
             Err(err) => return self.add_error(err),
         };
 
+        self.instructions.push(Instruction::MulHigh { acc, rhs, dst_hi, cond });
+    }
+
+    fn expect_single_reg_instruction(&mut self, instr: ASMSingleRegInstruction, cond: JumpCondition) {
+        let reg = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
         self.instructions
-            .push(Instruction::from_reg_operand_instruction(instr, acc, operand));
+            .push(Instruction::from_single_reg_instruction(instr, reg, cond));
     }
 
-    fn expect_single_reg_instruction(&mut self, instr: ASMSingleRegInstruction) {
+    /// Parses a pseudo-instruction, e.g. `CLR R0`, and emits the real instruction it stands for.
+    fn expect_pseudo_instruction(&mut self, instr: ASMPseudoInstruction, cond: JumpCondition) {
         let reg = match self.expect_register() {
             Ok(reg) => reg,
             Err(err) => return self.add_error(err),
         };
 
         self.instructions
-            .push(Instruction::from_single_reg_instruction(instr, reg));
+            .push(Instruction::from_pseudo_instruction(instr, reg, cond));
+    }
+
+    fn expect_set_instruction(&mut self, instr: ASMJumpInstruction) {
+        let reg = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
+        self.instructions.push(Instruction::SetIf {
+            reg,
+            condition: JumpCondition::from_jump_instruction(instr),
+        });
+    }
+
+    fn expect_cmov_instruction(&mut self, instr: ASMJumpInstruction) {
+        let to = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
+        if let Err(err) = self.expect_comma() {
+            return self.add_error(err);
+        }
+
+        let from = match self.expect_operand() {
+            Ok(op) => op,
+            Err(err) => return self.add_error(err),
+        };
+
+        self.instructions.push(Instruction::CmovIf {
+            to,
+            from,
+            condition: JumpCondition::from_jump_instruction(instr),
+        });
     }
 
-    fn expect_single_operand_instruction(&mut self, instr: ASMSingleOperandInstruction) {
+    fn expect_single_operand_instruction(&mut self, instr: ASMSingleOperandInstruction, cond: JumpCondition) {
         let operand = match self.expect_operand() {
             Ok(op) => op,
             Err(err) => return self.add_error(err),
         };
 
         self.instructions
-            .push(Instruction::from_single_operand_instruction(instr, operand));
+            .push(Instruction::from_single_operand_instruction(instr, operand, cond));
     }
 
-    fn expect_two_operand_instruction(&mut self, instr: ASMTwoOperandInstruction) {
+    fn expect_two_operand_instruction(&mut self, instr: ASMTwoOperandInstruction, cond: JumpCondition) {
+        let idx = self.idx;
+
         let lhs = match self.expect_operand() {
             Ok(op) => op,
             Err(err) => return self.add_error(err),
@@ -255,11 +784,16 @@ impl<'a, W: Word> Parser<'a, W> {
             Err(err) => return self.add_error(err),
         };
 
+        // the result of comparing two literals is known before running the program, so a register was likely forgotten
+        if matches!((lhs, rhs), (Operand::Value(_), Operand::Value(_))) && !self.options.allow_constant_comparisons {
+            return self.add_error(ParserError::ComparisonOfConstants { idx });
+        }
+
         self.instructions
-            .push(Instruction::from_two_operand_instruction(instr, lhs, rhs));
+            .push(Instruction::from_two_operand_instruction(instr, lhs, rhs, cond));
     }
 
-    fn expect_shift_instruction(&mut self, instr: ASMShiftInstruction) {
+    fn expect_shift_instruction(&mut self, instr: ASMShiftInstruction, cond: JumpCondition) {
         let register = match self.expect_register() {
             Ok(reg) => reg,
             Err(err) => return self.add_error(err),
@@ -269,16 +803,16 @@ impl<'a, W: Word> Parser<'a, W> {
             return self.add_error(err);
         }
 
-        let literal = match self.expect_word() {
-            Ok(lit) => lit,
+        let amount = match self.expect_operand() {
+            Ok(op) => op,
             Err(err) => return self.add_error(err),
         };
 
         self.instructions
-            .push(Instruction::from_shift_instruction(instr, register, literal));
+            .push(Instruction::from_shift_instruction(instr, register, amount, cond));
     }
 
-    fn expect_rotate_instruction(&mut self, instr: ASMRotateInstruction) {
+    fn expect_half_word_instruction(&mut self, instr: ASMHalfWordInstruction, cond: JumpCondition) {
         let register = match self.expect_register() {
             Ok(reg) => reg,
             Err(err) => return self.add_error(err),
@@ -293,14 +827,68 @@ impl<'a, W: Word> Parser<'a, W> {
             Err(err) => return self.add_error(err),
         };
 
-        let literal: usize = literal.into();
-        let literal: u32 = match literal.try_into() {
-            Ok(lit) => lit,
-            Err(err) => return self.add_error(ParserError::CannotConvertLiteralToU32 { literal, err }),
+        self.instructions
+            .push(Instruction::from_half_word_instruction(instr, register, literal, cond));
+    }
+
+    fn expect_port_instruction(&mut self, instr: ASMPortInstruction, cond: JumpCondition) {
+        let instruction = match instr {
+            ASMPortInstruction::In => {
+                let to = match self.expect_register() {
+                    Ok(reg) => reg,
+                    Err(err) => return self.add_error(err),
+                };
+
+                if let Err(err) = self.expect_comma() {
+                    return self.add_error(err);
+                }
+
+                let port = match self.expect_word() {
+                    Ok(lit) => lit,
+                    Err(err) => return self.add_error(err),
+                };
+
+                Instruction::In { to, port, cond }
+            }
+            ASMPortInstruction::Out => {
+                let port = match self.expect_word() {
+                    Ok(lit) => lit,
+                    Err(err) => return self.add_error(err),
+                };
+
+                if let Err(err) = self.expect_comma() {
+                    return self.add_error(err);
+                }
+
+                let from = match self.expect_operand() {
+                    Ok(op) => op,
+                    Err(err) => return self.add_error(err),
+                };
+
+                Instruction::Out { from, port, cond }
+            }
+        };
+
+        self.instructions.push(instruction);
+    }
+
+    fn expect_rotate_instruction(&mut self, instr: ASMRotateInstruction, cond: JumpCondition) {
+        let register = match self.expect_register() {
+            Ok(reg) => reg,
+            Err(err) => return self.add_error(err),
+        };
+
+        if let Err(err) = self.expect_comma() {
+            return self.add_error(err);
+        }
+
+        let amount = match self.expect_operand() {
+            Ok(op) => op,
+            Err(err) => return self.add_error(err),
         };
 
         self.instructions
-            .push(Instruction::from_rotate_instruction(instr, register, literal));
+            .push(Instruction::from_rotate_instruction(instr, register, amount, cond));
     }
 }
 
@@ -318,10 +906,22 @@ pub enum ParserError {
     DuplicateLabel { idx: usize, old_idx: usize },
     #[error("Unkown instruction at idx {idx}: {inst}")]
     UnknownInstruction { idx: usize, inst: String },
+    #[error("Instruction {inst} is missing an operand at idx {idx}. Expected: {expected}")]
+    MissingOperand {
+        idx: usize,
+        inst: String,
+        expected: &'static str,
+    },
+    #[error("Instruction {inst} has too many operands. The first one is at idx {idx}.")]
+    TooManyOperands { idx: usize, inst: String },
+    #[error(".word at idx {idx} is not in a data section. Start a data section with .data.")]
+    WordOutsideOfDataSection { idx: usize },
     #[error("Error while parsing register.")]
     RegisterParsing(#[from] RegisterError),
     #[error("Error while parsing literal.")]
     LiteralParsing(#[from] ParseIntError),
+    #[error("Literal {literal} does not fit into a {word_bits}-bit word.")]
+    LiteralOutOfRange { literal: String, word_bits: u32 },
     #[error("Strings cannot be converted to numeric values directly. You could use a hex representation instead.")]
     CannotConvertStrToVal,
     #[error("Cannot convert literal {literal} to u32. This is likely due to the literal being too large.\n{err}")]
@@ -330,4 +930,277 @@ pub enum ParserError {
     LabelNotFound { idx: usize, label: String },
     #[error("Index {idx} of label \".{label}\" cannot be converted to word.")]
     LabelIndexToWordConversionFailed { idx: usize, label: String },
+    #[error(
+        "Label \".{label}\" points to instruction {target}, which is past the end of the program ({program_len} instructions)."
+    )]
+    JumpTargetOutOfRange {
+        label: String,
+        target: usize,
+        program_len: usize,
+    },
+    #[error(
+        "Comparison of two literals at idx {idx}. Use a register as an operand or allow constant comparisons in the AssemblerOptions."
+    )]
+    ComparisonOfConstants { idx: usize },
+    #[error("Macro \"{name}\" has no .endm.")]
+    UnterminatedMacro { name: String },
+    #[error("Macro \"{name}\" is defined more than once.")]
+    DuplicateMacro { name: String },
+    #[error("Argument %{arg} of macro \"{name}\" is missing.")]
+    MacroArgumentMissing { name: String, arg: usize },
+    #[error("Macro \"{name}\" exceeds the limit of {limit} nested macro invocations.")]
+    MacroRecursionLimit { name: String, limit: usize },
+    #[error("Unknown jump condition: {cond}")]
+    UnknownJumpCondition { cond: String },
+    #[error("Second .entry at idx {idx}. A program has only one entry point.")]
+    DuplicateEntry { idx: usize },
+}
+
+/// Returns true if the token is the directive with the given name, e.g. `.data`.
+fn is_directive(token: &Token<'_>, name: &str) -> bool {
+    matches!(token, Token::Label(label) if label.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+    use procem::word::I32;
+
+    #[test]
+    fn test_recover_after_error() {
+        let tokens = Tokenizer::tokenize(
+            "
+            mov [R0], R1
+            add R1, #2
+            sub R2 R3, #1
+            push
+            nop
+            ",
+        )
+        .unwrap();
+        let mut parser = Parser::<I32>::new(&tokens);
+        parser.run();
+
+        assert_eq!(
+            parser.errors,
+            Some(vec![
+                ParserError::InvalidToken {
+                    idx: 1,
+                    expected: "Register",
+                    got: "OpenBracket".to_string(),
+                },
+                ParserError::InvalidToken {
+                    idx: 14,
+                    expected: "Comma",
+                    got: "Register(\"R3\")".to_string(),
+                },
+                ParserError::MissingOperand {
+                    idx: 19,
+                    inst: "push".to_string(),
+                    expected: "Register or Literal",
+                },
+            ])
+        );
+        // the valid instructions are still parsed, including the one consumed as an operand
+        assert_eq!(parser.instructions.len(), 2);
+        assert_eq!(parser.instruction_count, 5);
+    }
+
+    #[test]
+    fn test_too_many_operands() {
+        let tokens = Tokenizer::tokenize("mov R0, #5, #6\nnop\nnot R1 R2").unwrap();
+
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, AssemblerOptions::default()),
+            Err(vec![
+                ParserError::TooManyOperands {
+                    idx: 4,
+                    inst: "mov".to_string(),
+                },
+                ParserError::TooManyOperands {
+                    idx: 11,
+                    inst: "not".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_missing_operand() {
+        let tokens = Tokenizer::tokenize("add R0,\nnop\nmovl R1").unwrap();
+
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, AssemblerOptions::default()),
+            Err(vec![
+                ParserError::MissingOperand {
+                    idx: 3,
+                    inst: "add".to_string(),
+                    expected: "Register or Literal",
+                },
+                ParserError::MissingOperand {
+                    idx: 8,
+                    inst: "movl".to_string(),
+                    expected: "Comma",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_statements_end_at_line_breaks() {
+        // the label on the next line is not taken as the missing operand
+        let tokens = Tokenizer::tokenize(
+            "jmp
+.target
+nop",
+        )
+        .unwrap();
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, AssemblerOptions::default()),
+            Err(vec![ParserError::MissingOperand {
+                idx: 1,
+                inst: "jmp".to_string(),
+                expected: "Label",
+            }])
+        );
+
+        // the instruction after a line with a surplus operand is still parsed
+        let tokens = Tokenizer::tokenize(
+            "mov R0, #1, #2
+nop",
+        )
+        .unwrap();
+        let mut parser = Parser::<I32>::new(&tokens);
+        parser.run();
+        assert_eq!(parser.instructions.last(), Some(&Instruction::Nop));
+    }
+
+    #[test]
+    fn test_data_sections() {
+        let tokens = Tokenizer::tokenize(
+            "
+            mov R0, .after
+            .data
+            .table
+            .word #1, #2, #3
+            .greeting
+            .word #\"hi\", #0
+            .text
+            ldr R1, .table + #2
+            .data
+            .after
+            ",
+        )
+        .unwrap();
+        let parsed = Parser::<I32>::parse_all(&tokens, AssemblerOptions::default()).unwrap();
+
+        assert_eq!(parsed.data, [1, 2, 3, 'h' as i32, 'i' as i32, 0].map(Into::into));
+        assert_eq!(
+            parsed.instructions,
+            [
+                Instruction::Mov {
+                    to: Register::R0,
+                    from: Operand::Value(6.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+                Instruction::Ldr {
+                    reg: Register::R1,
+                    addr: Operand::Value(2.into()),
+                    cond: JumpCondition::Unconditional,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pseudo_instructions() {
+        let parse = |input| Parser::<I32>::parse(&Tokenizer::tokenize(input).unwrap(), AssemblerOptions::default());
+
+        assert_eq!(parse("clr R0"), parse("mov R0, #0"));
+        assert!(parse("clrne R3").is_ok());
+        assert_eq!(parse("clrne R3"), parse("movne R3, #0"));
+        assert_eq!(parse("tst R1"), parse("cmp R1, #0"));
+        assert_eq!(
+            parse("tst #1"),
+            Err(vec![ParserError::InvalidToken {
+                idx: 1,
+                expected: "Register",
+                got: "Literal(Decimal(\"1\"))".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_word_outside_of_data_section() {
+        let tokens = Tokenizer::tokenize("nop\n.word #1\nnop").unwrap();
+
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, AssemblerOptions::default()),
+            Err(vec![ParserError::WordOutsideOfDataSection { idx: 2 }])
+        );
+    }
+
+    #[test]
+    fn test_comparison_of_constants() {
+        let tokens = Tokenizer::tokenize("nop\ncmp #1, #2").unwrap();
+
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, AssemblerOptions::default()),
+            Err(vec![ParserError::ComparisonOfConstants { idx: 2 }])
+        );
+
+        let options = AssemblerOptions {
+            allow_constant_comparisons: true,
+        };
+        assert_eq!(
+            Parser::<I32>::parse(&tokens, options).map(|instructions| instructions.len()),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let parse_entry = |input| {
+            let tokens = Tokenizer::tokenize(input).unwrap();
+            Parser::<I32>::parse_all(&tokens, AssemblerOptions::default()).map(|parsed| parsed.entry)
+        };
+
+        assert_eq!(parse_entry("nop\nnop"), Ok(0));
+        assert_eq!(parse_entry(".entry .main\nnop\nnop\n.main\nnop"), Ok(2));
+        assert_eq!(parse_entry("nop\n.main\nnop\n.ENTRY .main"), Ok(1));
+        assert_eq!(
+            parse_entry(".entry .main\nnop"),
+            Err(vec![ParserError::LabelNotFound {
+                idx: 1,
+                label: ".main".to_string(),
+            }])
+        );
+        assert_eq!(
+            parse_entry(".entry .a\n.a\nnop\n.entry .a"),
+            Err(vec![ParserError::DuplicateEntry { idx: 7 }])
+        );
+        assert!(matches!(
+            parse_entry(".entry #1").unwrap_err()[..],
+            [ParserError::InvalidToken { idx: 1, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_jump_target_out_of_range() {
+        let tokens = [Token::Instruction("jmp"), Token::Label(".end")];
+        let mut parser = Parser::<I32>::new(&tokens);
+        // a label can only point past the end of the program if its index is wrong
+        parser.labels.insert(LabelKey(".end"), 2);
+        parser.run();
+
+        assert_eq!(
+            parser.errors,
+            Some(vec![ParserError::JumpTargetOutOfRange {
+                label: ".end".to_string(),
+                target: 2,
+                program_len: 1,
+            }])
+        );
+    }
 }
@@ -1,12 +1,26 @@
+use core::ops::Range;
+use std::borrow::Cow;
+
 use thiserror::Error;
 
+use crate::parser::ParserError;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Token<'a> {
-    Label(String),
-    Register(String),
+    // Labels, registers and instructions borrow from the input and keep their original case.
+    // Case-insensitive comparisons are done by the parser.
+    Label(&'a str),
+    Register(&'a str),
     Literal(Literal<'a>),
-    Instruction(String),
+    Instruction(&'a str),
+    // Positional argument in the body of a macro, e.g. `%1`.
+    MacroArgument(usize),
     Comma,
+    OpenBracket,
+    CloseBracket,
+    Plus,
+    // A line break, which ends a statement. Consecutive line breaks result in a single token.
+    Newline,
     End,
 }
 
@@ -17,12 +31,81 @@ pub(crate) enum Literal<'a> {
     Hexadecimal(&'a str),
     Octal(&'a str),
     Boolean(bool),
-    String(&'a str),
+    // Borrows from the input unless the string contains escape sequences.
+    String(Cow<'a, str>),
     Char(char),
 }
 
+/// A token of the assembly language together with its position in the source, see [`tokenize()`](crate::tokenize()).
+///
+/// Unlike the tokens used by the assembler it owns its text, so it can outlive the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PublicToken {
+    pub kind: TokenKind,
+    /// The text of the token as written in the source.
+    pub text: String,
+    /// The byte range of the token in the source.
+    pub span: Range<usize>,
+}
+
+/// The kind of a [`PublicToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenKind {
+    Label,
+    Register,
+    Literal,
+    Instruction,
+    MacroArgument,
+    Comma,
+    OpenBracket,
+    CloseBracket,
+    Plus,
+    Newline,
+    End,
+}
+
+impl PublicToken {
+    /// Converts the token back into a token of the assembler by tokenizing its text again.
+    /// The text has to be a single token of the same kind.
+    pub(crate) fn to_token(&self, idx: usize) -> Result<Token<'_>, ParserError> {
+        match Tokenizer::tokenize(&self.text).as_deref() {
+            // a line break on its own is not a token, as leading line breaks are skipped
+            Ok([]) if self.kind == TokenKind::Newline && self.text.contains('\n') => Ok(Token::Newline),
+            Ok([token]) if TokenKind::from(token) == self.kind => Ok(token.clone()),
+            _ => Err(ParserError::InvalidToken {
+                idx,
+                expected: "Single token of the given kind",
+                got: self.text.clone(),
+            }),
+        }
+    }
+}
+
+impl From<&Token<'_>> for TokenKind {
+    fn from(token: &Token<'_>) -> Self {
+        match token {
+            Token::Label(_) => Self::Label,
+            Token::Register(_) => Self::Register,
+            Token::Literal(_) => Self::Literal,
+            Token::Instruction(_) => Self::Instruction,
+            Token::MacroArgument(_) => Self::MacroArgument,
+            Token::Comma => Self::Comma,
+            Token::OpenBracket => Self::OpenBracket,
+            Token::CloseBracket => Self::CloseBracket,
+            Token::Plus => Self::Plus,
+            Token::Newline => Self::Newline,
+            Token::End => Self::End,
+        }
+    }
+}
+
+/// Tokens with the range in the input of each token.
+type SpannedTokens<'a> = (Vec<Token<'a>>, Vec<Range<usize>>);
+
 pub(crate) struct Tokenizer<'a> {
     tokens: Vec<Token<'a>>,
+    // the range in the input of each token
+    spans: Vec<Range<usize>>,
     curr_idx: usize,
     token_start_idx: usize,
     input: &'a str,
@@ -30,10 +113,11 @@ pub(crate) struct Tokenizer<'a> {
     errors: Option<Vec<TokenizerError>>,
 }
 
-impl Tokenizer<'_> {
-    const fn from(input: &str) -> Tokenizer<'_> {
+impl<'a> Tokenizer<'a> {
+    const fn from(input: &'a str) -> Self {
         Tokenizer {
             tokens: Vec::new(),
+            spans: Vec::new(),
             curr_idx: 0,
             token_start_idx: 0,
             input,
@@ -43,25 +127,42 @@ impl Tokenizer<'_> {
     }
 
     pub(crate) fn tokenize(input: &str) -> Result<Vec<Token<'_>>, Vec<TokenizerError>> {
+        Self::tokenize_with_spans(input).map(|(tokens, _)| tokens)
+    }
+
+    /// Tokenizes the input and also returns the range in the input of each token.
+    pub(crate) fn tokenize_with_spans(input: &str) -> Result<SpannedTokens<'_>, Vec<TokenizerError>> {
         let mut tokenizer = Tokenizer::from(input);
 
         tokenizer.run();
 
         match tokenizer.errors {
             Some(errors) => Err(errors),
-            None => Ok(tokenizer.tokens),
+            None => Ok((tokenizer.tokens, tokenizer.spans)),
         }
     }
 
     fn run(&mut self) {
         while self.curr_idx < self.input_len {
             self.token_start_idx = self.curr_idx;
+            let start = self.curr_idx;
+            let token_count = self.tokens.len();
+            let error_count = self.error_count();
 
             match self.get_curr_char() {
                 '.' => self.expect_label(),
-                'R' => self.expect_register(),
+                'R' if self.is_next_char_numeric() => self.expect_register(),
                 '#' => self.expect_literal(),
+                '%' => self.expect_macro_argument(),
                 ',' => self.expect_comma(),
+                '[' => self.expect_single_char(Token::OpenBracket),
+                ']' => self.expect_single_char(Token::CloseBracket),
+                '+' => self.expect_single_char(Token::Plus),
+                '\n' => {
+                    self.add_newline(self.curr_idx);
+                    self.curr_idx += 1;
+                    continue;
+                }
                 c if c.is_alphabetic() => self.expect_instruction(),
                 c if c.is_whitespace() => self.curr_idx += 1,
                 c => {
@@ -72,9 +173,52 @@ impl Tokenizer<'_> {
                     });
                 }
             }
+
+            // skip the rest of a malformed token, so that the following tokens are still checked
+            if self.error_count() > error_count {
+                self.skip_to_whitespace();
+            }
+
+            if self.tokens.len() > token_count {
+                self.add_span(start);
+            }
         }
     }
 
+    fn error_count(&self) -> usize {
+        self.errors.as_ref().map_or(0, Vec::len)
+    }
+
+    fn skip_to_whitespace(&mut self) {
+        while self.curr_idx < self.input_len && !self.get_curr_char().is_whitespace() {
+            self.curr_idx += 1;
+        }
+    }
+
+    /// Records the range of the token starting at `start`.
+    /// Some literals move past the whitespace after them, which does not belong to the token.
+    /// A line break in that whitespace is still added as a token.
+    fn add_span(&mut self, start: usize) {
+        let end = self.curr_idx.min(self.input_len);
+        let len = self.input[start..end].trim_end().len();
+        self.spans.push(start..start + len);
+
+        if let Some(offset) = self.input[start + len..end].find('\n') {
+            self.add_newline(start + len + offset);
+        }
+    }
+
+    /// Adds a line break at `idx` unless it follows another line break or is at the start of the input,
+    /// as empty lines do not contain statements.
+    fn add_newline(&mut self, idx: usize) {
+        if matches!(self.tokens.last(), None | Some(Token::Newline)) {
+            return;
+        }
+
+        self.tokens.push(Token::Newline);
+        self.spans.push(idx..idx + 1);
+    }
+
     #[inline]
     fn add_error(&mut self, err: TokenizerError) {
         self.errors.get_or_insert_default().push(err);
@@ -91,12 +235,22 @@ impl Tokenizer<'_> {
         )
     }
 
+    fn is_next_char_numeric(&self) -> bool {
+        self.input.chars().nth(self.curr_idx + 1).is_some_and(char::is_numeric)
+    }
+
+    /// Whitespace, a comma (e.g. in `cmp #1, R0`) and a closing bracket (e.g. in `[R0 + #4]`) end a literal.
+    fn is_curr_char_token_end(&self) -> bool {
+        let c = self.get_curr_char();
+        c.is_whitespace() || c == ',' || c == ']'
+    }
+
     fn set_curr_idx_to_token_end(&mut self) {
-        if self.get_curr_char().is_whitespace() {
+        if self.is_curr_char_token_end() {
             return;
         }
 
-        while self.curr_idx < self.input_len && !self.get_curr_char().is_whitespace() {
+        while self.curr_idx < self.input_len && !self.is_curr_char_token_end() {
             self.curr_idx += 1;
         }
 
@@ -110,9 +264,8 @@ impl Tokenizer<'_> {
             self.curr_idx += 1;
         }
 
-        self.tokens.push(Token::Label(
-            self.input[self.token_start_idx..self.curr_idx].to_uppercase(),
-        ));
+        self.tokens
+            .push(Token::Label(&self.input[self.token_start_idx..self.curr_idx]));
     }
 
     fn expect_instruction(&mut self) {
@@ -122,10 +275,13 @@ impl Tokenizer<'_> {
             self.curr_idx += 1;
         }
 
-        let inst = self.input[self.token_start_idx..self.curr_idx].to_uppercase();
+        let inst = &self.input[self.token_start_idx..self.curr_idx];
 
-        let token = if inst == "END" {
+        // the special registers are words like instructions, so they are recognized here
+        let token = if inst.eq_ignore_ascii_case("END") {
             Token::End
+        } else if inst.eq_ignore_ascii_case("PC") || inst.eq_ignore_ascii_case("SP") {
+            Token::Register(inst)
         } else {
             Token::Instruction(inst)
         };
@@ -140,19 +296,42 @@ impl Tokenizer<'_> {
             self.curr_idx += 1;
         }
 
-        self.tokens.push(Token::Register(
-            self.input[self.token_start_idx..self.curr_idx].to_uppercase(),
-        ));
+        self.tokens
+            .push(Token::Register(&self.input[self.token_start_idx..self.curr_idx]));
+    }
+
+    fn expect_macro_argument(&mut self) {
+        self.curr_idx += 1;
+
+        while self.curr_idx < self.input_len && self.get_curr_char().is_ascii_digit() {
+            self.curr_idx += 1;
+        }
+
+        // +1 to ignore the prefix %
+        match self.input[self.token_start_idx + 1..self.curr_idx].parse() {
+            Ok(arg) => self.tokens.push(Token::MacroArgument(arg)),
+            Err(_) => self.add_error(TokenizerError::MacroArgument {
+                idx: self.token_start_idx,
+            }),
+        }
     }
 
     fn expect_comma(&mut self) {
-        self.tokens.push(Token::Comma);
+        self.expect_single_char(Token::Comma);
+    }
+
+    fn expect_single_char(&mut self, token: Token<'a>) {
+        self.tokens.push(token);
         self.curr_idx += 1;
     }
 
     fn expect_literal(&mut self) {
         self.curr_idx += 1;
 
+        if self.curr_idx == self.input_len {
+            return self.add_error(TokenizerError::Literal { idx: self.curr_idx });
+        }
+
         match self.get_curr_char() {
             '\'' => self.expect_char_literal(),
             '"' => self.expect_string_literal(),
@@ -166,15 +345,56 @@ impl Tokenizer<'_> {
         self.curr_idx += 1;
     }
 
+    /// Returns the current character without changing its case.
+    fn get_raw_char(&self) -> Option<char> {
+        self.input.chars().nth(self.curr_idx)
+    }
+
+    /// Reads the escape sequence starting with the backslash at the current index, e.g. `\n` or `\x41`,
+    /// and moves to its last character.
+    fn expect_escape(&mut self) -> Option<char> {
+        let start = self.curr_idx;
+        self.curr_idx += 1;
+
+        let c = match self.get_raw_char() {
+            Some('n') => Some('\n'),
+            Some('t') => Some('\t'),
+            Some('r') => Some('\r'),
+            Some('0') => Some('\0'),
+            Some(c @ ('\\' | '\'' | '"')) => Some(c),
+            Some('x') => {
+                let digits = self.input.get(self.curr_idx + 1..self.curr_idx + 3);
+                self.curr_idx += 2;
+                digits
+                    .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .map(char::from)
+            }
+            _ => None,
+        };
+
+        if c.is_none() {
+            self.add_error(TokenizerError::Escape { idx: start });
+        }
+        c
+    }
+
     fn expect_char_literal(&mut self) {
         self.curr_idx += 1;
 
-        let c = self.get_curr_char();
+        let c = match self.get_raw_char() {
+            Some('\\') => match self.expect_escape() {
+                Some(c) => c,
+                None => return,
+            },
+            Some(_) => self.get_curr_char(),
+            None => return self.add_error(TokenizerError::CharLiteral { idx: self.curr_idx }),
+        };
 
         self.curr_idx += 1;
 
-        match self.get_curr_char() {
-            '\'' => self.tokens.push(Token::Literal(Literal::Char(c))),
+        match self.get_raw_char() {
+            Some('\'') => self.tokens.push(Token::Literal(Literal::Char(c))),
             _ => self.add_error(TokenizerError::CharLiteral { idx: self.curr_idx }),
         }
     }
@@ -182,40 +402,64 @@ impl Tokenizer<'_> {
     fn expect_string_literal(&mut self) {
         self.curr_idx += 1;
 
-        while self.get_curr_char() != '"' {
+        // +2 to ignore the prefix #"
+        let start = self.token_start_idx + 2;
+        // only allocated once an escape sequence is found
+        let mut unescaped: Option<String> = None;
+
+        loop {
+            match self.get_raw_char() {
+                Some('"') => break,
+                Some('\\') => {
+                    let escaped = unescaped.get_or_insert_with(|| self.input[start..self.curr_idx].to_string());
+                    if let Some(c) = self.expect_escape() {
+                        escaped.push(c);
+                    }
+                }
+                Some(c) => {
+                    if let Some(unescaped) = &mut unescaped {
+                        unescaped.push(c);
+                    }
+                }
+                None => {
+                    return self.add_error(TokenizerError::StringLiteral {
+                        idx: self.token_start_idx,
+                    });
+                }
+            }
             self.curr_idx += 1;
         }
 
-        // +2 to ignore the prefix #"
-        self.tokens.push(Token::Literal(Literal::String(
-            &self.input[self.token_start_idx + 2..self.curr_idx],
-        )));
+        let literal = unescaped.map_or_else(|| Cow::Borrowed(&self.input[start..self.curr_idx]), Cow::Owned);
+        self.tokens.push(Token::Literal(Literal::String(literal)));
     }
 
     fn expect_numeric_literal(&mut self) {
         let literal = if self.get_curr_char() == '0' {
             self.curr_idx += 1;
             self.token_start_idx = self.curr_idx;
-            match self.get_curr_char() {
-                'B' => {
+            match self.get_raw_char().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => {
                     self.set_curr_idx_to_token_end();
                     Literal::Binary(&self.input[self.token_start_idx + 1..=self.curr_idx])
                 }
-                'X' => {
+                Some('X') => {
                     self.set_curr_idx_to_token_end();
                     Literal::Hexadecimal(&self.input[self.token_start_idx + 1..=self.curr_idx])
                 }
-                'O' => {
+                Some('O') => {
                     self.set_curr_idx_to_token_end();
                     Literal::Octal(&self.input[self.token_start_idx + 1..=self.curr_idx])
                 }
-                'D' => {
+                Some('D') => {
                     self.set_curr_idx_to_token_end();
                     Literal::Decimal(&self.input[self.token_start_idx + 1..=self.curr_idx])
                 }
                 _ => {
+                    // go back to the leading zero, so that the literal cannot be empty
+                    self.curr_idx -= 1;
                     self.set_curr_idx_to_token_end();
-                    Literal::Decimal(&self.input[self.token_start_idx - 1..self.curr_idx])
+                    Literal::Decimal(&self.input[self.token_start_idx - 1..=self.curr_idx])
                 }
             }
         } else {
@@ -230,14 +474,15 @@ impl Tokenizer<'_> {
         self.curr_idx += 4; // len of "true"
 
         // +1 to ignore prefix #
-        match self.input[self.token_start_idx + 1..self.curr_idx]
-            .to_uppercase()
-            .as_str()
-        {
-            "TRUE" => self.tokens.push(Token::Literal(Literal::Boolean(true))),
-            _ => self.add_error(TokenizerError::BooleanTrueLiteral {
-                idx: self.token_start_idx,
-            }),
+        match self.input.get(self.token_start_idx + 1..self.curr_idx) {
+            Some(lit) if lit.eq_ignore_ascii_case("TRUE") => self.tokens.push(Token::Literal(Literal::Boolean(true))),
+            _ => {
+                // the literal may be shorter than "true", so the next token must not be skipped
+                self.curr_idx = self.token_start_idx + 1;
+                self.add_error(TokenizerError::BooleanTrueLiteral {
+                    idx: self.token_start_idx,
+                });
+            }
         }
     }
 
@@ -245,14 +490,15 @@ impl Tokenizer<'_> {
         self.curr_idx += 5; // len of "false"
 
         // +1 to ignore prefix #
-        match self.input[self.token_start_idx + 1..self.curr_idx]
-            .to_uppercase()
-            .as_str()
-        {
-            "FALSE" => self.tokens.push(Token::Literal(Literal::Boolean(false))),
-            _ => self.add_error(TokenizerError::BooleanFalseLiteral {
-                idx: self.token_start_idx,
-            }),
+        match self.input.get(self.token_start_idx + 1..self.curr_idx) {
+            Some(lit) if lit.eq_ignore_ascii_case("FALSE") => self.tokens.push(Token::Literal(Literal::Boolean(false))),
+            _ => {
+                // the literal may be shorter than "false", so the next token must not be skipped
+                self.curr_idx = self.token_start_idx + 1;
+                self.add_error(TokenizerError::BooleanFalseLiteral {
+                    idx: self.token_start_idx,
+                });
+            }
         }
     }
 }
@@ -265,10 +511,16 @@ pub enum TokenizerError {
     Literal { idx: usize },
     #[error("Expected char literal at idx {idx} to end with \'.")]
     CharLiteral { idx: usize },
+    #[error("Expected string literal at idx {idx} to end with \".")]
+    StringLiteral { idx: usize },
+    #[error("Invalid escape sequence at idx {idx}.")]
+    Escape { idx: usize },
     #[error("Expected boolean literal TRUE/true at idx {idx}.")]
     BooleanTrueLiteral { idx: usize },
     #[error("Expected boolean literal FALSE/false at idx {idx}.")]
     BooleanFalseLiteral { idx: usize },
+    #[error("Expected the number of a macro argument after % at idx {idx}.")]
+    MacroArgument { idx: usize },
 }
 
 #[cfg(test)]
@@ -278,6 +530,19 @@ mod test {
     #[test]
     fn test_tokenize() {}
 
+    #[test]
+    fn test_spans() {
+        let input = "mov R0, #0x1F\n.loop jmp .loop #true ";
+        let (tokens, spans) = Tokenizer::tokenize_with_spans(input).unwrap();
+
+        assert_eq!(tokens.len(), spans.len());
+        let texts: Vec<_> = spans.into_iter().map(|span| &input[span]).collect();
+        assert_eq!(
+            texts,
+            ["mov", "R0", ",", "#0x1F", "\n", ".loop", "jmp", ".loop", "#true"]
+        );
+    }
+
     #[test]
     fn test_run() {
         let mut t = Tokenizer::from(
@@ -294,33 +559,101 @@ mod test {
         assert_eq!(
             t.tokens,
             vec![
-                Token::Label(".MAIN".into()),
-                Token::Instruction("MOV".into()),
-                Token::Register("R0".into()),
+                Token::Label(".main"),
+                Token::Newline,
+                Token::Instruction("MOV"),
+                Token::Register("R0"),
                 Token::Comma,
                 Token::Literal(Literal::Decimal("5")),
-                Token::Instruction("NOP".into()),
-                Token::Instruction("MOV".into()),
-                Token::Register("R256".into()),
+                Token::Newline,
+                Token::Instruction("nop"),
+                Token::Newline,
+                Token::Instruction("MOV"),
+                Token::Register("R256"),
+                Token::Comma,
+                Token::Literal(Literal::Hexadecimal("Bc2a")),
+                Token::Newline,
+                Token::Instruction("Mul"),
+                Token::Register("R0"),
+                Token::Comma,
+                Token::Register("r256"),
+                Token::Newline,
+                Token::Instruction("JMP"),
+                Token::Label(".main"),
+                Token::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_instruction_starting_with_r() {
+        let mut t = Tokenizer::from("ror R1, #1\nret");
+        t.run();
+        assert_eq!(
+            t.tokens,
+            vec![
+                Token::Instruction("ror"),
+                Token::Register("R1"),
                 Token::Comma,
-                Token::Literal(Literal::Hexadecimal("Bc2a".into())),
-                Token::Instruction("MUL".into()),
-                Token::Register("R0".into()),
+                Token::Literal(Literal::Decimal("1")),
+                Token::Newline,
+                Token::Instruction("ret"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_indexed_address() {
+        let mut t = Tokenizer::from("ldr R0, [R1 + #4]\nstr R0, [R1+#0]");
+        t.run();
+        assert_eq!(
+            t.tokens,
+            vec![
+                Token::Instruction("ldr"),
+                Token::Register("R0"),
+                Token::Comma,
+                Token::OpenBracket,
+                Token::Register("R1"),
+                Token::Plus,
+                Token::Literal(Literal::Decimal("4")),
+                Token::CloseBracket,
+                Token::Newline,
+                Token::Instruction("str"),
+                Token::Register("R0"),
                 Token::Comma,
-                Token::Register("R256".into()),
-                Token::Instruction("JMP".into()),
-                Token::Label(".MAIN".into())
+                Token::OpenBracket,
+                Token::Register("R1"),
+                Token::Plus,
+                Token::Literal(Literal::Decimal("0")),
+                Token::CloseBracket,
             ]
         );
     }
 
+    #[test]
+    fn test_run_borrows_from_input() {
+        let input = ".loop\n    mov R0, #1\n    add r1, R0\n    jmp .loop\n".repeat(100);
+        let mut t = Tokenizer::from(&input);
+        t.run();
+        assert!(t.errors.is_none());
+        // every line ends with a line break
+        assert_eq!(t.tokens.len(), 1500);
+
+        let input_range = input.as_bytes().as_ptr_range();
+        for token in &t.tokens {
+            if let Token::Label(s) | Token::Register(s) | Token::Instruction(s) = token {
+                assert!(input_range.contains(&s.as_ptr()));
+            }
+        }
+    }
+
     #[test]
     fn test_add_error() {
         let mut t = Tokenizer::from("");
         let err = TokenizerError::TokenStart { start: ' ', idx: 0 };
         assert!(t.errors.is_none());
         t.add_error(err.clone());
-        assert_eq!(t.errors.unwrap(), vec![err.into()]);
+        assert_eq!(t.errors.unwrap(), vec![err]);
     }
 
     #[test]
@@ -342,30 +675,60 @@ mod test {
     fn test_expect_label() {
         let mut t = Tokenizer::from(".main");
         t.expect_label();
-        assert_eq!(t.tokens[0], Token::Label(".MAIN".into()));
+        assert_eq!(t.tokens[0], Token::Label(".main"));
         t = Tokenizer::from(".MAIN");
         t.expect_label();
-        assert_eq!(t.tokens[0], Token::Label(".MAIN".into()))
+        assert_eq!(t.tokens[0], Token::Label(".MAIN"))
     }
 
     #[test]
     fn test_expect_instruction() {
         let mut t = Tokenizer::from("mov");
         t.expect_instruction();
-        assert_eq!(t.tokens[0], Token::Instruction("MOV".into()));
+        assert_eq!(t.tokens[0], Token::Instruction("mov"));
         t = Tokenizer::from("JMP");
         t.expect_instruction();
-        assert_eq!(t.tokens[0], Token::Instruction("JMP".into()));
+        assert_eq!(t.tokens[0], Token::Instruction("JMP"));
     }
 
     #[test]
     fn test_expect_register() {
         let mut t = Tokenizer::from("R0");
         t.expect_register();
-        assert_eq!(t.tokens[0], Token::Register("R0".into()));
+        assert_eq!(t.tokens[0], Token::Register("R0"));
         t = Tokenizer::from("R4242");
         t.expect_register();
-        assert_eq!(t.tokens[0], Token::Register("R4242".into()));
+        assert_eq!(t.tokens[0], Token::Register("R4242"));
+    }
+
+    #[test]
+    fn test_run_special_registers() {
+        let mut t = Tokenizer::from("inc PC\nmov sp, #16\nspin");
+        t.run();
+        assert_eq!(
+            t.tokens,
+            vec![
+                Token::Instruction("inc"),
+                Token::Register("PC"),
+                Token::Newline,
+                Token::Instruction("mov"),
+                Token::Register("sp"),
+                Token::Comma,
+                Token::Literal(Literal::Decimal("16")),
+                Token::Newline,
+                Token::Instruction("spin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expect_macro_argument() {
+        let mut t = Tokenizer::from("%12");
+        t.expect_macro_argument();
+        assert_eq!(t.tokens[0], Token::MacroArgument(12));
+        let mut t = Tokenizer::from("% 1");
+        t.expect_macro_argument();
+        assert_eq!(t.errors, Some(vec![TokenizerError::MacroArgument { idx: 0 }]));
     }
 
     #[test]
@@ -382,7 +745,7 @@ mod test {
         assert_eq!(t.tokens[0], Token::Literal(Literal::Decimal("42")));
         let mut t = Tokenizer::from("#0x4H");
         t.expect_literal();
-        assert_eq!(t.tokens[0], Token::Literal(Literal::Hexadecimal("4H".into())));
+        assert_eq!(t.tokens[0], Token::Literal(Literal::Hexadecimal("4H")));
         let mut t = Tokenizer::from("#0b010110");
         t.expect_literal();
         assert_eq!(t.tokens[0], Token::Literal(Literal::Binary("010110")));
@@ -397,7 +760,7 @@ mod test {
         assert_eq!(t.tokens[0], Token::Literal(Literal::Boolean(false)));
         let mut t = Tokenizer::from("#\"Hello, there\"");
         t.expect_literal();
-        assert_eq!(t.tokens[0], Token::Literal(Literal::String("Hello, there")));
+        assert_eq!(t.tokens[0], Token::Literal(Literal::String("Hello, there".into())));
         let mut t = Tokenizer::from("#\'7\'");
         t.expect_literal();
         assert_eq!(t.tokens[0], Token::Literal(Literal::Char('7')));
@@ -416,7 +779,68 @@ mod test {
         t.expect_literal();
         assert_eq!(
             t.tokens[0],
-            Token::Literal(Literal::String("Jajajajaja2498291849102+#amfl929r2jlsamfa3"))
+            Token::Literal(Literal::String("Jajajajaja2498291849102+#amfl929r2jlsamfa3".into()))
+        );
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let mut t = Tokenizer::from("#'\\n'");
+        t.expect_literal();
+        assert_eq!(t.tokens[0], Token::Literal(Literal::Char('\n')));
+        let mut t = Tokenizer::from("#'\\x41'");
+        t.expect_literal();
+        assert_eq!(t.tokens[0], Token::Literal(Literal::Char('A')));
+        let mut t = Tokenizer::from("#'\\''");
+        t.expect_literal();
+        assert_eq!(t.tokens[0], Token::Literal(Literal::Char('\'')));
+
+        let mut t = Tokenizer::from("#\"a\\tb\\\\c\\\"d\\0\"");
+        t.expect_literal();
+        assert_eq!(t.tokens[0], Token::Literal(Literal::String("a\tb\\c\"d\0".into())));
+        assert_eq!(t.errors, None);
+    }
+
+    #[test]
+    fn test_invalid_escape_sequence() {
+        let mut t = Tokenizer::from("#'\\q'");
+        t.expect_literal();
+        assert_eq!(t.errors, Some(vec![TokenizerError::Escape { idx: 2 }]));
+        let mut t = Tokenizer::from("#'\\xZ1'");
+        t.expect_literal();
+        assert_eq!(t.errors, Some(vec![TokenizerError::Escape { idx: 2 }]));
+    }
+
+    #[test]
+    fn test_recover_after_errors() {
+        let input = "mov R0, #xyz\nmov R1, #tru\nmov R2, @@";
+        assert_eq!(
+            Tokenizer::tokenize(input),
+            Err(vec![
+                TokenizerError::Literal { idx: 9 },
+                TokenizerError::BooleanTrueLiteral { idx: 21 },
+                TokenizerError::TokenStart { start: '@', idx: 35 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unterminated_literals() {
+        assert_eq!(
+            Tokenizer::tokenize("mov R0, #\"abc"),
+            Err(vec![TokenizerError::StringLiteral { idx: 8 }])
+        );
+        assert_eq!(
+            Tokenizer::tokenize("mov R0, #'"),
+            Err(vec![TokenizerError::CharLiteral { idx: 10 }])
+        );
+        assert_eq!(
+            Tokenizer::tokenize("mov R0, #"),
+            Err(vec![TokenizerError::Literal { idx: 9 }])
+        );
+        assert_eq!(
+            Tokenizer::tokenize("mov R0, #0").unwrap().last(),
+            Some(&Token::Literal(Literal::Decimal("0")))
         );
     }
 
@@ -433,7 +857,7 @@ mod test {
         assert_eq!(t.tokens[0], Token::Literal(Literal::Decimal("-42")));
         t = Tokenizer::from("#0x4H");
         t.expect_literal();
-        assert_eq!(t.tokens[0], Token::Literal(Literal::Hexadecimal("4H".into())));
+        assert_eq!(t.tokens[0], Token::Literal(Literal::Hexadecimal("4H")));
         t = Tokenizer::from("#0b010110");
         t.expect_literal();
         assert_eq!(t.tokens[0], Token::Literal(Literal::Binary("010110")));
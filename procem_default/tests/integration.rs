@@ -1,6 +1,13 @@
-use procem::{processor::Processor, program::Program, register::Register, word::I32};
+use procem::{
+    processor::{HistoryError, Processor, RunOutcome},
+    program::{Program, ProgramError},
+    register::{Flag, Register},
+    stack::{DynStack, StackError, StackGrowth},
+    word::{I8, I16, I32},
+};
 use procem_default::{
-    AssemblerError, assemble,
+    AssembledProgram, AssemblerError, RunError, assemble,
+    encoding::BinaryProgram,
     instruction::{Instruction, jump_condition::JumpCondition, operand::Operand},
     parser::ParserError,
 };
@@ -25,12 +32,14 @@ fn simple_5x2_multiplication() {
         Program::<IS, Vec<Instruction<I32>>, I32>::new(vec![
             Instruction::Mov {
                 to: Register::R0,
-                from: Operand::Value(2.into())
+                from: Operand::Value(2.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Add {
                 acc: Register::R1,
                 rhs: Operand::Register(Register::R0),
-                signed: false
+                set_flags: false,
+                cond: JumpCondition::Unconditional
             },
             Instruction::Jump {
                 to: 0.into(),
@@ -77,31 +86,38 @@ fn parse_various_literals() {
         Program::from(vec![
             Instruction::Mov {
                 to: Register::R0,
-                from: Operand::Value(42.into())
+                from: Operand::Value(42.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R1,
-                from: Operand::Value(42.into())
+                from: Operand::Value(42.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R2,
-                from: Operand::Value(42.into())
+                from: Operand::Value(42.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R3,
-                from: Operand::Value(42.into())
+                from: Operand::Value(42.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R4,
-                from: Operand::Value(1.into())
+                from: Operand::Value(1.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R5,
-                from: Operand::Value(0.into())
+                from: Operand::Value(0.into()),
+                cond: JumpCondition::Unconditional
             },
             Instruction::Mov {
                 to: Register::R6,
-                from: Operand::Value(65.into())
+                from: Operand::Value(65.into()),
+                cond: JumpCondition::Unconditional
             }
         ])
     )
@@ -149,6 +165,42 @@ fn control_flow_and_labels() {
     assert_eq!(processor.registers.get_reg(Register::R0), 5.into());
 }
 
+#[test]
+fn run_to_loop_label() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #0
+        mov R1, #5
+        .loop
+        add R0, #1
+        subs R1, #1
+        jnz .loop
+        ",
+    )
+    .unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    // stops before the first iteration and stays there, as the pc already points to the loop
+    assert_eq!(processor.run_to_pc(2, 100), Ok(true));
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+    assert_eq!(processor.run_to_pc(2, 100), Ok(true));
+    assert_eq!(processor.cycle_count(), 2);
+
+    // the next iteration is reached after executing the loop body
+    processor.execute_next_instruction().unwrap();
+    assert_eq!(processor.run_to_pc(2, 100), Ok(true));
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+
+    // the limit is exceeded before the next iteration
+    processor.execute_next_instruction().unwrap();
+    assert_eq!(processor.run_to_pc(2, 1), Ok(false));
+    assert_eq!(processor.registers.pc(), 4.into());
+
+    // the program completes without reaching an instruction that is not executed again
+    assert_eq!(processor.run_to_pc(0, 100), Ok(false));
+    assert_eq!(processor.registers.get_reg(Register::R0), 5.into());
+}
+
 #[test]
 fn test_overflow_and_flags() {
     let program = assemble::<I32>(
@@ -164,7 +216,7 @@ fn test_overflow_and_flags() {
 
     let _ = processor.run_program();
     assert_eq!(processor.registers.get_reg(Register::R0), i32::MIN.into());
-    assert_eq!(processor.registers.get_flag(procem::register::Flag::Z), true);
+    assert!(processor.registers.get_flag(procem::register::Flag::Z));
 }
 
 #[test]
@@ -196,3 +248,1885 @@ fn invalid_assembly_should_fail() {
         Err(vec![AssemblerError::Parser(ParserError::CannotConvertStrToVal)])
     );
 }
+
+#[test]
+fn dispatch_table_matches_execute() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #5
+        mov R1, #1
+        mov R2, #0
+        .loop
+        mul R1, R0
+        push R1
+        xor R2, R1
+        shl R2, #1
+        subs R0, #1
+        jnz .loop
+        pop R3
+        ",
+    )
+    .unwrap();
+
+    let mut matched = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(matched.run_program(), Ok(()));
+
+    let table = program.dispatch_table::<1024>();
+    let mut dispatched = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(dispatched.run_program_dispatched(&table), Ok(()));
+
+    assert_eq!(matched, dispatched);
+    assert_eq!(dispatched.registers.get_reg(Register::R1), 120.into());
+}
+
+#[test]
+fn shift_by_register() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        mov R1, #0
+        .loop
+        mov R2, #1
+        shl R2, R1
+        add R0, R2
+        add R1, #1
+        cmp R1, #4
+        jnz .loop
+        shrs R0, R1
+        ",
+    )
+    .unwrap();
+    assert_eq!(program[3].to_string(), "SHL R2, R1");
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    // 1 + 1 + 2 + 4 + 8, shifted right by 4
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+    assert!(!processor.registers.get_flag(Flag::Z));
+}
+
+#[test]
+fn rotate_through_carry() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        mov R1, #-2147483648
+        rcl R1, #1
+        rcl R0, #1
+        rcr R0, #2
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R1), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R0), i32::MIN.into());
+    assert!(processor.registers.get_flag(procem::register::Flag::C));
+}
+
+#[test]
+fn run_n_instructions() {
+    let program = assemble::<I32>(
+        "
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        inc R0
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.run_n(3), Ok(3));
+    assert_eq!(processor.registers.pc(), 3.into());
+    assert_eq!(processor.registers.get_reg(Register::R0), 3.into());
+
+    assert_eq!(processor.run_n(20), Ok(7));
+    assert_eq!(processor.registers.pc(), 10.into());
+    assert_eq!(processor.registers.get_reg(Register::R0), 10.into());
+}
+
+#[test]
+fn infinite_loop_hits_instruction_limit() {
+    let program = assemble::<I32>(
+        "
+        .loop
+        inc R0
+        jmp .loop
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(
+        processor.run_program_with_limit(100),
+        Err(ProgramError::InstructionLimitExceeded { limit: 100 })
+    );
+    assert_eq!(processor.registers.get_reg(Register::R0), 50.into());
+}
+
+#[test]
+fn multiple_labels_on_same_instruction() {
+    let program = assemble::<I32>(
+        "
+        mov R1, #0
+        .a
+        .b
+        mov R0, #1
+        jmp .a
+        jmp .b
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(program[2], program[3]);
+    assert_eq!(
+        program[2],
+        Instruction::Jump {
+            to: 1.into(),
+            condition: JumpCondition::Unconditional
+        }
+    );
+}
+
+#[test]
+fn duplicate_label_should_fail() {
+    let result = assemble::<I32>(
+        "
+        .a
+        mov R0, #1
+        .a
+        mov R0, #2
+        ",
+    );
+
+    assert_eq!(
+        result,
+        Err(vec![AssemblerError::Parser(ParserError::DuplicateLabel {
+            idx: 1,
+            old_idx: 0
+        })])
+    );
+}
+
+#[test]
+fn current_instruction() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #10
+        subs R0, #1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.current_instruction(), Some(&program[0]));
+    assert_eq!(processor.current_instruction_asm(), Some("MOV R0, #10".to_string()));
+
+    assert!(processor.execute_next_instruction().is_ok());
+    assert_eq!(processor.current_instruction_asm(), Some("SUBS R0, #1".to_string()));
+
+    assert!(processor.execute_next_instruction().is_ok());
+    assert_eq!(processor.current_instruction(), None);
+    assert_eq!(processor.current_instruction_asm(), None);
+}
+
+#[test]
+fn swap_register_with_memory() {
+    let program = assemble::<I32>(
+        "
+        push #10
+        push #20
+        mov R0, #30
+        swp R0, #1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), 10.into());
+    assert_eq!(processor.stack.read(1.into()), 30.into());
+    assert_eq!(processor.stack.read(2.into()), 20.into());
+}
+
+#[test]
+fn set_condition_into_register() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        mov R1, #2
+        cmp R0, R1
+        setl R2
+        setg R3
+        setnz R4
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R2), 1.into());
+    assert_eq!(processor.registers.get_reg(Register::R3), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R4), 1.into());
+}
+
+#[test]
+fn logical_flag_setting_variants() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #12
+        ands R0, #0
+        setz R1
+        mov R2, #3
+        and R2, #0
+        ors R2, #-4
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R1), 1.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), (-4).into());
+    assert!(processor.registers.get_flag(procem::register::Flag::S));
+    assert!(!processor.registers.get_flag(procem::register::Flag::Z));
+}
+
+#[test]
+fn conditional_move() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #5
+        mov R1, #3
+        cmp R0, R1
+        cmovg R2, #1
+        cmovl R3, #1
+        cmp R1, R0
+        cmovg R4, R0
+        cmovle R5, R0
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R2), 1.into());
+    assert_eq!(processor.registers.get_reg(Register::R3), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R4), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R5), 5.into());
+}
+
+#[test]
+fn case_insensitive_labels_and_instructions() {
+    let program = assemble::<I32>(
+        "
+        .Start
+        Inc R0
+        cmp r0, #3
+        jnz .START
+        END
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), 3.into());
+}
+
+#[test]
+fn run_program_detailed_completes() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #2
+        mul R0, #3
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.run_program_detailed(), RunOutcome::Completed { steps: 2 });
+    assert_eq!(processor.registers.get_reg(Register::R0), 6.into());
+}
+
+#[test]
+fn run_program_detailed_reports_error() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        call #10
+        mov R0, #2
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(
+        processor.run_program_detailed(),
+        RunOutcome::Error {
+            steps: 1,
+            error: ProgramError::InvalidJumpTarget { pc: 10 }
+        }
+    );
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+}
+
+#[test]
+fn run_program_detailed_hits_step_limit() {
+    let program = assemble::<I32>(
+        "
+        .loop
+        inc R0
+        jmp .loop
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(
+        processor.run_program_detailed_with_limit(10),
+        RunOutcome::StepLimit { steps: 10 }
+    );
+    assert_eq!(processor.registers.get_reg(Register::R0), 5.into());
+}
+
+#[test]
+fn iterate_array_with_indexed_addressing() {
+    // sums the array at stack addresses 10..15 using the base register R1 and a constant offset of 10
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        .fill
+        str R0, [R1 + #10]
+        inc R0
+        inc R1
+        cmp R1, #5
+        jnz .fill
+        mov R0, #0
+        mov R1, #0
+        .sum
+        ldr R4, [R1 + #10]
+        add R0, R4
+        inc R1
+        cmp R1, #5
+        jnz .sum
+        ldr R2, [R1+#9]
+        ldr R3, [R1]
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), 15.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 5.into());
+    assert_eq!(processor.registers.get_reg(Register::R3), 0.into());
+    assert_eq!(processor.stack.read(12.into()), 3.into());
+}
+
+#[test]
+fn indexed_addressing_only_in_ldr_and_str() {
+    for src in ["add R0, [R1 + #10]", "push [R1]", "cmp R0, [R1]", "shl R0, [R1 + #1]"] {
+        let errors = assemble::<I32>(src).unwrap_err();
+        assert!(
+            matches!(
+                errors.as_slice(),
+                [AssemblerError::Parser(ParserError::InvalidToken {
+                    expected: "Register or Literal",
+                    ..
+                })]
+            ),
+            "{src}: {errors:?}"
+        );
+    }
+}
+
+#[test]
+fn stack_expression_evaluation() {
+    // (6 - 3) * (4 + 1) / 5
+    let program = assemble::<I32>(
+        "
+        push #6
+        push #3
+        ssub
+        push #4
+        push #1
+        sadd
+        smul
+        push #5
+        sdiv
+        pop R0
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), 3.into());
+    assert_eq!(processor.registers.sp(), 0.into());
+}
+
+#[test]
+fn saturating_arithmetic() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #2147483600
+        addsat R0, #100
+        mov R1, #-2147483600
+        subsat R1, #100
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), i32::MAX.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), i32::MIN.into());
+    assert!(processor.registers.get_flag(procem::register::Flag::V));
+}
+
+#[test]
+fn encode_decode_factorial_program() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #5
+        mov R1, #1
+        .loop
+        mul R1, R0
+        subs R0, #1
+        jnz .loop
+        ",
+    )
+    .unwrap();
+
+    let decoded = AssembledProgram::<I32>::decode(&program.encode()).unwrap();
+    assert_eq!(decoded, program);
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&decoded).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R1), 120.into());
+}
+
+#[test]
+fn predicated_instructions() {
+    let program = assemble::<I32>(
+        "
+        cmp R0, #0
+        addeq R1, #1
+        addne R2, #1
+        cmp R0, #1
+        ADDEQ R3, #1
+        movne R4, #7
+        adds R5, #-1
+        addsmi R6, #1
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        program[1],
+        Instruction::Add {
+            acc: Register::R1,
+            rhs: Operand::Value(1.into()),
+            set_flags: false,
+            cond: JumpCondition::Zero
+        }
+    );
+    // `ADDS` is the flag setting add, a condition can be appended to it
+    assert_eq!(program[6].cond(), Some(JumpCondition::Unconditional));
+    assert_eq!(program[7].cond(), Some(JumpCondition::Signed));
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R1), 1.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R3), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R4), 7.into());
+    assert_eq!(processor.registers.get_reg(Register::R6), 1.into());
+}
+
+#[test]
+fn predicated_jumps_are_rejected() {
+    for src in ["jzeq .a", "nopeq", "setzeq R0", "cmovzeq R0, R1"] {
+        let errors = assemble::<I32>(format!(".a\n{src}")).unwrap_err();
+        assert!(
+            matches!(
+                errors.as_slice(),
+                [AssemblerError::Parser(ParserError::UnknownInstruction { .. }), ..]
+            ),
+            "{src}: {errors:?}"
+        );
+    }
+}
+
+#[test]
+fn cycle_count() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #3
+        mul R0, #4
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.cycle_count(), 0);
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R0), 12.into());
+    assert_eq!(processor.cycle_count(), 1 + 3);
+}
+
+#[test]
+fn profile_loop() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #3
+        .loop
+        add R1, R0
+        subs R0, #1
+        jnz .loop
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.enable_profiling();
+
+    let _ = processor.run_program();
+    assert_eq!(processor.registers.get_reg(Register::R1), 6.into());
+    assert_eq!(
+        processor.profile().iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [("ADD", 3), ("JNZ", 3), ("MOV", 1), ("SUBS", 3)]
+    );
+}
+
+#[test]
+fn profile_disabled_by_default() {
+    let program = assemble::<I32>("mov R0, #3").unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    let _ = processor.run_program();
+    assert!(processor.profile().is_empty());
+}
+
+#[test]
+fn jump_to_label_after_last_instruction() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        jmp .end
+        mov R0, #2
+        .end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        program[1],
+        Instruction::Jump {
+            to: 3.into(),
+            condition: JumpCondition::Unconditional
+        }
+    );
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(processor.run_program_detailed(), RunOutcome::Completed { steps: 2 });
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+}
+
+#[test]
+fn jump_to_undefined_label() {
+    assert_eq!(
+        assemble::<I32>("jmp .nowhere"),
+        Err(vec![AssemblerError::Parser(ParserError::LabelNotFound {
+            idx: 1,
+            label: ".nowhere".to_string()
+        })])
+    );
+}
+
+#[test]
+fn dyn_stack_grows_past_initial_capacity() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #20
+        .loop
+        push R0
+        subs R0, #1
+        jnz .loop
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<0, _, _, _, DynStack<_>>::builder()
+        .with_stack(DynStack::with_capacity(4, 64))
+        .with_program(&program)
+        .build();
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.sp(), 20.into());
+    assert_eq!(processor.stack.len(), 21);
+    for i in 1..=20 {
+        assert_eq!(processor.stack.read(i.into()), (21 - i).into());
+    }
+}
+
+#[test]
+fn literal_out_of_range_for_word() {
+    assert_eq!(
+        assemble::<I8>("mov R0, #300"),
+        Err(vec![AssemblerError::Parser(ParserError::LiteralOutOfRange {
+            literal: "300".to_string(),
+            word_bits: 8
+        })])
+    );
+
+    let program = assemble::<I8>("mov R0, #127").unwrap();
+    assert_eq!(
+        program[0],
+        Instruction::Mov {
+            to: Register::R0,
+            from: Operand::Value(127.into()),
+            cond: JumpCondition::Unconditional
+        }
+    );
+}
+
+#[test]
+fn build_constant_from_halves() {
+    let program = assemble::<I32>(
+        "
+        movl R0, #0x5678
+        movh R0, #0x1234
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 0x1234_5678.into());
+}
+
+#[test]
+fn and_mask_then_jump_if_zero() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #0b1000
+        ands R0, #0b0111
+        jz .masked
+        mov R1, #1
+        .masked
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert!(processor.registers.get_flag(procem::register::Flag::Z));
+    assert_eq!(processor.registers.get_reg(Register::R1), 0.into());
+}
+
+#[test]
+fn jump_if_parity_odd() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #0x100
+        adds R0, #0b0111
+        jpo .odd
+        mov R1, #1
+        .odd
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    // only the low byte with three set bits counts
+    assert!(!processor.registers.get_flag(procem::register::Flag::P));
+    assert_eq!(processor.registers.get_reg(Register::R1), 0.into());
+}
+
+#[test]
+fn stack_growth_directions() {
+    let program = assemble::<I32>(
+        "
+        push #1
+        push #2
+        sadd
+        pop R0
+        ",
+    )
+    .unwrap();
+
+    for (growth, start, top) in [(StackGrowth::Up, 0, 2), (StackGrowth::Down, 15, 13)] {
+        let mut processor = Processor::<16, _, _, _>::builder()
+            .with_stack_growth(growth)
+            .with_program(&program)
+            .build();
+        assert_eq!(processor.registers.sp(), start.into());
+
+        processor.run_n(2).unwrap();
+        assert_eq!(processor.registers.sp(), top.into(), "{growth:?}");
+        assert_eq!(processor.stack.read(top.into()), 2.into());
+
+        processor.run_program().unwrap();
+        assert_eq!(processor.registers.sp(), start.into(), "{growth:?}");
+        assert_eq!(processor.registers.get_reg(Register::R0), 3.into());
+    }
+}
+
+#[test]
+fn peek_stack_without_popping() {
+    let program = assemble::<I32>("push #1\npush #2").unwrap();
+
+    for growth in [StackGrowth::Up, StackGrowth::Down] {
+        let mut processor = Processor::<16, _, _, _>::builder()
+            .with_stack_growth(growth)
+            .with_program(&program)
+            .build();
+        processor.run_program().unwrap();
+        let sp = processor.registers.sp();
+
+        assert_eq!(processor.peek_stack(), 2.into(), "{growth:?}");
+        assert_eq!(processor.peek_stack_offset(0), 2.into(), "{growth:?}");
+        assert_eq!(processor.peek_stack_offset(-1), 1.into(), "{growth:?}");
+        assert_eq!(processor.registers.sp(), sp);
+    }
+}
+
+#[test]
+fn write_and_read_memory_block() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #0
+        .loop
+        ldr R1, [R0 + #8]
+        add R1, R1
+        str R1, [R0 + #8]
+        inc R0
+        cmp R0, #4
+        jnz .loop
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    let data = [1.into(), 2.into(), 3.into(), 4.into()];
+    processor.write_memory(8, &data).unwrap();
+    assert_eq!(processor.read_memory(8, 4), Ok(data.as_slice()));
+
+    processor.run_program().unwrap();
+    assert_eq!(
+        processor.read_memory(8, 4),
+        Ok([2.into(), 4.into(), 6.into(), 8.into()].as_slice())
+    );
+    assert_eq!(
+        processor.read_memory(14, 4),
+        Err(StackError::OutOfBounds {
+            addr: 14,
+            len: 4,
+            stack_size: 16
+        })
+    );
+}
+
+#[test]
+fn call_and_return() {
+    let program = assemble::<I32>(
+        "
+        call #3
+        call #3
+        jmp .end
+        add R0, #2
+        ret
+        .end
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 4.into());
+    assert_eq!(processor.call_depth(), 0);
+}
+
+#[test]
+fn return_without_call() {
+    let program = assemble::<I32>(
+        "
+        ret
+        mov R0, #1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    assert_eq!(
+        processor.run_program_detailed(),
+        RunOutcome::Error {
+            steps: 0,
+            error: ProgramError::ReturnWithoutCall { pc: 0 }
+        }
+    );
+    // the program did not jump to the address on the stack
+    assert_eq!(processor.registers.pc(), 1.into());
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+}
+
+#[test]
+fn jump_far_out_of_range() {
+    let program = Program::from(vec![
+        Instruction::Mov {
+            to: Register::R0,
+            from: Operand::Value(1.into()),
+            cond: JumpCondition::Unconditional,
+        },
+        Instruction::Jump {
+            to: 1_000_000.into(),
+            condition: JumpCondition::Unconditional,
+        },
+    ]);
+
+    let mut processor = Processor::<1024, _, _, I32>::builder().with_program(&program).build();
+
+    assert_eq!(
+        processor.run_program(),
+        Err(ProgramError::InvalidJumpTarget { pc: 1_000_000 })
+    );
+
+    // jumping directly behind the last instruction ends the program
+    let program = Program::from(vec![Instruction::Jump {
+        to: 1.into(),
+        condition: JumpCondition::Unconditional,
+    }]);
+    let mut processor = Processor::<1024, _, _, I32>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program(), Ok(()));
+}
+
+#[test]
+fn jump_out_of_range_reported_consistently() {
+    // every instruction that jumps to an address past the end reports the same error
+    let jumps = [
+        vec![Instruction::Jump {
+            to: 5.into(),
+            condition: JumpCondition::Unconditional,
+        }],
+        vec![Instruction::JumpRel {
+            offset: 4.into(),
+            condition: JumpCondition::Unconditional,
+        }],
+        assemble::<I32>("call #5").unwrap().to_vec(),
+        assemble::<I32>("push #5\nret").unwrap().to_vec(),
+    ];
+    for instructions in jumps {
+        let program = Program::from(instructions);
+        let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+        // a return is only valid after a call
+        processor.enter_call();
+
+        let error = loop {
+            if let Err(error) = processor.execute_next_instruction() {
+                break error;
+            }
+        };
+        assert_eq!(error, ProgramError::InvalidJumpTarget { pc: 5 }, "{program:?}");
+        // the program counter still points behind the jumping instruction
+        assert_eq!(
+            Into::<usize>::into(processor.registers.pc()),
+            program.len(),
+            "{program:?}"
+        );
+    }
+
+    // a direct jump is validated the same way
+    let program = assemble::<I32>("nop").unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.jump(5.into());
+    assert_eq!(processor.registers.pc(), 0.into());
+    assert_eq!(
+        processor.execute_next_instruction(),
+        Err(ProgramError::InvalidJumpTarget { pc: 5 })
+    );
+
+    // setting the program counter bypasses the validation until the instruction was executed
+    let program = assemble::<I32>("mov PC, #5").unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(
+        processor.execute_next_instruction(),
+        Err(ProgramError::InvalidJumpTarget { pc: 5 })
+    );
+    assert_eq!(processor.registers.pc(), 5.into());
+}
+
+#[test]
+fn set_pc_within_program() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #1
+        mov R1, #2
+        ",
+    )
+    .unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    // skip the first instruction
+    assert_eq!(processor.set_pc(1), Ok(()));
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 2.into());
+
+    processor.set_pc(0).unwrap();
+    assert_eq!(
+        processor.set_pc(2),
+        Err(ProgramError::PCOutOfBounds { pc: 2, program_len: 2 })
+    );
+    assert_eq!(processor.registers.pc(), 0.into());
+
+    let mut processor = Processor::<1024, Instruction<I32>, Vec<_>, I32>::new();
+    assert_eq!(processor.set_pc(0), Err(ProgramError::NoProgramLoaded));
+}
+
+#[test]
+fn index_registers_after_run() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #7
+        push R0
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    assert_eq!(processor.registers[Register::PC], 2.into());
+    assert_eq!(processor.registers[Register::SP], 1.into());
+    assert_eq!(processor.registers[Register::R0], 7.into());
+
+    processor.registers[Register::PC] = 0.into();
+    assert_eq!(processor.current_instruction(), Some(&program[0]));
+}
+
+#[test]
+fn special_registers_in_assembly() {
+    let program = assemble::<I32>(
+        "
+        inc PC
+        mov sp, #16
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        &program[..],
+        [
+            Instruction::Inc {
+                reg: Register::PC,
+                set_flags: false,
+                cond: JumpCondition::Unconditional,
+            },
+            Instruction::Mov {
+                to: Register::SP,
+                from: Operand::Value(16.into()),
+                cond: JumpCondition::Unconditional,
+            },
+        ]
+    );
+}
+
+#[test]
+fn relative_jumps() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #3
+        .loop
+        add R1, #2
+        subs R0, #1
+        jrnz .loop
+        jr .skip
+        mov R1, #-1
+        .skip
+        nop
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        program[3],
+        Instruction::JumpRel {
+            offset: (-3).into(),
+            condition: JumpCondition::NotZero,
+        }
+    );
+    assert_eq!(
+        program[4],
+        Instruction::JumpRel {
+            offset: 1.into(),
+            condition: JumpCondition::Unconditional,
+        }
+    );
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    assert_eq!(processor.registers[Register::R1], 6.into());
+}
+
+#[test]
+fn initial_stack_pointer() {
+    let program = assemble::<I32>(
+        "
+        push #7
+        push #8
+        pop R0
+        pop R1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder()
+        .with_stack_pointer(4.into())
+        .with_program(&program)
+        .build();
+    assert_eq!(processor.registers.sp(), 4.into());
+
+    processor.run_n(2).unwrap();
+    assert_eq!(processor.stack.read(5.into()), 7.into());
+    assert_eq!(processor.stack.read(6.into()), 8.into());
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.sp(), 4.into());
+    assert_eq!(processor.registers.get_reg(Register::R0), 8.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 7.into());
+}
+
+#[test]
+fn push_pop_push() {
+    let program = assemble::<I32>(
+        "
+        push #1
+        pop R0
+        push #2
+        push #3
+        pop R1
+        pop R2
+        ",
+    )
+    .unwrap();
+
+    for growth in [StackGrowth::Up, StackGrowth::Down] {
+        let mut processor = Processor::<16, _, _, _>::builder()
+            .with_stack_growth(growth)
+            .with_program(&program)
+            .build();
+        let start = processor.registers.sp();
+
+        processor.run_program().unwrap();
+        assert_eq!(processor.registers.sp(), start, "{growth:?}");
+        assert!(processor.stack_is_empty());
+        assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+        assert_eq!(processor.registers.get_reg(Register::R1), 3.into());
+        assert_eq!(processor.registers.get_reg(Register::R2), 2.into());
+    }
+}
+
+#[test]
+fn pop_from_empty_stack() {
+    let program = assemble::<I32>(
+        "
+        push #1
+        pop R0
+        pop R1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program(), Err(ProgramError::StackUnderflow { pc: 2 }));
+    assert_eq!(processor.registers.sp(), 0.into());
+
+    let program = assemble::<I32>("push #1\nsadd").unwrap();
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program(), Err(ProgramError::StackUnderflow { pc: 1 }));
+    assert_eq!(processor.registers.sp(), 1.into());
+}
+
+#[test]
+fn push_onto_full_stack() {
+    let program = assemble::<I32>(".l\npush #1\njmp .l").unwrap();
+
+    // the slot at the initial stack pointer is never written, so three values fit
+    for (growth, top) in [(StackGrowth::Up, 3), (StackGrowth::Down, 0)] {
+        let mut processor = Processor::<4, _, _, _>::builder()
+            .with_program(&program)
+            .with_stack_growth(growth)
+            .build();
+
+        assert_eq!(processor.run_program(), Err(ProgramError::StackOverflow { pc: 0 }));
+        assert_eq!(processor.registers.sp(), top.into(), "{growth:?}");
+    }
+
+    let program = assemble::<I32>("call #0").unwrap();
+    let mut processor = Processor::<4, _, _, _>::builder().with_program(&program).build();
+    assert_eq!(processor.run_program(), Err(ProgramError::StackOverflow { pc: 0 }));
+    assert_eq!(processor.call_depth(), 3);
+}
+
+#[test]
+fn absolute_values() {
+    let program = assemble::<I8>(
+        "
+        mov R0, #-100
+        abs R0
+        mov R1, #-128
+        abs R1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_n(2).unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 100.into());
+    assert!(!processor.registers.get_flag(Flag::V));
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R1), (-128).into());
+    assert!(processor.registers.get_flag(Flag::V));
+}
+
+#[test]
+fn powers() {
+    let program = assemble::<I16>(
+        "
+        mov R0, #2
+        mov R1, #8
+        pow R0, R1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 256.into());
+
+    let program = assemble::<I8>(
+        "
+        mov R0, #3
+        pow R0, #5
+        mov R1, #3
+        pow R1, #-1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_n(2).unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), (-13).into());
+    assert!(!processor.registers.get_flag(Flag::V));
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R1), 3.into());
+    assert!(processor.registers.get_flag(Flag::V));
+}
+
+#[test]
+fn macro_expansion() {
+    let program = assemble::<I32>(
+        "
+        .macro swap
+            push %1
+            mov %1, %2
+            pop %2
+        .endm
+        .macro double
+            add %1, %1
+        .endm
+
+        mov R0, #1
+        mov R1, #2
+        swap R0, R1
+        double R1
+        ",
+    )
+    .unwrap();
+
+    let expected = assemble::<I32>(
+        "
+        mov R0, #1
+        mov R1, #2
+        push R0
+        mov R0, R1
+        pop R1
+        add R1, R1
+        ",
+    )
+    .unwrap();
+    assert_eq!(program, expected);
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 2.into());
+}
+
+#[test]
+fn recursive_macro() {
+    let errors = assemble::<I32>(".macro forever\nforever\n.endm\nforever").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![AssemblerError::Parser(ParserError::MacroRecursionLimit {
+            name: "forever".to_string(),
+            limit: 64,
+        })]
+    );
+}
+
+#[test]
+fn dump_after_factorial() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #5
+        mov R1, #1
+        .loop
+        mul R1, R0
+        subs R0, #1
+        jnz .loop
+        push R1
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    let dump = processor.dump();
+    assert!(dump.contains("R1: 120\n"));
+    assert!(dump.contains("PC: 6\nSP: 1\n"));
+    assert!(dump.contains("flags: [C: false, S: false, V: false, Z: true, P: true]\n"));
+    assert!(dump.ends_with("stack[1]: 120\n"));
+    assert_eq!(dump.lines().count(), 16 + 3 + 1);
+    assert_eq!(dump, processor.dump());
+}
+
+#[test]
+fn step_back_restores_previous_states() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #3
+        push R0
+        add R0, #4
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder()
+        .with_program(&program)
+        .build()
+        .with_history(2);
+
+    let mut states = vec![processor.state()];
+    for _ in 0..3 {
+        processor.execute_next_instruction().unwrap();
+        states.push(processor.state());
+    }
+    assert_eq!(processor.registers.get_reg(Register::R0), 7.into());
+
+    processor.step_back().unwrap();
+    assert_eq!(processor.state(), states[2]);
+    processor.step_back().unwrap();
+    assert_eq!(processor.state(), states[1]);
+    assert_eq!(processor.registers.pc(), 1.into());
+    assert_eq!(processor.step_back(), Err(HistoryError::Exhausted));
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.state(), states[3]);
+}
+
+#[test]
+fn step_back_through_arithmetic_to_initial_state() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #10
+        mov R1, #-3
+        mul R0, R1
+        push R0
+        sub R0, #-30
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<1024, _, _, _>::builder()
+        .with_program(&program)
+        .build()
+        .with_history(16);
+    let initial = processor.state();
+
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+    assert_eq!(processor.stack.read(processor.registers.sp()), (-30).into());
+
+    let mut steps = 0;
+    while processor.step_back().is_ok() {
+        steps += 1;
+    }
+    assert_eq!(steps, 5);
+    assert_eq!(processor.state(), initial);
+}
+
+#[test]
+fn flag_policy_suppresses_carry() {
+    let program = assemble::<I8>(
+        "
+        mov R0, #100
+        adds R0, #100
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert!(processor.registers.get_flag(Flag::C));
+
+    let mut processor = Processor::<8, _, _, _>::builder()
+        .with_program(&program)
+        .with_flag_policy(|_, flag| flag != Flag::C)
+        .build();
+    processor.run_program().unwrap();
+    assert!(!processor.registers.get_flag(Flag::C));
+    assert!(processor.registers.get_flag(Flag::V));
+}
+
+#[test]
+fn count_zeros_for_log2() {
+    let program = assemble::<I16>(
+        "
+        mov R0, #1000
+        clz R0
+        mov R1, #15
+        sub R1, R0
+        mov R2, #0
+        ctz R2
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    // floor(log2(1000)) is the index of the highest set bit
+    assert_eq!(processor.registers.get_reg(Register::R1), 9.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 16.into());
+}
+
+#[test]
+fn byte_swap_converts_endianness() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #0x01020304
+        bswap R0
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    assert_eq!(processor.registers.get_reg(Register::R0), 0x0403_0201.into());
+}
+
+#[test]
+fn double_width_product_with_mulh() {
+    let program = assemble::<I8>(
+        "
+        mov R0, #0x40
+        mulh R0, R1, #0x04
+        ",
+    )
+    .unwrap();
+    assert_eq!(program[1].to_string(), "MULH R0, R1, #4");
+
+    let mut processor = Processor::<8, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    // 0x40 * 0x04 = 0x0100
+    assert_eq!(processor.registers.get_reg(Register::R0), 0x00.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 0x01.into());
+    assert!(assemble::<I8>("mulh R0, #4").is_err());
+}
+
+#[test]
+fn step_back_without_history() {
+    let program = assemble::<I32>("nop").unwrap();
+    let mut processor = Processor::<1024, _, _, _>::builder().with_program(&program).build();
+
+    processor.execute_next_instruction().unwrap();
+    assert_eq!(processor.step_back(), Err(HistoryError::Disabled));
+}
+
+#[test]
+fn public_token_stream() {
+    use procem_default::{
+        tokenize,
+        tokenizer::{PublicToken, TokenKind},
+    };
+
+    let source = ".start\n  add R1, [SP + #2]\n  jmp .start";
+    let tokens = tokenize(source).unwrap();
+
+    let token = |kind, text: &str, start: usize| PublicToken {
+        kind,
+        text: text.to_string(),
+        span: start..start + text.len(),
+    };
+    assert_eq!(
+        tokens,
+        vec![
+            token(TokenKind::Label, ".start", 0),
+            token(TokenKind::Newline, "\n", 6),
+            token(TokenKind::Instruction, "add", 9),
+            token(TokenKind::Register, "R1", 13),
+            token(TokenKind::Comma, ",", 15),
+            token(TokenKind::OpenBracket, "[", 17),
+            token(TokenKind::Register, "SP", 18),
+            token(TokenKind::Plus, "+", 21),
+            token(TokenKind::Literal, "#2", 23),
+            token(TokenKind::CloseBracket, "]", 25),
+            token(TokenKind::Newline, "\n", 26),
+            token(TokenKind::Instruction, "jmp", 29),
+            token(TokenKind::Label, ".start", 33),
+        ]
+    );
+    for token in &tokens {
+        assert_eq!(source[token.span.clone()], token.text);
+    }
+}
+
+#[test]
+fn parse_public_tokens() {
+    use procem_default::{parse, tokenize, tokenizer::TokenKind};
+
+    let tokens = tokenize(
+        "
+        .loop
+        subs R0, #1
+        jnz .loop
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        parse::<I32>(&tokens),
+        Ok(vec![
+            Instruction::Sub {
+                acc: Register::R0,
+                rhs: Operand::Value(1.into()),
+                set_flags: true,
+                cond: JumpCondition::Unconditional,
+            },
+            Instruction::Jump {
+                to: 0.into(),
+                condition: JumpCondition::NotZero,
+            },
+        ])
+    );
+
+    let mut tokens = tokens;
+    tokens[2].kind = TokenKind::Label;
+    assert_eq!(
+        parse::<I32>(&tokens),
+        Err(vec![ParserError::InvalidToken {
+            idx: 2,
+            expected: "Single token of the given kind",
+            got: "subs".to_string(),
+        }])
+    );
+}
+
+#[test]
+fn source_spans_of_instructions() {
+    use procem_default::{SourceSpan, assemble_with_spans};
+
+    let source = "
+.start
+    mov R0, #10
+    mov R1, #0
+        div R0, R1
+    jmp .start
+";
+    let (program, spans) = assemble_with_spans::<I32>(source).unwrap();
+    assert_eq!(program, assemble::<I32>(source).unwrap());
+    assert_eq!(spans.len(), 4);
+
+    let div = &spans[2];
+    assert_eq!(
+        *div,
+        SourceSpan {
+            line: 5,
+            column: 9,
+            range: 47..57,
+        }
+    );
+    assert_eq!(&source[div.range.clone()], "div R0, R1");
+    assert_eq!(&source[spans[3].range.clone()], "jmp .start");
+}
+
+#[test]
+fn source_map_of_instructions() {
+    use procem_default::assemble_with_source_map;
+
+    // blank lines and labels do not produce instructions
+    let source = "
+.start
+
+    mov R0, #1
+
+.loop
+    add R0, R0
+    jmp .loop
+";
+    let (program, lines) = assemble_with_source_map::<I32>(source).unwrap();
+    assert_eq!(program.len(), 3);
+    assert_eq!(lines, [4, 7, 8]);
+}
+
+#[test]
+fn one_error_per_erroneous_line() {
+    let errors = assemble::<I32>(
+        "
+        mov [R0], R1
+        add R1, #2
+        sub R2 R3, #1
+        nop
+        ",
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        errors[0],
+        AssemblerError::Parser(ParserError::InvalidToken { idx: 1, .. })
+    ));
+    assert!(matches!(
+        errors[1],
+        AssemblerError::Parser(ParserError::InvalidToken { idx: 14, .. })
+    ));
+}
+
+#[test]
+fn processor_owning_its_program() {
+    type OwningProcessor = Processor<'static, 1024, Instruction<I32>, Vec<Instruction<I32>>, I32>;
+
+    fn ready_processor(n: i32) -> OwningProcessor {
+        // the source and the program are dropped or moved when the function returns
+        let source = format!("mov R0, #{n}\nmul R0, R0");
+        let program = assemble::<I32>(source).unwrap();
+        Processor::builder().with_owned_program(program).build()
+    }
+
+    let mut processor = ready_processor(7);
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 49.into());
+
+    let mut processor = OwningProcessor::new();
+    processor.load_program_owned(assemble::<I32>("mov R1, #3").unwrap());
+    assert_eq!(processor.program().map(|program| program.len()), Some(1));
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R1), 3.into());
+}
+
+#[test]
+fn shared_program_on_two_processors() {
+    use std::sync::Arc;
+
+    let instructions: Arc<[_]> = assemble::<I32>(
+        "
+        mov R0, #1
+        .loop
+        add R0, R0
+        jmp .loop
+        ",
+    )
+    .unwrap()
+    .to_vec()
+    .into();
+    let program = Program::from_arc(instructions);
+
+    let run = |steps| {
+        let program = program.clone();
+        std::thread::spawn(move || {
+            let mut processor = Processor::<16, _, _, _>::builder().with_owned_program(program).build();
+            processor.run_n(steps).unwrap();
+            processor.registers.get_reg(Register::R0)
+        })
+    };
+    let short = run(3);
+    let long = run(9);
+
+    assert_eq!(short.join().unwrap(), 2.into());
+    assert_eq!(long.join().unwrap(), 16.into());
+    assert!(std::ptr::eq(program.clone().as_ptr(), program.as_ptr()));
+}
+
+#[test]
+fn escaped_char_literals() {
+    let program = assemble::<I32>(
+        r"
+        mov R0, #'\n'
+        mov R1, #'\x41'
+        mov R2, #'\\'
+        ",
+    )
+    .unwrap();
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 10.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 65.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 92.into());
+}
+
+#[test]
+fn io_ports() {
+    let program = assemble::<I32>(
+        "
+        in R0, #1
+        add R0, #1
+        out #0, R0
+        out #0, #7
+        in R1, #2
+        ",
+    )
+    .unwrap();
+
+    let mut output = Vec::new();
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.connect_input(1.into(), |_| 41.into());
+    processor.connect_output(0.into(), |_, val| output.push(val));
+
+    assert_eq!(
+        processor.run_program(),
+        Err(ProgramError::UnconnectedPort { port: 2, pc: 4 })
+    );
+    assert_eq!(processor.registers.get_reg(Register::R0), 42.into());
+
+    drop(processor);
+    assert_eq!(output, [42.into(), 7.into()]);
+}
+
+#[test]
+fn io_ports_with_shared_host_state() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let program = assemble::<I32>("out #0, #1\nout #0, #2").unwrap();
+
+    // the handler does not need to be Send or Sync to accumulate into a Vec of the host
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    let sink = Rc::clone(&output);
+    processor.connect_output(0.into(), move |_, val| sink.borrow_mut().push(val));
+
+    processor.run_program().unwrap();
+    assert_eq!(*output.borrow(), [1.into(), 2.into()]);
+}
+
+#[test]
+fn lookup_table_in_data_section() {
+    use procem_default::assemble_with_metadata;
+
+    let assembled = assemble_with_metadata::<I32>(
+        "
+        .data
+        .mytable
+        .word #1, #2, #3
+        .text
+        ldr R0, .mytable + #1
+        mov R1, .mytable
+        ldr R2, [R1 + #2]
+        ",
+    )
+    .unwrap();
+    assert_eq!(assembled.data, [1.into(), 2.into(), 3.into()]);
+    assert_eq!(assembled.entry, 0);
+
+    let mut processor = Processor::<16, _, _, _>::builder()
+        .with_program(&assembled.program)
+        .with_stack_pointer(3.into())
+        .build();
+    processor.write_memory(0, &assembled.data).unwrap();
+    processor.run_program().unwrap();
+
+    assert_eq!(processor.registers.get_reg(Register::R0), 2.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 3.into());
+}
+
+#[test]
+fn pseudo_instructions_assemble_to_real_instructions() {
+    let program = assemble::<I32>(
+        "
+        mov R0, #7
+        tst R0
+        clrne R0
+        tst R0
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(program.len(), 4);
+    assert_eq!(program[2], assemble::<I32>("movne R0, #0").unwrap()[0]);
+
+    let mut processor = Processor::<16, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+    assert!(processor.registers.get_flag(Flag::Z));
+}
+
+#[test]
+fn run_source_returns_final_processor() {
+    use procem_default::run_source;
+
+    let processor = run_source::<16, I32>("mov R0, #5").unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 5.into());
+    assert_eq!(processor.registers.pc(), 1.into());
+
+    assert!(matches!(run_source::<16, I32>("mov R0"), Err(RunError::Assembler(_))));
+    assert_eq!(
+        run_source::<16, I32>("ret").err(),
+        Some(RunError::Program(ProgramError::ReturnWithoutCall { pc: 0 }))
+    );
+
+    let processor = run_source::<16, I32>(".data\n.table\n.word #3, #4\n.text\nldr R0, .table + #1\npush R0").unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 4.into());
+    assert_eq!(
+        processor.read_memory(0, 4),
+        Ok(&[3.into(), 4.into(), 0.into(), 4.into()][..])
+    );
+    assert!(matches!(
+        run_source::<2, I32>(".data\n.word #1, #2, #3\n.text\nnop"),
+        Err(RunError::Data(StackError::OutOfBounds { len: 3, .. }))
+    ));
+}
+
+#[test]
+fn optimized_program_behaves_the_same() {
+    use procem_default::optimize::Optimize;
+
+    let program = assemble::<I32>(
+        "
+        mov R0, #0
+        add R0, #10
+        nop
+        mov R1, #0
+        .loop
+        nop
+        add R1, R0
+        push R1
+        subs R0, #1
+        jrnz .loop
+        nop
+        call #12
+        jmp .finish
+        .double
+        nop
+        add R1, R1
+        ret
+        .finish
+        nop
+        ",
+    )
+    .unwrap();
+    let optimized = program.optimize();
+
+    assert_eq!(optimized.len(), program.len() - 6);
+    assert!(!optimized.contains(&Instruction::Nop));
+
+    let mut processor = Processor::<32, _, _, _>::builder().with_program(&program).build();
+    processor.run_program().unwrap();
+    let mut optimized_processor = Processor::<32, _, _, _>::builder().with_program(&optimized).build();
+    optimized_processor.run_program().unwrap();
+
+    assert_eq!(optimized_processor.registers.get_reg(Register::R1), 110.into());
+    assert_eq!(
+        optimized_processor.registers.get_reg(Register::R0),
+        processor.registers.get_reg(Register::R0)
+    );
+    assert_eq!(
+        optimized_processor.registers.get_reg(Register::R1),
+        processor.registers.get_reg(Register::R1)
+    );
+    // the return address of the call, which is pushed after the sums, is an address of the optimized program
+    for addr in 0..=10 {
+        assert_eq!(
+            optimized_processor.stack.read(addr.into()),
+            processor.stack.read(addr.into())
+        );
+    }
+    assert_eq!(optimized_processor.stack.read(11.into()), 7.into());
+}
+
+#[test]
+fn start_at_entry_point() {
+    use procem_default::{assemble_with_metadata, run_source};
+
+    let src = "
+        .entry .main
+        .data
+        .value
+        .word #7
+        .text
+        mov R0, #1
+        mov R1, #2
+        .main
+        ldr R2, .value
+        ";
+    let assembled = assemble_with_metadata::<I32>(src).unwrap();
+    assert_eq!(assembled.entry, 2);
+    assert_eq!(assembled.data, [7.into()]);
+
+    let processor = Processor::<16, _, _, _>::builder()
+        .with_program(&assembled.program)
+        .with_program_counter(2.into())
+        .build();
+    assert_eq!(processor.registers.pc(), 2.into());
+
+    // the first two instructions are skipped and the data is written to the stack
+    let mut processor = run_source::<16, I32>(src).unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R1), 0.into());
+    assert_eq!(processor.registers.get_reg(Register::R2), 7.into());
+    assert_eq!(processor.registers.sp(), 1.into());
+    assert_eq!(processor.registers.pc(), 3.into());
+
+    // without an entry point the program starts at its first instruction
+    processor = run_source::<16, I32>("mov R0, #1\nmov R1, #2").unwrap();
+    assert_eq!(processor.registers.get_reg(Register::R0), 1.into());
+}